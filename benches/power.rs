@@ -0,0 +1,72 @@
+// Compares the exponentiation methods in monoid::PowerOptions.  Run with
+//     cargo bench --bench power
+use number_theory::number_theory::monoid::{Exponent, PowerMethod, PowerOptions};
+use number_theory::number_theory::power_mod::power_with_exponent;
+use std::time::Instant;
+
+fn bench<E: Exponent + Copy>(
+    name: &str,
+    method: PowerMethod,
+    bases: &[i128],
+    exp: E,
+    modulo: i128,
+    rounds: u32,
+) {
+    let options = PowerOptions { method };
+    let start = Instant::now();
+    let mut check: i128 = 0;
+    for _ in 0..rounds {
+        for &base in bases {
+            check += power_with_exponent(base, exp, modulo, &options)
+                .unwrap_or_else(|_| panic!("Failed!"));
+        }
+    }
+    let per_call = start.elapsed() / (rounds * bases.len() as u32);
+    println!("{:<20} {:>10?} per call (check {})", name, per_call, check);
+}
+
+// Limbs of a 2048 bit exponent with the top bit set as in an RSA private exponent
+fn rsa_sized() -> Vec<u64> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut limbs: Vec<u64> = (0..32)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        })
+        .collect();
+    limbs[31] |= 1 << 63;
+    limbs
+}
+
+fn main() {
+    // Largest prime below 2^62 so that products of residues fit in an i128
+    let modulo: i128 = 4611686018427387847;
+    let bases: Vec<i128> = (0..16).map(|i| 1234567890123 + 977 * i).collect();
+    let exps = [
+        ("dense", i128::MAX),
+        ("sparse", (1 << 126) + 1),
+        ("random", 0x5a0f_93c1_77e2_0b4d_e8a6_1f3c_9d24_7b61),
+    ];
+    let methods = [
+        ("binary", PowerMethod::Binary),
+        ("fixed window 4", PowerMethod::FixedWindow(4)),
+        ("fixed window 5", PowerMethod::FixedWindow(5)),
+        ("sliding window 4", PowerMethod::SlidingWindow(4)),
+        ("sliding window 5", PowerMethod::SlidingWindow(5)),
+        ("naf", PowerMethod::Naf),
+    ];
+    for (exp_name, exp) in exps.iter() {
+        println!("{} exponent", exp_name);
+        for (name, method) in methods.iter() {
+            bench(name, *method, &bases, *exp, modulo, 2000);
+        }
+    }
+    let limbs = rsa_sized();
+    let limbs: &[u64] = &limbs;
+    println!("2048 bit exponent");
+    for (name, method) in methods.iter() {
+        bench(name, *method, &bases, limbs, modulo, 100);
+    }
+}
@@ -1,13 +1,29 @@
 pub mod number_theory {
+    pub mod continued_fraction;
+    pub mod elliptic;
     pub mod euclidean;
+    pub mod factorization;
+    pub mod finite_field;
+    pub mod gaussian;
+    pub mod monoid;
+    pub mod ntt;
+    pub mod pell;
+    pub mod poly;
     pub mod power_mod;
+    pub mod quadratic_forms;
+    pub mod rational;
+    pub mod sqrt_mod;
+    pub mod squares;
+    pub mod totient;
 }
 
 pub mod utilities {
+    pub mod bit_ops;
     pub mod nt_error;
-    pub mod u_to_i;
     pub(crate) mod numeric_trait;
-    pub(crate) mod utilities;
+    pub mod u_to_i;
+    #[allow(clippy::module_inception)]
+    pub mod utilities;
 }
 
 #[cfg(test)]
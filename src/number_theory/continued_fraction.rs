@@ -0,0 +1,332 @@
+use crate::number_theory::euclidean::ExtMatrix;
+use crate::utilities::nt_error;
+use crate::utilities::numeric_trait::Numeric;
+use crate::utilities::utilities;
+
+// Largest integer <= num / den for den != 0
+pub(crate) fn floor_div<T: Numeric>(num: T, den: T) -> T {
+    let q = num / den;
+    if (num % den != T::zero()) && ((num < T::zero()) != (den < T::zero())) {
+        q - T::one()
+    } else {
+        q
+    }
+}
+
+fn checked_mul_add<T: Numeric>(a: T, b: T, c: T) -> Result<T, nt_error::NtError> {
+    a.checked_mul(&b)
+        .and_then(|ab| ab.checked_add(&c))
+        .ok_or(nt_error::NtError::Overflow)
+}
+
+/**
+Returns the partial quotients of the continued fraction of p / q
+
+# Arguments
+
+* `p`, `q` - Numerator and nonzero denominator
+
+# Returns
+* Vector [a0; a1, a2, ...] with a0 = floor(p / q) and all later terms positive.  These are the
+  quotients produced by the extended Euclidean algorithm.  BadArgument if q is zero.
+
+# Examples
+
+```
+use number_theory::number_theory::continued_fraction;
+
+let quotients = continued_fraction::partial_quotients(415, 93)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(quotients, vec![4, 2, 6, 7]);
+```
+*/
+pub fn partial_quotients<T: Numeric>(p: T, q: T) -> Result<Vec<T>, nt_error::NtError> {
+    if q == T::zero() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let (p, q) = if q < T::zero() { (-p, -q) } else { (p, q) };
+    let a0 = floor_div(p, q);
+    let mut ret = vec![a0];
+
+    // Everything after a0 is the expansion of q / r with 0 <= r < q
+    let r = p - a0 * q;
+    if r != T::zero() {
+        let mut mtx = ExtMatrix::new(q, r);
+        while !mtx.finished() {
+            ret.push(mtx.step());
+        }
+    }
+    Ok(ret)
+}
+
+/**
+Iterator over the convergents of a continued fraction
+
+Yields each convergent h / k as the pair (h, k) with k > 0.  Overflow is yielded once if a
+convergent doesn't fit in T after which the iterator is finished.
+
+# Examples
+
+```
+use number_theory::number_theory::continued_fraction::Convergents;
+
+let convergents: Vec<(i64, i64)> = Convergents::new(&[4, 2, 6, 7])
+    .map(|c| c.unwrap_or_else(|_| panic!("Failed!")))
+    .collect();
+assert_eq!(convergents, vec![(4, 1), (9, 2), (58, 13), (415, 93)]);
+```
+*/
+pub struct Convergents<'a, T: Numeric> {
+    quotients: &'a [T],
+    idx: usize,
+    // (h_{n-2}, h_{n-1}) and (k_{n-2}, k_{n-1})
+    h: (T, T),
+    k: (T, T),
+}
+
+impl<'a, T: Numeric> Convergents<'a, T> {
+    pub fn new(quotients: &'a [T]) -> Convergents<'a, T> {
+        Convergents {
+            quotients,
+            idx: 0,
+            h: (T::zero(), T::one()),
+            k: (T::one(), T::zero()),
+        }
+    }
+}
+
+impl<T: Numeric> Iterator for Convergents<'_, T> {
+    type Item = Result<(T, T), nt_error::NtError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let a = *self.quotients.get(self.idx)?;
+        let next = checked_mul_add(a, self.h.1, self.h.0)
+            .and_then(|h| Ok((h, checked_mul_add(a, self.k.1, self.k.0)?)));
+        match next {
+            Ok((h, k)) => {
+                self.idx += 1;
+                self.h = (self.h.1, h);
+                self.k = (self.k.1, k);
+                Some(Ok((h, k)))
+            }
+            Err(e) => {
+                self.idx = self.quotients.len();
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/**
+Iterator over the semiconvergents of a continued fraction
+
+Yields (h_{n-2} + m h_{n-1}, k_{n-2} + m k_{n-1}) for m = 1..=a_n for each n in order of
+increasing denominator.  m = a_n is the convergent itself so the convergents are all included.
+Every best rational approximation is among these.  Overflow is handled as in Convergents.
+
+# Examples
+
+```
+use number_theory::number_theory::continued_fraction::Semiconvergents;
+
+let semis: Vec<(i64, i64)> = Semiconvergents::new(&[0, 2, 3])
+    .map(|c| c.unwrap_or_else(|_| panic!("Failed!")))
+    .collect();
+assert_eq!(semis, vec![(0, 1), (1, 1), (1, 2), (1, 3), (2, 5), (3, 7)]);
+```
+*/
+pub struct Semiconvergents<'a, T: Numeric> {
+    quotients: &'a [T],
+    idx: usize,
+    // Multiplier of the current partial quotient to use next
+    m: T,
+    h: (T, T),
+    k: (T, T),
+}
+
+impl<'a, T: Numeric> Semiconvergents<'a, T> {
+    pub fn new(quotients: &'a [T]) -> Semiconvergents<'a, T> {
+        Semiconvergents {
+            quotients,
+            idx: 0,
+            m: T::one(),
+            h: (T::zero(), T::one()),
+            k: (T::one(), T::zero()),
+        }
+    }
+}
+
+impl<T: Numeric> Iterator for Semiconvergents<'_, T> {
+    type Item = Result<(T, T), nt_error::NtError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let a = *self.quotients.get(self.idx)?;
+        // a0 may be zero or negative and only contributes its convergent
+        let m = if self.idx == 0 { a } else { self.m };
+        let next = checked_mul_add(m, self.h.1, self.h.0)
+            .and_then(|h| Ok((h, checked_mul_add(m, self.k.1, self.k.0)?)));
+        match next {
+            Ok((h, k)) => {
+                if m == a {
+                    self.idx += 1;
+                    self.m = T::one();
+                    self.h = (self.h.1, h);
+                    self.k = (self.k.1, k);
+                } else {
+                    self.m += T::one();
+                }
+                Some(Ok((h, k)))
+            }
+            Err(e) => {
+                self.idx = self.quotients.len();
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/**
+Returns the best rational approximation to p / q with a bounded denominator
+
+# Arguments
+
+* `p`, `q` - Numerator and nonzero denominator of the value to approximate
+* `max_den` - Largest allowed denominator - must be positive
+
+# Returns
+* (h, k) with 0 < k <= max_den minimizing |h / k - p / q|.  Ties go to the smaller denominator.
+  p / q itself in lowest terms if its denominator is small enough.  BadArgument if q is zero or
+  max_den isn't positive, Overflow if the comparison needs products that don't fit in T.
+
+# Examples
+
+```
+use number_theory::number_theory::continued_fraction;
+
+// Best approximations to pi = 3.14159265...
+let pi = (314159265, 100000000);
+assert_eq!(continued_fraction::best_approximation(pi.0, pi.1, 10), Ok((22, 7)));
+assert_eq!(continued_fraction::best_approximation(pi.0, pi.1, 200), Ok((355, 113)));
+```
+*/
+pub fn best_approximation<T: Numeric>(p: T, q: T, max_den: T) -> Result<(T, T), nt_error::NtError> {
+    if max_den <= T::zero() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let quotients = partial_quotients(p, q)?;
+    let (p, q) = if q < T::zero() { (-p, -q) } else { (p, q) };
+
+    // (h_{-2}, k_{-2}) and (h_{-1}, k_{-1}) so the first convergent is always accepted
+    let mut prev = (T::zero(), T::one());
+    let mut last = (T::one(), T::zero());
+    for (idx, convergent) in Convergents::new(&quotients).enumerate() {
+        let convergent = convergent?;
+        if convergent.1 > max_den {
+            // Largest semiconvergent between last and convergent that's still allowed
+            let m = (max_den - prev.1) / last.1;
+            let semi = (prev.0 + m * last.0, prev.1 + m * last.1);
+            // Only when m is exactly half the partial quotient does the tail decide
+            let two_m = m * T::from_u8(2).unwrap();
+            if two_m < quotients[idx] {
+                return Ok(last);
+            } else if two_m > quotients[idx] {
+                return Ok(semi);
+            }
+            // Compare |semi - p/q| against |last - p/q| by cross multiplying
+            let dist = |(h, k): (T, T)| -> Result<T, nt_error::NtError> {
+                let hq = h.checked_mul(&q).ok_or(nt_error::NtError::Overflow)?;
+                let pk = p.checked_mul(&k).ok_or(nt_error::NtError::Overflow)?;
+                Ok(num::abs(hq - pk))
+            };
+            let semi_err = dist(semi)?.checked_mul(&last.1);
+            let last_err = dist(last)?.checked_mul(&semi.1);
+            return match (semi_err, last_err) {
+                (Some(semi_err), Some(last_err)) => {
+                    Ok(if semi_err < last_err { semi } else { last })
+                }
+                _ => Err(nt_error::NtError::Overflow),
+            };
+        }
+        prev = last;
+        last = convergent;
+    }
+    Ok(last)
+}
+
+/**
+Returns the periodic continued fraction of a quadratic irrational (P + sqrt(D)) / Q
+
+# Arguments
+
+* `p`, `d`, `q` - P, D and Q.  D must be positive and not a perfect square and Q nonzero.
+
+# Returns
+* (preperiod, period) where the expansion is preperiod followed by period repeated forever.
+  BadArgument for a bad D or Q and Overflow if the intermediate values don't fit in T.
+
+# Examples
+
+```
+use number_theory::number_theory::continued_fraction;
+
+// sqrt(7) = [2; 1, 1, 1, 4, 1, 1, 1, 4, ...]
+let (pre, period) = continued_fraction::quadratic_irrational(0, 7, 1)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(pre, vec![2]);
+assert_eq!(period, vec![1, 1, 1, 4]);
+```
+*/
+#[allow(clippy::type_complexity)]
+pub fn quadratic_irrational<T: Numeric>(
+    p: T,
+    d: T,
+    q: T,
+) -> Result<(Vec<T>, Vec<T>), nt_error::NtError> {
+    if d <= T::zero() || q == T::zero() || utilities::is_square(d) {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let overflow = || nt_error::NtError::Overflow;
+
+    // The recurrence needs Q | D - P^2 so scale everything by |Q| if it doesn't
+    let p_sq = p.checked_mul(&p).ok_or_else(overflow)?;
+    let (mut p, d, mut q) = if (d - p_sq) % q == T::zero() {
+        (p, d, q)
+    } else {
+        let abs_q = num::abs(q);
+        let q_sq = q.checked_mul(&q).ok_or_else(overflow)?;
+        (
+            p.checked_mul(&abs_q).ok_or_else(overflow)?,
+            d.checked_mul(&q_sq).ok_or_else(overflow)?,
+            q.checked_mul(&abs_q).ok_or_else(overflow)?,
+        )
+    };
+    let s = utilities::isqrt(d)?;
+
+    // Once (P + sqrt(D)) / Q is reduced (> 1 with conjugate in (-1, 0)) every later complete
+    // quotient is too and the expansion is purely periodic from there
+    let is_reduced = |p: T, q: T| p > T::zero() && p <= s && q > s - p && q <= s + p;
+
+    let mut quotients = Vec::new();
+    let mut first_reduced: Option<(usize, T, T)> = None;
+    loop {
+        match first_reduced {
+            Some((idx, p0, q0)) if p == p0 && q == q0 => {
+                let period = quotients.split_off(idx);
+                return Ok((quotients, period));
+            }
+            None if is_reduced(p, q) => first_reduced = Some((quotients.len(), p, q)),
+            _ => (),
+        }
+
+        // floor((P + sqrt(D)) / Q) - sqrt(D) is irrational so it lies strictly between s and s + 1
+        let a = if q > T::zero() {
+            floor_div(p + s, q)
+        } else {
+            floor_div(p + s + T::one(), q)
+        };
+        quotients.push(a);
+        p = a.checked_mul(&q).ok_or_else(overflow)? - p;
+        let p_sq = p.checked_mul(&p).ok_or_else(overflow)?;
+        q = (d - p_sq) / q;
+    }
+}
@@ -0,0 +1,1078 @@
+use crate::number_theory::euclidean::{self, EuclideanDomain};
+use crate::number_theory::monoid::{pow_monoid, Monoid};
+use crate::number_theory::power_mod;
+use crate::utilities::nt_error;
+use crate::utilities::numeric_trait::Numeric;
+
+// Largest window scalar_mul_wnaf will build a table for
+const MAX_WNAF_WIDTH: u32 = 16;
+
+// -k for negative scalars k - Overflow for the most negative value of T
+fn negate_scalar<T: Numeric>(k: T) -> Result<T, nt_error::NtError> {
+    T::zero().checked_sub(&k).ok_or(nt_error::NtError::Overflow)
+}
+
+/**
+Point on an elliptic curve in affine coordinates
+
+The point at infinity is the group identity.  Points don't carry their curve - pass them to the
+curve's methods.
+*/
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Point<T: Numeric> {
+    Infinity,
+    Affine(T, T),
+}
+
+impl<T: Numeric> Point<T> {
+    pub fn is_infinity(&self) -> bool {
+        *self == Point::Infinity
+    }
+}
+
+/// Point (X : Y : Z) in homogeneous projective coordinates standing for (X / Z, Y / Z).  Z = 0 at
+/// infinity.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ProjectivePoint<T: Numeric> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+/// Point (X : Y : Z) in Jacobian coordinates standing for (X / Z^2, Y / Z^3).  Z = 0 at infinity.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct JacobianPoint<T: Numeric> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+/// Point (X : Z) on a Montgomery curve with only the x coordinate X / Z kept.  Z = 0 at infinity.
+/// P and -P are the same XZPoint.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct XZPoint<T: Numeric> {
+    pub x: T,
+    pub z: T,
+}
+
+// Arithmetic mod p for values already in [0, p) - 2p is checked to fit when a curve is made.
+// Products fall back on power_mod's doubling multiplication when p^2 doesn't fit so composite
+// moduli up to half of T's range work for factoring.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Field<T: Numeric> {
+    p: T,
+    wide: bool,
+}
+
+impl<T: Numeric> Field<T> {
+    fn new(p: T) -> Result<Field<T>, nt_error::NtError> {
+        if p < T::from_u8(3).unwrap() {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        if p.checked_add(&p).is_none() {
+            return Err(nt_error::NtError::Overflow);
+        }
+        let wide = p.checked_mul(&p).is_none();
+        Ok(Field { p, wide })
+    }
+
+    fn reduce(&self, a: T) -> T {
+        a.div_rem(&self.p).1
+    }
+
+    fn small(&self, c: u8) -> T {
+        self.reduce(T::from_u8(c).unwrap())
+    }
+
+    fn add(&self, a: T, b: T) -> T {
+        (a + b) % self.p
+    }
+
+    fn sub(&self, a: T, b: T) -> T {
+        (a + self.p - b) % self.p
+    }
+
+    fn neg(&self, a: T) -> T {
+        (self.p - a) % self.p
+    }
+
+    fn mul(&self, a: T, b: T) -> T {
+        if self.wide {
+            power_mod::mul_mod_ct_reduced(a, b, self.p)
+        } else {
+            a * b % self.p
+        }
+    }
+
+    fn sqr(&self, a: T) -> T {
+        self.mul(a, a)
+    }
+
+    fn inv(&self, a: T) -> Result<T, nt_error::NtError> {
+        euclidean::inverse_mod(a, self.p)
+    }
+
+    // a^e for e >= 0 by square and multiply
+    fn pow(&self, a: T, e: T) -> T {
+        let mut res = T::one();
+        for bit in (0..e.bit_length().unwrap()).rev() {
+            res = self.sqr(res);
+            if ((e >> bit as usize) & T::one()) == T::one() {
+                res = self.mul(res, a);
+            }
+        }
+        res
+    }
+
+    // A square root of a in [0, p) by Tonelli-Shanks as in sqrt_mod::sqrt_mod_prime but with the
+    // field's multiplication so that wide p works.  NoSolns if a isn't a square and BadArgument if
+    // p turns out not to be prime.
+    fn sqrt(&self, a: T) -> Result<T, nt_error::NtError> {
+        let (one, p) = (T::one(), self.p);
+        if a == T::zero() {
+            return Ok(a);
+        }
+        // Euler's criterion
+        let half = (p - one) >> 1;
+        if self.pow(a, half) != one {
+            return Err(nt_error::NtError::NoSolns);
+        }
+
+        // p - 1 = q 2^s with q odd
+        let mut q = p - one;
+        let mut s = 0u32;
+        while (q & one) == T::zero() {
+            q = q >> 1;
+            s += 1;
+        }
+        if s == 1 {
+            // p = 3 (mod 4) and 2p fits so p + 1 does
+            return Ok(self.pow(a, (p + one) >> 2));
+        }
+
+        // A non-residue z gives a generator c of the 2-Sylow subgroup
+        let mut z = self.small(2);
+        while self.pow(z, half) != p - one {
+            z += one;
+            if z == p {
+                return Err(nt_error::NtError::BadArgument);
+            }
+        }
+        let mut m = s;
+        let mut c = self.pow(z, q);
+        let mut t = self.pow(a, q);
+        let mut x = self.pow(a, (q + one) >> 1);
+
+        // Invariant: x^2 = a t with t of order 2^i for some i < m
+        while t != one {
+            let mut i = 0u32;
+            let mut t_pow = t;
+            while t_pow != one {
+                t_pow = self.sqr(t_pow);
+                i += 1;
+                if i == m {
+                    return Err(nt_error::NtError::BadArgument);
+                }
+            }
+            let mut b = c;
+            for _ in 0..(m - i - 1) {
+                b = self.sqr(b);
+            }
+            m = i;
+            c = self.sqr(b);
+            t = self.mul(t, c);
+            x = self.mul(x, b);
+        }
+        Ok(x)
+    }
+}
+
+// Bits of a nonnegative k from the top
+fn bits_from_top<T: Numeric>(k: T) -> Result<Vec<bool>, nt_error::NtError> {
+    Ok((0..k.bit_length()?)
+        .rev()
+        .map(|bit| ((k >> bit as usize) & T::one()) != T::zero())
+        .collect())
+}
+
+/**
+Short Weierstrass curve y^2 = x^3 + a x + b over the integers mod a prime p > 3
+
+The affine operations divide by inverse_mod so with a composite p they report NoSolns exactly
+when a denominator shares a factor with p.  Projective and Jacobian coordinates avoid the
+division until the end.  Primality of p isn't checked.
+
+# Examples
+
+```
+use number_theory::number_theory::elliptic::{Point, WeierstrassCurve};
+
+// y^2 = x^3 + 2x + 3 over F_97
+let curve = WeierstrassCurve::new(2, 3, 97).unwrap_or_else(|_| panic!("Failed!"));
+let p = curve.point(3, 6).unwrap_or_else(|_| panic!("Failed!"));
+let double = curve.double(&p).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(double, Point::Affine(80, 10));
+assert_eq!(curve.scalar_mul(5, &p), Ok(Point::Infinity));
+```
+*/
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WeierstrassCurve<T: Numeric> {
+    a: T,
+    b: T,
+    field: Field<T>,
+}
+
+impl<T: Numeric> WeierstrassCurve<T> {
+    /**
+    Creates the curve y^2 = x^3 + a x + b mod p
+
+    # Returns
+    * The curve with a and b reduced mod p.  BadArgument if p < 5 or the curve is singular
+      (4a^3 + 27b^2 = 0 mod p) and Overflow if 2p doesn't fit in T.
+    */
+    pub fn new(a: T, b: T, p: T) -> Result<WeierstrassCurve<T>, nt_error::NtError> {
+        if p < T::from_u8(5).unwrap() {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        let field = Field::new(p)?;
+        let (a, b) = (field.reduce(a), field.reduce(b));
+        let disc = field.add(
+            field.mul(field.small(4), field.mul(a, field.sqr(a))),
+            field.mul(field.small(27), field.sqr(b)),
+        );
+        if disc == T::zero() {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        Ok(WeierstrassCurve { a, b, field })
+    }
+
+    pub fn a(&self) -> T {
+        self.a
+    }
+
+    pub fn b(&self) -> T {
+        self.b
+    }
+
+    pub fn modulus(&self) -> T {
+        self.field.p
+    }
+
+    // x^3 + a x + b
+    fn rhs(&self, x: T) -> T {
+        let f = &self.field;
+        f.add(f.mul(f.add(f.sqr(x), self.a), x), self.b)
+    }
+
+    /**
+    Returns whether a point is valid - infinity or reduced coordinates satisfying the equation
+    */
+    pub fn is_on_curve(&self, point: &Point<T>) -> bool {
+        match *point {
+            Point::Infinity => true,
+            Point::Affine(x, y) => {
+                let range = T::zero()..self.field.p;
+                range.contains(&x) && range.contains(&y) && self.field.sqr(y) == self.rhs(x)
+            }
+        }
+    }
+
+    /**
+    Returns the affine point (x, y) after validating it
+
+    # Returns
+    * The point with x and y reduced mod p.  BadArgument if it isn't on the curve.
+    */
+    pub fn point(&self, x: T, y: T) -> Result<Point<T>, nt_error::NtError> {
+        let point = Point::Affine(self.field.reduce(x), self.field.reduce(y));
+        if !self.is_on_curve(&point) {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        Ok(point)
+    }
+
+    /**
+    Returns the compressed form of a point - x and the parity of y - or None for infinity
+    */
+    pub fn compress(&self, point: &Point<T>) -> Option<(T, bool)> {
+        match *point {
+            Point::Infinity => None,
+            Point::Affine(x, y) => Some((x, (y & T::one()) == T::one())),
+        }
+    }
+
+    /**
+    Returns the point with a given x coordinate and parity of y
+
+    # Arguments
+
+    * `x` - x coordinate
+    * `y_odd` - Whether y in [0, p) should be odd
+
+    # Returns
+    * The point (x, y) with y a square root of x^3 + a x + b by Tonelli-Shanks, or p - y for the
+      other parity.  This works for every p the curve accepts, including those whose square
+      doesn't fit in T.  NoSolns if x^3 + a x + b isn't a square or is zero and y_odd is
+      set.  BadArgument if p is detected not to be prime.
+    */
+    pub fn decompress(&self, x: T, y_odd: bool) -> Result<Point<T>, nt_error::NtError> {
+        let x = self.field.reduce(x);
+        let mut y = self.field.sqrt(self.rhs(x))?;
+        if ((y & T::one()) == T::one()) != y_odd {
+            if y == T::zero() {
+                return Err(nt_error::NtError::NoSolns);
+            }
+            y = self.field.p - y;
+        }
+        Ok(Point::Affine(x, y))
+    }
+
+    pub fn negate(&self, point: &Point<T>) -> Point<T> {
+        match *point {
+            Point::Infinity => Point::Infinity,
+            Point::Affine(x, y) => Point::Affine(x, self.field.neg(y)),
+        }
+    }
+
+    /**
+    Returns P + Q by the chord and tangent construction in affine coordinates
+
+    # Returns
+    * The sum - one inverse_mod per addition whose NoSolns is passed through.
+    */
+    pub fn add(&self, p: &Point<T>, q: &Point<T>) -> Result<Point<T>, nt_error::NtError> {
+        let f = &self.field;
+        let ((x1, y1), (x2, y2)) = match (*p, *q) {
+            (Point::Infinity, _) => return Ok(*q),
+            (_, Point::Infinity) => return Ok(*p),
+            (Point::Affine(x1, y1), Point::Affine(x2, y2)) => ((x1, y1), (x2, y2)),
+        };
+        let slope = if x1 == x2 {
+            if f.add(y1, y2) == T::zero() {
+                return Ok(Point::Infinity);
+            }
+            // Tangent (3x^2 + a) / 2y
+            let num = f.add(f.mul(f.small(3), f.sqr(x1)), self.a);
+            f.mul(num, f.inv(f.add(y1, y1))?)
+        } else {
+            f.mul(f.sub(y2, y1), f.inv(f.sub(x2, x1))?)
+        };
+        let x3 = f.sub(f.sub(f.sqr(slope), x1), x2);
+        let y3 = f.sub(f.mul(slope, f.sub(x1, x3)), y1);
+        Ok(Point::Affine(x3, y3))
+    }
+
+    pub fn double(&self, p: &Point<T>) -> Result<Point<T>, nt_error::NtError> {
+        self.add(p, p)
+    }
+
+    pub fn to_projective(&self, p: &Point<T>) -> ProjectivePoint<T> {
+        match *p {
+            Point::Infinity => ProjectivePoint {
+                x: T::zero(),
+                y: T::one(),
+                z: T::zero(),
+            },
+            Point::Affine(x, y) => ProjectivePoint { x, y, z: T::one() },
+        }
+    }
+
+    /// Affine form of a projective point - NoSolns if Z isn't invertible
+    pub fn from_projective(&self, p: &ProjectivePoint<T>) -> Result<Point<T>, nt_error::NtError> {
+        if p.z == T::zero() {
+            return Ok(Point::Infinity);
+        }
+        let z_inv = self.field.inv(p.z)?;
+        Ok(Point::Affine(
+            self.field.mul(p.x, z_inv),
+            self.field.mul(p.y, z_inv),
+        ))
+    }
+
+    /**
+    Returns P + Q in projective coordinates without inversions
+    */
+    pub fn add_projective(
+        &self,
+        p: &ProjectivePoint<T>,
+        q: &ProjectivePoint<T>,
+    ) -> ProjectivePoint<T> {
+        let f = &self.field;
+        if p.z == T::zero() {
+            return *q;
+        }
+        if q.z == T::zero() {
+            return *p;
+        }
+        let y1z2 = f.mul(p.y, q.z);
+        let x1z2 = f.mul(p.x, q.z);
+        let z1z2 = f.mul(p.z, q.z);
+        let u = f.sub(f.mul(q.y, p.z), y1z2);
+        let v = f.sub(f.mul(q.x, p.z), x1z2);
+        if v == T::zero() {
+            return if u == T::zero() {
+                self.double_projective(p)
+            } else {
+                self.to_projective(&Point::Infinity)
+            };
+        }
+        let vv = f.sqr(v);
+        let vvv = f.mul(v, vv);
+        let r = f.mul(vv, x1z2);
+        let a = f.sub(f.sub(f.mul(f.sqr(u), z1z2), vvv), f.add(r, r));
+        ProjectivePoint {
+            x: f.mul(v, a),
+            y: f.sub(f.mul(u, f.sub(r, a)), f.mul(vvv, y1z2)),
+            z: f.mul(vvv, z1z2),
+        }
+    }
+
+    /**
+    Returns 2P in projective coordinates without inversions
+    */
+    pub fn double_projective(&self, p: &ProjectivePoint<T>) -> ProjectivePoint<T> {
+        let f = &self.field;
+        if p.z == T::zero() || p.y == T::zero() {
+            return self.to_projective(&Point::Infinity);
+        }
+        let w = f.add(f.mul(self.a, f.sqr(p.z)), f.mul(f.small(3), f.sqr(p.x)));
+        let s = f.mul(p.y, p.z);
+        let b = f.mul(f.mul(p.x, p.y), s);
+        let h = f.sub(f.sqr(w), f.mul(f.small(8), b));
+        let s2 = f.sqr(s);
+        ProjectivePoint {
+            x: f.mul(f.small(2), f.mul(h, s)),
+            y: f.sub(
+                f.mul(w, f.sub(f.mul(f.small(4), b), h)),
+                f.mul(f.small(8), f.mul(f.sqr(p.y), s2)),
+            ),
+            z: f.mul(f.small(8), f.mul(s2, s)),
+        }
+    }
+
+    pub fn to_jacobian(&self, p: &Point<T>) -> JacobianPoint<T> {
+        match *p {
+            Point::Infinity => JacobianPoint {
+                x: T::one(),
+                y: T::one(),
+                z: T::zero(),
+            },
+            Point::Affine(x, y) => JacobianPoint { x, y, z: T::one() },
+        }
+    }
+
+    /// Affine form of a Jacobian point - NoSolns if Z isn't invertible
+    pub fn from_jacobian(&self, p: &JacobianPoint<T>) -> Result<Point<T>, nt_error::NtError> {
+        let f = &self.field;
+        if p.z == T::zero() {
+            return Ok(Point::Infinity);
+        }
+        let z_inv = f.inv(p.z)?;
+        let z_inv2 = f.sqr(z_inv);
+        Ok(Point::Affine(
+            f.mul(p.x, z_inv2),
+            f.mul(p.y, f.mul(z_inv2, z_inv)),
+        ))
+    }
+
+    /**
+    Returns P + Q in Jacobian coordinates without inversions
+    */
+    pub fn add_jacobian(&self, p: &JacobianPoint<T>, q: &JacobianPoint<T>) -> JacobianPoint<T> {
+        let f = &self.field;
+        if p.z == T::zero() {
+            return *q;
+        }
+        if q.z == T::zero() {
+            return *p;
+        }
+        let z1z1 = f.sqr(p.z);
+        let z2z2 = f.sqr(q.z);
+        let u1 = f.mul(p.x, z2z2);
+        let u2 = f.mul(q.x, z1z1);
+        let s1 = f.mul(p.y, f.mul(q.z, z2z2));
+        let s2 = f.mul(q.y, f.mul(p.z, z1z1));
+        let h = f.sub(u2, u1);
+        let r = f.sub(s2, s1);
+        if h == T::zero() {
+            return if r == T::zero() {
+                self.double_jacobian(p)
+            } else {
+                self.to_jacobian(&Point::Infinity)
+            };
+        }
+        let hh = f.sqr(h);
+        let hhh = f.mul(h, hh);
+        let v = f.mul(u1, hh);
+        let x3 = f.sub(f.sub(f.sqr(r), hhh), f.add(v, v));
+        JacobianPoint {
+            x: x3,
+            y: f.sub(f.mul(r, f.sub(v, x3)), f.mul(s1, hhh)),
+            z: f.mul(h, f.mul(p.z, q.z)),
+        }
+    }
+
+    /**
+    Returns 2P in Jacobian coordinates without inversions
+    */
+    pub fn double_jacobian(&self, p: &JacobianPoint<T>) -> JacobianPoint<T> {
+        let f = &self.field;
+        if p.z == T::zero() || p.y == T::zero() {
+            return self.to_jacobian(&Point::Infinity);
+        }
+        let yy = f.sqr(p.y);
+        let s = f.mul(f.small(4), f.mul(p.x, yy));
+        let m = f.add(
+            f.mul(f.small(3), f.sqr(p.x)),
+            f.mul(self.a, f.sqr(f.sqr(p.z))),
+        );
+        let x3 = f.sub(f.sqr(m), f.add(s, s));
+        JacobianPoint {
+            x: x3,
+            y: f.sub(f.mul(m, f.sub(s, x3)), f.mul(f.small(8), f.sqr(yy))),
+            z: f.mul(f.small(2), f.mul(p.y, p.z)),
+        }
+    }
+
+    fn negate_jacobian(&self, p: &JacobianPoint<T>) -> JacobianPoint<T> {
+        JacobianPoint {
+            x: p.x,
+            y: self.field.neg(p.y),
+            z: p.z,
+        }
+    }
+
+    // (|k|, P or -P) so that k P = |k| (±P) - Overflow if |k| doesn't fit in T
+    fn signed(&self, k: T, p: &Point<T>) -> Result<(T, JacobianPoint<T>), nt_error::NtError> {
+        if k < T::zero() {
+            Ok((negate_scalar(k)?, self.to_jacobian(&self.negate(p))))
+        } else {
+            Ok((k, self.to_jacobian(p)))
+        }
+    }
+
+    /**
+    Returns k P by double-and-add
+
+    # Arguments
+
+    * `k` - Any scalar - negative values multiply -P
+    * `p` - Point on the curve
+
+    # Returns
+    * k P from a left to right double-and-add in Jacobian coordinates with a single inversion at
+      the end.  NoSolns if that inversion fails and Overflow if k is the most negative value of T.
+    */
+    pub fn scalar_mul(&self, k: T, p: &Point<T>) -> Result<Point<T>, nt_error::NtError> {
+        let (k, point) = self.signed(k, p)?;
+        let base = WeierstrassElement {
+            curve: *self,
+            point,
+        };
+        self.from_jacobian(&pow_monoid(&base, k)?.point)
+    }
+
+    /**
+    Returns k P using the width-w non-adjacent form of k
+
+    # Arguments
+
+    * `k` - Any scalar - negative values multiply -P
+    * `p` - Point on the curve
+    * `width` - Window width in [2, 16]
+
+    # Returns
+    * k P with k written in signed digits that are zero or odd with absolute value below
+      2^(width - 1) and at most one nonzero in any width consecutive digits.  P, 3P, ...,
+      (2^(width - 1) - 1) P are precomputed and negated as needed since negation is free.  Width 2
+      is the ordinary NAF.  BadArgument for widths outside [2, 16], NoSolns if the final
+      inversion fails and Overflow if k is the most negative value of T.
+    */
+    pub fn scalar_mul_wnaf(
+        &self,
+        k: T,
+        p: &Point<T>,
+        width: u32,
+    ) -> Result<Point<T>, nt_error::NtError> {
+        if !(2..=MAX_WNAF_WIDTH).contains(&width) {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        let (mut k, base) = self.signed(k, p)?;
+
+        // Digits lowest first.  Reduce k mod 2^width into (-2^(width - 1), 2^(width - 1)).  When
+        // 2^width - 1 doesn't fit in T neither does any k >= 2^width so k is its own residue.
+        let window = 1i64 << width;
+        let mask = T::from_i64(window - 1);
+        let mut digits = Vec::new();
+        while k > T::zero() {
+            if (k & T::one()) == T::one() {
+                let low = mask.map_or(k, |mask| k & mask).to_i64().unwrap();
+                let digit = if low >= window / 2 { low - window } else { low };
+                // k and digit are odd so (k - digit) / 2 = (k >> 1) - (digit >> 1), which unlike
+                // k - digit can't overflow.  |digit| <= low <= k so digit fits in T.
+                k = (k >> 1) - T::from_i64(digit >> 1).unwrap();
+                digits.push(digit);
+            } else {
+                k = k >> 1;
+                digits.push(0);
+            }
+        }
+
+        // table[i] = (2i + 1) P
+        let twice = self.double_jacobian(&base);
+        let mut table = vec![base];
+        for i in 1..(window / 4) as usize {
+            let next = self.add_jacobian(&table[i - 1], &twice);
+            table.push(next);
+        }
+
+        let mut res = self.to_jacobian(&Point::Infinity);
+        for &digit in digits.iter().rev() {
+            res = self.double_jacobian(&res);
+            if digit > 0 {
+                res = self.add_jacobian(&res, &table[(digit / 2) as usize]);
+            } else if digit < 0 {
+                let neg = self.negate_jacobian(&table[(-digit / 2) as usize]);
+                res = self.add_jacobian(&res, &neg);
+            }
+        }
+        self.from_jacobian(&res)
+    }
+
+    /**
+    Returns k P by the Montgomery ladder
+
+    # Returns
+    * k P keeping R1 - R0 = P and doing one addition and one doubling per bit whatever its value
+      so the sequence of operations only depends on the length of k.  NoSolns if the final
+      inversion fails and Overflow if k is the most negative value of T.
+    */
+    pub fn scalar_mul_ladder(&self, k: T, p: &Point<T>) -> Result<Point<T>, nt_error::NtError> {
+        let (k, base) = self.signed(k, p)?;
+        let mut r0 = self.to_jacobian(&Point::Infinity);
+        let mut r1 = base;
+        for bit in bits_from_top(k)? {
+            if bit {
+                r0 = self.add_jacobian(&r0, &r1);
+                r1 = self.double_jacobian(&r1);
+            } else {
+                r1 = self.add_jacobian(&r0, &r1);
+                r0 = self.double_jacobian(&r0);
+            }
+        }
+        self.from_jacobian(&r0)
+    }
+}
+
+/**
+Point of a short Weierstrass curve kept in Jacobian coordinates together with its curve
+
+The group law makes these a Monoid so monoid::pow_monoid and friends give scalar multiples.  The
+curve has to match for op - BadArgument otherwise.
+
+# Examples
+
+```
+use number_theory::number_theory::elliptic::{Point, WeierstrassCurve, WeierstrassElement};
+use number_theory::number_theory::monoid::{pow_monoid_with, PowerMethod, PowerOptions};
+
+let curve = WeierstrassCurve::new(2, 3, 97).unwrap_or_else(|_| panic!("Failed!"));
+let p = WeierstrassElement::new(&curve, &Point::Affine(3, 6));
+let options = PowerOptions {
+    method: PowerMethod::Naf,
+};
+let q = pow_monoid_with(&p, 2, &options).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(q.point(), Ok(Point::Affine(80, 10)));
+```
+*/
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WeierstrassElement<T: Numeric> {
+    curve: WeierstrassCurve<T>,
+    point: JacobianPoint<T>,
+}
+
+impl<T: Numeric> WeierstrassElement<T> {
+    pub fn new(curve: &WeierstrassCurve<T>, point: &Point<T>) -> WeierstrassElement<T> {
+        WeierstrassElement {
+            curve: *curve,
+            point: curve.to_jacobian(point),
+        }
+    }
+
+    pub fn curve(&self) -> &WeierstrassCurve<T> {
+        &self.curve
+    }
+
+    /// Affine point - NoSolns if Z can't be inverted
+    pub fn point(&self) -> Result<Point<T>, nt_error::NtError> {
+        self.curve.from_jacobian(&self.point)
+    }
+}
+
+impl<T: Numeric> Monoid for WeierstrassElement<T> {
+    const COMMUTATIVE: bool = true;
+
+    fn identity(&self) -> Self {
+        WeierstrassElement::new(&self.curve, &Point::Infinity)
+    }
+
+    fn op(&self, other: &Self) -> Result<Self, nt_error::NtError> {
+        if self.curve != other.curve {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        // add_jacobian handles equal points but doubling directly is cheaper
+        let point = if self == other {
+            self.curve.double_jacobian(&self.point)
+        } else {
+            self.curve.add_jacobian(&self.point, &other.point)
+        };
+        Ok(WeierstrassElement {
+            curve: self.curve,
+            point,
+        })
+    }
+
+    fn inverse(&self) -> Result<Self, nt_error::NtError> {
+        Ok(WeierstrassElement {
+            curve: self.curve,
+            point: self.curve.negate_jacobian(&self.point),
+        })
+    }
+}
+
+/**
+Montgomery curve b y^2 = x^3 + a x^2 + x over the integers mod an odd p
+
+Besides the affine group law these support x-only arithmetic on XZPoints where doubling and
+differential addition need no inversions - the basis of the Montgomery ladder and of ECM.  Only
+(a + 2) / 4 is needed for that and making the curve reports NoSolns if 4 isn't invertible.  With
+a composite p the affine operations report NoSolns when a denominator shares a factor with p.
+
+# Examples
+
+```
+use number_theory::number_theory::elliptic::{MontgomeryCurve, Point};
+
+// Curve25519's coefficients over a small prime: y^2 = x^3 + 486662 x^2 + x mod 1009
+let curve = MontgomeryCurve::new(486662, 1, 1009).unwrap_or_else(|_| panic!("Failed!"));
+let p = curve.lift_x(3).unwrap_or_else(|_| panic!("Failed!"));
+let q = curve.scalar_mul(1000, &p).unwrap_or_else(|_| panic!("Failed!"));
+let ladder = curve.ladder(1000, &curve.to_xz(&p)).unwrap_or_else(|_| panic!("Failed!"));
+match q {
+    Point::Affine(x, _) => assert_eq!(curve.x_coordinate(&ladder), Ok(Some(x))),
+    Point::Infinity => assert_eq!(curve.x_coordinate(&ladder), Ok(None)),
+}
+```
+*/
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MontgomeryCurve<T: Numeric> {
+    a: T,
+    b: T,
+    // (a + 2) / 4 for x-only doubling
+    a24: T,
+    field: Field<T>,
+}
+
+impl<T: Numeric> MontgomeryCurve<T> {
+    /**
+    Creates the curve b y^2 = x^3 + a x^2 + x mod p
+
+    # Returns
+    * The curve with a and b reduced mod p.  BadArgument if p < 3, b = 0 or a^2 = 4 mod p (the
+      singular cases), NoSolns if 4 isn't invertible mod p and Overflow if 2p doesn't fit in T.
+    */
+    pub fn new(a: T, b: T, p: T) -> Result<MontgomeryCurve<T>, nt_error::NtError> {
+        let field = Field::new(p)?;
+        let (a, b) = (field.reduce(a), field.reduce(b));
+        if b == T::zero() || field.sqr(a) == field.small(4) {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        let a24 = field.mul(field.add(a, field.small(2)), field.inv(field.small(4))?);
+        Ok(MontgomeryCurve { a, b, a24, field })
+    }
+
+    pub fn a(&self) -> T {
+        self.a
+    }
+
+    pub fn b(&self) -> T {
+        self.b
+    }
+
+    pub fn modulus(&self) -> T {
+        self.field.p
+    }
+
+    // (x^3 + a x^2 + x) / b
+    fn y_squared(&self, x: T) -> Result<T, nt_error::NtError> {
+        let f = &self.field;
+        let num = f.mul(f.add(f.mul(f.add(x, self.a), x), T::one()), x);
+        Ok(f.mul(num, f.inv(self.b)?))
+    }
+
+    /**
+    Returns whether a point is valid - infinity or reduced coordinates satisfying the equation
+    */
+    pub fn is_on_curve(&self, point: &Point<T>) -> bool {
+        let f = &self.field;
+        match *point {
+            Point::Infinity => true,
+            Point::Affine(x, y) => {
+                let range = T::zero()..f.p;
+                let rhs = f.mul(f.add(f.mul(f.add(x, self.a), x), T::one()), x);
+                range.contains(&x) && range.contains(&y) && f.mul(self.b, f.sqr(y)) == rhs
+            }
+        }
+    }
+
+    /**
+    Returns a point with x coordinate x
+
+    # Returns
+    * (x, y) for a square root y of (x^3 + a x^2 + x) / b by Tonelli-Shanks for any accepted p -
+      the other is (x, -y).  NoSolns if there is none and BadArgument if p is detected not to be
+      prime.
+    */
+    pub fn lift_x(&self, x: T) -> Result<Point<T>, nt_error::NtError> {
+        let x = self.field.reduce(x);
+        let y = self.field.sqrt(self.y_squared(x)?)?;
+        Ok(Point::Affine(x, y))
+    }
+
+    pub fn negate(&self, point: &Point<T>) -> Point<T> {
+        match *point {
+            Point::Infinity => Point::Infinity,
+            Point::Affine(x, y) => Point::Affine(x, self.field.neg(y)),
+        }
+    }
+
+    /**
+    Returns P + Q in affine coordinates - one inverse_mod whose NoSolns is passed through
+    */
+    pub fn add(&self, p: &Point<T>, q: &Point<T>) -> Result<Point<T>, nt_error::NtError> {
+        let f = &self.field;
+        let ((x1, y1), (x2, y2)) = match (*p, *q) {
+            (Point::Infinity, _) => return Ok(*q),
+            (_, Point::Infinity) => return Ok(*p),
+            (Point::Affine(x1, y1), Point::Affine(x2, y2)) => ((x1, y1), (x2, y2)),
+        };
+        let slope = if x1 == x2 {
+            if f.add(y1, y2) == T::zero() {
+                return Ok(Point::Infinity);
+            }
+            // Tangent (3x^2 + 2ax + 1) / 2by
+            let num = f.add(
+                f.add(
+                    f.mul(f.small(3), f.sqr(x1)),
+                    f.mul(f.add(self.a, self.a), x1),
+                ),
+                T::one(),
+            );
+            f.mul(num, f.inv(f.mul(f.add(self.b, self.b), y1))?)
+        } else {
+            f.mul(f.sub(y2, y1), f.inv(f.sub(x2, x1))?)
+        };
+        let x3 = f.sub(f.sub(f.sub(f.mul(self.b, f.sqr(slope)), self.a), x1), x2);
+        let y3 = f.sub(f.mul(slope, f.sub(x1, x3)), y1);
+        Ok(Point::Affine(x3, y3))
+    }
+
+    pub fn double(&self, p: &Point<T>) -> Result<Point<T>, nt_error::NtError> {
+        self.add(p, p)
+    }
+
+    /**
+    Returns k P by double-and-add in affine coordinates - negative k multiplies -P
+
+    Overflow if k is the most negative value of T.
+    */
+    pub fn scalar_mul(&self, k: T, p: &Point<T>) -> Result<Point<T>, nt_error::NtError> {
+        let (k, point) = if k < T::zero() {
+            (negate_scalar(k)?, self.negate(p))
+        } else {
+            (k, *p)
+        };
+        let base = MontgomeryElement {
+            curve: *self,
+            point,
+        };
+        Ok(pow_monoid(&base, k)?.point)
+    }
+
+    pub fn to_xz(&self, p: &Point<T>) -> XZPoint<T> {
+        match *p {
+            Point::Infinity => XZPoint {
+                x: T::one(),
+                z: T::zero(),
+            },
+            Point::Affine(x, _) => XZPoint { x, z: T::one() },
+        }
+    }
+
+    /// x = X / Z or None at infinity - NoSolns if Z isn't invertible
+    pub fn x_coordinate(&self, p: &XZPoint<T>) -> Result<Option<T>, nt_error::NtError> {
+        if p.z == T::zero() {
+            return Ok(None);
+        }
+        Ok(Some(self.field.mul(p.x, self.field.inv(p.z)?)))
+    }
+
+    /**
+    Returns 2P from the x-only doubling formula
+    */
+    pub fn x_double(&self, p: &XZPoint<T>) -> XZPoint<T> {
+        let f = &self.field;
+        let sum = f.sqr(f.add(p.x, p.z));
+        let diff = f.sqr(f.sub(p.x, p.z));
+        // sum - diff = 4xz
+        let four_xz = f.sub(sum, diff);
+        XZPoint {
+            x: f.mul(sum, diff),
+            z: f.mul(four_xz, f.add(diff, f.mul(self.a24, four_xz))),
+        }
+    }
+
+    /**
+    Returns P + Q from the x-only differential addition formula given P - Q
+
+    The difference must not be the point at infinity - use x_double for P + P.
+    */
+    pub fn x_add(&self, p: &XZPoint<T>, q: &XZPoint<T>, diff: &XZPoint<T>) -> XZPoint<T> {
+        let f = &self.field;
+        let u = f.mul(f.sub(p.x, p.z), f.add(q.x, q.z));
+        let v = f.mul(f.add(p.x, p.z), f.sub(q.x, q.z));
+        XZPoint {
+            x: f.mul(diff.z, f.sqr(f.add(u, v))),
+            z: f.mul(diff.x, f.sqr(f.sub(u, v))),
+        }
+    }
+
+    /**
+    Returns k P by the x-only Montgomery ladder
+
+    # Arguments
+
+    * `k` - Nonnegative scalar
+    * `p` - x-only point
+
+    # Returns
+    * k P keeping R1 - R0 = P with one x_add and one x_double per bit.  BadArgument for negative
+      k.  For P at infinity or of order 2 the differential addition degenerates so the result is
+      only meaningful for other points.
+    */
+    pub fn ladder(&self, k: T, p: &XZPoint<T>) -> Result<XZPoint<T>, nt_error::NtError> {
+        if k < T::zero() {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        let mut r0 = XZPoint {
+            x: T::one(),
+            z: T::zero(),
+        };
+        let mut r1 = *p;
+        for bit in bits_from_top(k)? {
+            if bit {
+                r0 = self.x_add(&r1, &r0, p);
+                r1 = self.x_double(&r1);
+            } else {
+                r1 = self.x_add(&r1, &r0, p);
+                r0 = self.x_double(&r0);
+            }
+        }
+        Ok(r0)
+    }
+
+    /**
+    Returns the isomorphic short Weierstrass curve
+
+    # Returns
+    * y^2 = x^3 + a' x + b' with a' = (3 - a^2) / (3b^2) and b' = (2a^3 - 9a) / (27b^3) where
+      (x, y) maps to (x / b + a / (3b), y / b) by to_weierstrass_point.  NoSolns if 3 isn't
+      invertible and errors from WeierstrassCurve::new are passed through.
+    */
+    pub fn to_weierstrass(&self) -> Result<WeierstrassCurve<T>, nt_error::NtError> {
+        let f = &self.field;
+        let three_b = f.mul(f.small(3), self.b);
+        let inv_3bb = f.inv(f.mul(three_b, self.b))?;
+        let inv_27bbb = f.inv(f.mul(f.mul(f.small(9), self.b), f.mul(three_b, self.b)))?;
+        let a2 = f.sqr(self.a);
+        let new_a = f.mul(f.sub(f.small(3), a2), inv_3bb);
+        let new_b = f.mul(
+            f.sub(
+                f.mul(f.small(2), f.mul(a2, self.a)),
+                f.mul(f.small(9), self.a),
+            ),
+            inv_27bbb,
+        );
+        WeierstrassCurve::new(new_a, new_b, f.p)
+    }
+
+    /// Image of a point on the curve returned by to_weierstrass
+    pub fn to_weierstrass_point(&self, p: &Point<T>) -> Result<Point<T>, nt_error::NtError> {
+        let f = &self.field;
+        match *p {
+            Point::Infinity => Ok(Point::Infinity),
+            Point::Affine(x, y) => {
+                let b_inv = f.inv(self.b)?;
+                let shift = f.mul(self.a, f.inv(f.mul(f.small(3), self.b))?);
+                Ok(Point::Affine(
+                    f.add(f.mul(x, b_inv), shift),
+                    f.mul(y, b_inv),
+                ))
+            }
+        }
+    }
+}
+
+/**
+Affine point of a Montgomery curve together with its curve
+
+A Monoid under the group law in the same way as WeierstrassElement.  Each op is one inverse_mod
+whose NoSolns is passed through.
+*/
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MontgomeryElement<T: Numeric> {
+    curve: MontgomeryCurve<T>,
+    point: Point<T>,
+}
+
+impl<T: Numeric> MontgomeryElement<T> {
+    pub fn new(curve: &MontgomeryCurve<T>, point: &Point<T>) -> MontgomeryElement<T> {
+        MontgomeryElement {
+            curve: *curve,
+            point: *point,
+        }
+    }
+
+    pub fn curve(&self) -> &MontgomeryCurve<T> {
+        &self.curve
+    }
+
+    pub fn point(&self) -> Point<T> {
+        self.point
+    }
+}
+
+impl<T: Numeric> Monoid for MontgomeryElement<T> {
+    const COMMUTATIVE: bool = true;
+
+    fn identity(&self) -> Self {
+        MontgomeryElement::new(&self.curve, &Point::Infinity)
+    }
+
+    fn op(&self, other: &Self) -> Result<Self, nt_error::NtError> {
+        if self.curve != other.curve {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        Ok(MontgomeryElement {
+            curve: self.curve,
+            point: self.curve.add(&self.point, &other.point)?,
+        })
+    }
+
+    fn inverse(&self) -> Result<Self, nt_error::NtError> {
+        Ok(MontgomeryElement::new(
+            &self.curve,
+            &self.curve.negate(&self.point),
+        ))
+    }
+}
@@ -1,38 +1,121 @@
 use crate::utilities::nt_error;
 use crate::utilities::numeric_trait::Numeric;
-use num::{abs, FromPrimitive};
+use std::ops::{Add, Mul, Sub};
 
-struct ExtMatrix<T: Numeric> {
+/**
+A ring with division with remainder - the setting for the Euclidean algorithm
+
+The remainder of a.div_rem(b) must have a smaller norm than b so repeated division terminates.
+Implemented for every Numeric type with Euclidean division - the remainder is in [0, |b|) and the
+norm is the absolute value.
+*/
+pub trait EuclideanDomain:
+    Clone + PartialEq + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    // The Euclidean function
+    type Norm: Ord;
+
+    // Values like zero and one may need context from self (a modulus for instance)
+    fn zero_like(&self) -> Self;
+    fn one_like(&self) -> Self;
+    fn is_zero(&self) -> bool {
+        *self == self.zero_like()
+    }
+    // (q, r) with self = q * other + r and norm(r) < norm(other)
+    fn div_rem(&self, other: &Self) -> (Self, Self);
+    fn norm(&self) -> Self::Norm;
+    // Unit u making u * self the preferred associate - one for zero
+    fn normalizing_unit(&self) -> Self;
+    // Product or Overflow for rings with bounded representations
+    fn try_mul(&self, other: &Self) -> Result<Self, nt_error::NtError> {
+        Ok(self.clone() * other.clone())
+    }
+}
+
+impl<T: Numeric> EuclideanDomain for T {
+    type Norm = T;
+
+    fn zero_like(&self) -> T {
+        T::zero()
+    }
+
+    fn one_like(&self) -> T {
+        T::one()
+    }
+
+    fn div_rem(&self, other: &T) -> (T, T) {
+        let (q, r) = (*self / *other, *self % *other);
+        if r >= T::zero() {
+            (q, r)
+        } else if *other > T::zero() {
+            (q - T::one(), r + *other)
+        } else {
+            (q + T::one(), r - *other)
+        }
+    }
+
+    fn norm(&self) -> T {
+        num::abs(*self)
+    }
+
+    fn normalizing_unit(&self) -> T {
+        if *self < T::zero() {
+            -T::one()
+        } else {
+            T::one()
+        }
+    }
+
+    fn try_mul(&self, other: &T) -> Result<T, nt_error::NtError> {
+        self.checked_mul(other).ok_or(nt_error::NtError::Overflow)
+    }
+}
+
+// u * val for the normalizing unit u of val
+fn normalized<T: EuclideanDomain>(val: T) -> T {
+    val.normalizing_unit() * val
+}
+
+pub(crate) struct ExtMatrix<T: EuclideanDomain> {
     first_row: usize,
     mtx: [[T; 3]; 3],
 }
 
-impl<T: Numeric> ExtMatrix<T> {
-    fn new(val1: T, val2: T) -> ExtMatrix<T> {
-        let zero = T::zero();
-        let one = T::one();
+impl<T: EuclideanDomain> ExtMatrix<T> {
+    pub(crate) fn new(val1: T, val2: T) -> ExtMatrix<T> {
+        let zero = val1.zero_like();
+        let one = val1.one_like();
 
-        let mtx = [[val1, one, zero], [val2, zero, one], [zero, zero, zero]];
+        let mtx = [
+            [val1, one.clone(), zero.clone()],
+            [val2, zero.clone(), one],
+            [zero.clone(), zero.clone(), zero],
+        ];
         ExtMatrix { first_row: 0, mtx }
     }
 
-    fn step(&mut self) {
+    // Performs one division step and returns its quotient - these are the partial quotients of
+    // the continued fraction of val1 / val2
+    pub(crate) fn step(&mut self) -> T {
         let next_row: usize = (self.first_row + 1) % 3;
         let spare_row: usize = (self.first_row + 2) % 3;
-        let q = self.mtx[self.first_row][0] / self.mtx[next_row][0];
-        for i in 0..3 {
-            self.mtx[spare_row][i] = self.mtx[self.first_row][i] - q * self.mtx[next_row][i];
+        let (q, r) = self.mtx[self.first_row][0].div_rem(&self.mtx[next_row][0]);
+        self.mtx[spare_row][0] = r;
+        for i in 1..3 {
+            self.mtx[spare_row][i] =
+                self.mtx[self.first_row][i].clone() - q.clone() * self.mtx[next_row][i].clone();
         }
         self.first_row = (self.first_row + 1) % 3;
+        q
     }
 
-    fn finished(&self) -> bool {
-        self.mtx[(self.first_row + 1) % 3][0] == T::zero()
+    pub(crate) fn finished(&self) -> bool {
+        self.mtx[(self.first_row + 1) % 3][0].is_zero()
     }
 
-    fn result(&self) -> (T, T, T) {
+    pub(crate) fn result(&self) -> (T, T, T) {
         let row = &self.mtx[self.first_row];
-        (row[0], row[1], row[2])
+        (row[0].clone(), row[1].clone(), row[2].clone())
     }
 }
 
@@ -45,7 +128,7 @@ Returns a GCD of two numbers
 * `val2` - Second number
 
 # Returns
-* GCD
+* GCD normalized by normalizing_unit - so nonnegative for integers
 
 # Examples
 
@@ -56,16 +139,16 @@ let gcd = euclidean::gcd(97, 18);
 assert_eq!(gcd, 1);
 ```
 */
-pub fn gcd<T: Numeric>(val1: T, val2: T) -> T {
-    let mut val1 = abs(val1);
-    let mut val2 = abs(val2);
+pub fn gcd<T: EuclideanDomain>(val1: T, val2: T) -> T {
+    let mut val1 = val1;
+    let mut val2 = val2;
 
-    while val2 != T::zero() {
-        let r = val1 % val2;
+    while !val2.is_zero() {
+        let (_, r) = val1.div_rem(&val2);
         val1 = val2;
         val2 = r;
     }
-    val1
+    normalized(val1)
 }
 
 /**
@@ -77,7 +160,7 @@ Returns a LCM of two numbers
 * `val2` - Second number
 
 # Returns
-* LCM
+* LCM normalized as in gcd.  Overflow if it doesn't fit.
 
 # Examples
 
@@ -89,12 +172,13 @@ let lcm = euclidean::lcm(4, 6)
 assert_eq!(lcm, 12);
 ```
 */
-pub fn lcm<T: Numeric>(val1: T, val2: T) -> Result<T, nt_error::NtError> {
-    let opt = val1.checked_mul(&val2);
-    match opt {
-        None => Err(nt_error::NtError::Overflow),
-        Some(val) => Ok(val / gcd(val1, val2)),
+pub fn lcm<T: EuclideanDomain>(val1: T, val2: T) -> Result<T, nt_error::NtError> {
+    if val1.is_zero() || val2.is_zero() {
+        return Ok(val1.zero_like());
     }
+    // Dividing first keeps the product as small as possible
+    let (quot, _) = val1.div_rem(&gcd(val1.clone(), val2.clone()));
+    Ok(normalized(quot.try_mul(&val2)?))
 }
 
 /**
@@ -106,7 +190,8 @@ Returns a GCD of two numbers and the linear coefficients to produce that GCD fro
 * `val2` - Second number
 
 # Returns
-* Tuple whose first value is GCD, second value is coeff for val1 , third value is coeff for val2
+* Tuple whose first value is GCD, second value is coeff for val1 , third value is coeff for val2.
+  The GCD is normalized as in gcd.
 
 # Examples
 
@@ -118,12 +203,14 @@ assert_eq!(gcd, 1);
 assert_eq!(coeff1 * 97 + coeff2 * 18, gcd);
 ```
 */
-pub fn calc_euclidean_ext<T: Numeric>(val1: T, val2: T) -> (T, T, T) {
+pub fn calc_euclidean_ext<T: EuclideanDomain>(val1: T, val2: T) -> (T, T, T) {
     let mut mtx = ExtMatrix::new(val1, val2);
     while !mtx.finished() {
         mtx.step();
     }
-    mtx.result()
+    let (gcd, coeff1, coeff2) = mtx.result();
+    let unit = gcd.normalizing_unit();
+    (unit.clone() * gcd, unit.clone() * coeff1, unit * coeff2)
 }
 
 /**
@@ -135,8 +222,8 @@ Returns a function which produces coefficients to solve ax + by = c if any exist
 
 # Returns
 * Option with a closure which takes an i32 and each different value produces different x, y
-to solve the above equation.  The function provides a "small" pair for i = 0.  If there
-is no solution then Option.None is returned.
+  to solve the above equation.  The function provides a "small" pair for i = 0.  If there
+  is no solution then Option.None is returned.  BadArgument if a and b are both zero.
 
 # Examples
 
@@ -155,37 +242,65 @@ assert_eq!(7*x1 + 13 * y1, 5);
 
 ```
 */
-pub fn solve_diophantine<T: Numeric>(
+#[allow(clippy::type_complexity)]
+pub fn solve_diophantine<T: EuclideanDomain>(
     a: T,
     b: T,
     c: T,
 ) -> Result<(impl Fn(i32) -> (T, T), T), nt_error::NtError> {
-    let (gcd, c1, c2) = calc_euclidean_ext(a, b);
-    if c % gcd != T::zero() {
+    let (gcd, c1, c2) = calc_euclidean_ext(a.clone(), b.clone());
+    if gcd.is_zero() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let (c_div, rem) = c.div_rem(&gcd);
+    if !rem.is_zero() {
         return Err(nt_error::NtError::NoSolns);
     }
-    let cnst1 = c * c1 / gcd;
-    let cnst2 = c * c2 / gcd;
-    let cf1 = b / gcd;
-    let cf2 = -a / gcd;
-
-    let q = if abs(cnst1) > abs(cnst2) {
-        cnst1 / cf1
+    let cnst1 = c_div.try_mul(&c1)?;
+    let cnst2 = c_div.try_mul(&c2)?;
+    let cf1 = b.div_rem(&gcd).0;
+    let cf2 = a.zero_like() - a.div_rem(&gcd).0;
+
+    // Shift along the solutions to shrink the larger of the two constants
+    let q = if cnst1.norm() > cnst2.norm() && !cf1.is_zero() {
+        cnst1.div_rem(&cf1).0
+    } else if !cf2.is_zero() {
+        cnst2.div_rem(&cf2).0
     } else {
-        cnst2 / cf2
+        cnst1.zero_like()
     };
 
-    let cnst1 = cnst1 - q * cf1;
-    let cnst2 = cnst2 - q * cf2;
+    let cnst1 = cnst1 - q.clone() * cf1.clone();
+    let cnst2 = cnst2 - q * cf2.clone();
 
     let result = move |i: i32| {
-        let i_t = FromPrimitive::from_i32(i).unwrap();
-        (cf1 * i_t + cnst1, cf2 * i_t + cnst2)
+        let i_t = from_i32(i, &cf1);
+        (
+            cf1.clone() * i_t.clone() + cnst1.clone(),
+            cf2.clone() * i_t + cnst2.clone(),
+        )
     };
 
     Ok((result, gcd))
 }
 
+// i as a multiple of one in the ring of like by double and add
+fn from_i32<T: EuclideanDomain>(i: i32, like: &T) -> T {
+    let one = like.one_like();
+    let mut ret = like.zero_like();
+    for bit in (0..32).rev() {
+        ret = ret.clone() + ret;
+        if (i.unsigned_abs() >> bit) & 1 == 1 {
+            ret = ret + one.clone();
+        }
+    }
+    if i < 0 {
+        like.zero_like() - ret
+    } else {
+        ret
+    }
+}
+
 /**
 Returns solutions to ax = b (mod modulo)
 
@@ -220,10 +335,7 @@ pub fn solve_linear_congruence<T: Numeric>(
 ) -> Result<Vec<T>, nt_error::NtError> {
     let zero = T::zero();
     let one = T::one();
-    let (func_solns, gcd) = match solve_diophantine(a, modulo, b) {
-        Err(e) => return Err(e),
-        Ok(val) => val,
-    };
+    let (func_solns, gcd) = solve_diophantine(a, modulo, b)?;
     let mut ret = Vec::new();
     let gcd_int_opt = num::ToPrimitive::to_i32(&gcd);
     let gcd_int = match gcd_int_opt {
@@ -253,7 +365,8 @@ Returns a^-1 (mod modulo)
 * `a`, `modulo` - Coefficients in a^-1 (mod modulo)
 
 # Returns
-* Some(Inverse of a mod modulo) or None if there is no inverse
+* Inverse of a mod modulo straight from calc_euclidean_ext reduced by div_rem - so in
+  [0, |modulo|) for integers.  NoSolns if there is no inverse and BadArgument if modulo is zero.
 
 # Examples
 
@@ -265,9 +378,82 @@ let inverse = euclidean::inverse_mod(3, 11)
 assert_eq!(inverse, 4);
 ```
 */
-pub fn inverse_mod<T: Numeric>(n: T, modulo: T) -> Result<T, nt_error::NtError> {
-    match solve_linear_congruence(n, T::one(), modulo) {
-        Err(e) => Err(e),
-        Ok(ret) => Ok(ret[0]),
+pub fn inverse_mod<T: EuclideanDomain>(n: T, modulo: T) -> Result<T, nt_error::NtError> {
+    if modulo.is_zero() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let modulo = normalized(modulo);
+    let (gcd, coeff, _) = calc_euclidean_ext(n.div_rem(&modulo).1, modulo.clone());
+    if gcd != gcd.one_like() {
+        return Err(nt_error::NtError::NoSolns);
+    }
+    Ok(coeff.div_rem(&modulo).1)
+}
+
+/**
+Returns the inverses of a list of numbers mod modulo using a single modular inversion
+
+# Arguments
+
+* `values` - Numbers to invert
+* `modulo` - Modulus - must be positive
+
+# Returns
+* Vector with the inverse of each value in [0, modulo).  Uses Montgomery's trick of inverting the
+  product of all the values and peeling the individual inverses off with multiplications.
+  NonInvertible(i) if values[i] is the first value sharing a factor with modulo, Overflow if
+  modulo^2 doesn't fit in T and BadArgument if modulo isn't positive.
+
+# Examples
+
+```
+use number_theory::number_theory::euclidean;
+use number_theory::utilities::nt_error::NtError;
+
+let inverses = euclidean::batch_inverse_mod(&[2, 3, 4], 11)
+    .unwrap_or_else(|_| panic!("Failed"));
+assert_eq!(inverses, vec![6, 4, 3]);
+let err = euclidean::batch_inverse_mod(&[2, 3, 4], 9);
+assert_eq!(err, Err(NtError::NonInvertible(1)));
+```
+*/
+pub fn batch_inverse_mod<T: Numeric>(values: &[T], modulo: T) -> Result<Vec<T>, nt_error::NtError> {
+    if modulo <= T::zero() {
+        return Err(nt_error::NtError::BadArgument);
     }
+    let mul_mod = |a: T, b: T| match a.checked_mul(&b) {
+        None => Err(nt_error::NtError::Overflow),
+        Some(val) => Ok(val % modulo),
+    };
+
+    let values: Vec<T> = values.iter().map(|val| val.div_rem(&modulo).1).collect();
+    // prefixes[i] = product of values[0..=i]
+    let mut prefixes = Vec::with_capacity(values.len());
+    let mut product = T::one() % modulo;
+    for &val in values.iter() {
+        product = mul_mod(product, val)?;
+        prefixes.push(product);
+    }
+
+    let mut inv = match inverse_mod(product, modulo) {
+        Ok(inv) => inv,
+        Err(nt_error::NtError::NoSolns) => {
+            let idx = values
+                .iter()
+                .position(|&val| gcd(val, modulo) != T::one())
+                .unwrap();
+            return Err(nt_error::NtError::NonInvertible(idx));
+        }
+        Err(e) => return Err(e),
+    };
+    // inv is the inverse of prefixes[i] at the top of each iteration
+    let mut ret = vec![T::zero(); values.len()];
+    for i in (1..values.len()).rev() {
+        ret[i] = mul_mod(inv, prefixes[i - 1])?;
+        inv = mul_mod(inv, values[i])?;
+    }
+    if !values.is_empty() {
+        ret[0] = inv;
+    }
+    Ok(ret)
 }
@@ -0,0 +1,607 @@
+use crate::number_theory::elliptic::{MontgomeryCurve, XZPoint};
+use crate::number_theory::euclidean::{self, EuclideanDomain};
+use crate::number_theory::power_mod;
+use crate::utilities::nt_error;
+use crate::utilities::numeric_trait::Numeric;
+
+/**
+Returns the prime factorization of a number by trial division
+
+# Arguments
+
+* `n` - Positive number to be factored
+
+# Returns
+* Vector of (prime, exponent) pairs in increasing order of prime.  Empty for n = 1.  BadArgument
+  if n isn't positive.
+
+# Examples
+
+```
+use number_theory::number_theory::factorization;
+
+let factors = factorization::factor(360)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(factors, vec![(2, 3), (3, 2), (5, 1)]);
+```
+*/
+pub fn factor<T: Numeric>(n: T) -> Result<Vec<(T, u32)>, nt_error::NtError> {
+    if n <= T::zero() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let two = T::one() + T::one();
+    let mut ret = Vec::new();
+    let mut rest = n;
+    let mut divisor = two;
+    loop {
+        match divisor.checked_mul(&divisor) {
+            Some(sq) if sq <= rest => (),
+            // Anything left over has no divisor <= its square root
+            _ => break,
+        }
+        let mut exp = 0;
+        while rest % divisor == T::zero() {
+            rest = rest / divisor;
+            exp += 1;
+        }
+        if exp > 0 {
+            ret.push((divisor, exp));
+        }
+        divisor += if divisor == two { T::one() } else { two };
+    }
+    if rest != T::one() {
+        ret.push((rest, 1));
+    }
+    Ok(ret)
+}
+
+// x^e mod n - by power_mod::power_ct when n^2 doesn't fit
+fn pow_mod<T: Numeric>(x: T, e: T, n: T) -> Result<T, nt_error::NtError> {
+    if n.checked_mul(&n).is_some() {
+        power_mod::power(x, e, n)
+    } else {
+        power_mod::power_ct(x, e, n)
+    }
+}
+
+// Bases making Miller-Rabin deterministic below 3.3 * 10^24
+const WITNESSES: [u8; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+
+/**
+Returns whether n is prime by Miller-Rabin
+
+# Arguments
+
+* `n` - Number to test
+
+# Returns
+* True for primes.  The first thirteen primes are used as witnesses which is deterministic for
+  n < 3.3 * 10^24 - so for every 64 bit value - and a strong probable prime test beyond that.
+  Values up to 1 are not prime.  Powers are taken with power_mod::power when n^2 fits in T and
+  with power_mod::power_ct otherwise so Overflow only arises if 2n doesn't fit.
+
+# Examples
+
+```
+use number_theory::number_theory::factorization;
+
+assert_eq!(factorization::is_prime(1000000007i64), Ok(true));
+assert_eq!(factorization::is_prime(3215031751i64), Ok(false));
+```
+*/
+pub fn is_prime<T: Numeric>(n: T) -> Result<bool, nt_error::NtError> {
+    if n < T::from_u8(2).unwrap() {
+        return Ok(false);
+    }
+    for &w in WITNESSES.iter() {
+        let w = T::from_u8(w).unwrap();
+        if n == w {
+            return Ok(true);
+        }
+        if n % w == T::zero() {
+            return Ok(false);
+        }
+    }
+    let pow = |x: T, e: T| pow_mod(x, e, n);
+
+    // n - 1 = d 2^s with d odd
+    let n_1 = n - T::one();
+    let mut d = n_1;
+    let mut s = 0;
+    while (d & T::one()) == T::zero() {
+        d = d >> 1;
+        s += 1;
+    }
+    'witness: for &w in WITNESSES.iter() {
+        let mut x = pow(T::from_u8(w).unwrap(), d)?;
+        if x == T::one() || x == n_1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = pow(x, T::from_u8(2).unwrap())?;
+            if x == n_1 {
+                continue 'witness;
+            }
+        }
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/**
+Returns the primes up to a limit by the sieve of Eratosthenes
+
+# Arguments
+
+* `limit` - Largest number to consider
+
+# Returns
+* The primes p <= limit in increasing order
+
+# Examples
+
+```
+use number_theory::number_theory::factorization;
+
+assert_eq!(factorization::primes_up_to(20), vec![2, 3, 5, 7, 11, 13, 17, 19]);
+```
+*/
+pub fn primes_up_to(limit: u64) -> Vec<u64> {
+    if limit < 2 {
+        return vec![];
+    }
+    let limit = limit as usize;
+    let mut composite = vec![false; limit + 1];
+    let mut ret = Vec::new();
+    for i in 2..=limit {
+        if composite[i] {
+            continue;
+        }
+        ret.push(i as u64);
+        for multiple in (i.saturating_mul(i)..=limit).step_by(i) {
+            composite[multiple] = true;
+        }
+    }
+    ret
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+// Lenstra's elliptic curve method
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Giant step of ecm stage 2 - each prime is m D +- j for a baby step j < D / 2 coprime to D
+const ECM_D: u64 = 210;
+
+/**
+Options for ecm
+
+* `b1` - Stage 1 bound - each curve point is multiplied by every prime power up to b1.  Raised to
+  D / 2 = 105 if smaller so stage 2 can start from a nonzero giant step.
+* `b2` - Stage 2 bound - the group order may have one more prime factor in (b1, b2]
+* `curves` - Number of curves to try
+* `sigma` - Suyama parameter of the first curve - curve i uses sigma + i.  Must be at least 6.
+
+The defaults are the usual choice for factors of around 20 digits.  Note that ecm works in a fixed
+width T so n is limited to 38 digits even with i128 (see ecm) - a 20 digit factor can only be
+found when its cofactor has at most 18 digits.
+*/
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EcmOptions {
+    pub b1: u64,
+    pub b2: u64,
+    pub curves: u32,
+    pub sigma: u64,
+}
+
+impl Default for EcmOptions {
+    fn default() -> Self {
+        EcmOptions {
+            b1: 11_000,
+            b2: 1_900_000,
+            curves: 74,
+            sigma: 6,
+        }
+    }
+}
+
+// Argument checks shared by the factoring methods - Some(2) for even n
+fn check_composite<T: Numeric>(n: T, b1: u64, b2: u64) -> Result<Option<T>, nt_error::NtError> {
+    let two = T::from_u8(2).unwrap();
+    if n < two || b2 < b1 {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    if n.checked_add(&n).is_none() {
+        return Err(nt_error::NtError::Overflow);
+    }
+    if is_prime(n)? {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    Ok(if n % two == T::zero() {
+        Some(two)
+    } else {
+        None
+    })
+}
+
+// a * b mod n for a, b in [0, n) - by doubling when the product doesn't fit
+fn mul_mod<T: Numeric>(a: T, b: T, n: T) -> T {
+    match a.checked_mul(&b) {
+        Some(val) => val % n,
+        None => power_mod::mul_mod_ct_reduced(a, b, n),
+    }
+}
+
+// gcd(val, n) if it's a proper factor of n
+fn proper_factor<T: Numeric>(val: T, n: T) -> Option<T> {
+    let g = euclidean::gcd(val, n);
+    if g != T::one() && g != n {
+        Some(g)
+    } else {
+        None
+    }
+}
+
+// Runs both stages on the Suyama curve for sigma - Some(factor) if either finds one
+fn ecm_curve<T: Numeric>(
+    n: T,
+    sigma: u64,
+    b1: u64,
+    b2: u64,
+    primes: &[u64],
+) -> Result<Option<T>, nt_error::NtError> {
+    let scalar = |c: u64| T::from_u64(c).ok_or(nt_error::NtError::Overflow);
+    let small = |c: u64| scalar(c).map(|c| c.div_rem(&n).1);
+    let mul = |a: T, b: T| mul_mod(a, b, n);
+    let add = |a: T, b: T| (a + b) % n;
+    let sub = |a: T, b: T| (a - b).div_rem(&n).1;
+
+    // u = sigma^2 - 5 and v = 4 sigma give the curve with A + 2 = (v - u)^3 (3u + v) / (4 u^3 v)
+    // and the point with x = u^3 / v^3.  Its group order is divisible by 12.
+    let sigma = small(sigma)?;
+    let u = sub(mul(sigma, sigma), small(5)?);
+    let v = mul(small(4)?, sigma);
+    let u3 = mul(mul(u, u), u);
+    let v_u = sub(v, u);
+    let num = mul(mul(mul(v_u, v_u), v_u), add(mul(small(3)?, u), v));
+    let den = mul(mul(small(4)?, u3), v);
+    let inv = match euclidean::inverse_mod(den, n) {
+        Ok(inv) => inv,
+        Err(nt_error::NtError::NoSolns) => return Ok(proper_factor(den, n)),
+        Err(e) => return Err(e),
+    };
+    let curve = match MontgomeryCurve::new(sub(mul(num, inv), small(2)?), T::one(), n) {
+        Ok(curve) => curve,
+        // Singular mod n
+        Err(nt_error::NtError::BadArgument) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    // Stage 1 - a factor p turns up when the order of the point mod p divides the product of the
+    // prime powers up to b1 since then its Z is 0 mod p and can't be inverted.  Normalizing after
+    // every prime keeps the factors of n from all turning up at once.
+    let mut point = XZPoint {
+        x: u3,
+        z: mul(mul(v, v), v),
+    };
+    for &p in primes.iter().take_while(|&&p| p <= b1) {
+        let mut pe = 1;
+        while pe <= b1 / p {
+            pe *= p;
+            point = curve.ladder(scalar(p)?, &point)?;
+            point = match curve.x_coordinate(&point) {
+                Ok(Some(x)) => XZPoint { x, z: T::one() },
+                Ok(None) => return Ok(None),
+                Err(nt_error::NtError::NoSolns) => return Ok(proper_factor(point.z, n)),
+                Err(e) => return Err(e),
+            };
+        }
+    }
+    if b2 <= b1 {
+        return Ok(None);
+    }
+
+    // Stage 2 - the order may be one prime q = m D +- j in (b1, b2] more.  Then x(m D Q) = x(j Q)
+    // mod p so X_m Z_j - X_j Z_m is 0 mod p.  baby[i] is (2i + 1) Q for the odd j < D / 2.
+    let twice = curve.x_double(&point);
+    let mut baby = vec![point, curve.x_add(&twice, &point, &point)];
+    while baby.len() < (ECM_D / 4) as usize {
+        let len = baby.len();
+        let next = curve.x_add(&baby[len - 1], &twice, &baby[len - 2]);
+        baby.push(next);
+    }
+    let giant = curve.ladder(scalar(ECM_D)?, &point)?;
+    // b1 >= D / 2 so the first m is at least 1
+    let mut m = (b1 + 1 + ECM_D / 2) / ECM_D;
+    let mut prev = curve.ladder(scalar((m - 1) * ECM_D)?, &point)?;
+    let mut cur = curve.ladder(scalar(m * ECM_D)?, &point)?;
+    let mut acc = T::one();
+    for &p in primes.iter().skip_while(|&&p| p <= b1) {
+        while p > m * ECM_D + ECM_D / 2 {
+            match euclidean::inverse_mod(acc, n) {
+                Ok(_) => (),
+                Err(nt_error::NtError::NoSolns) => return Ok(proper_factor(acc, n)),
+                Err(e) => return Err(e),
+            }
+            // The differential addition can't take the point at infinity as the difference
+            let next = if m == 1 {
+                curve.x_double(&cur)
+            } else {
+                curve.x_add(&cur, &giant, &prev)
+            };
+            prev = cur;
+            cur = next;
+            m += 1;
+        }
+        let j = p.abs_diff(m * ECM_D);
+        let step = &baby[(j / 2) as usize];
+        acc = mul(acc, sub(mul(cur.x, step.z), mul(step.x, cur.z)));
+    }
+    match euclidean::inverse_mod(acc, n) {
+        Ok(_) => Ok(None),
+        Err(nt_error::NtError::NoSolns) => Ok(proper_factor(acc, n)),
+        Err(e) => Err(e),
+    }
+}
+
+/**
+Returns a proper factor of n by Lenstra's elliptic curve method
+
+# Arguments
+
+* `n` - Composite number to split
+* `options` - Stage bounds, number of curves and the first Suyama parameter
+
+# Returns
+* A factor d with 1 < d < n, not necessarily prime.  Each curve is a Montgomery curve from
+  Suyama's parametrization with x-only arithmetic from elliptic::MontgomeryCurve.  Stage 1
+  multiplies by the prime powers up to b1 and stage 2 checks the primes in (b1, b2] with baby
+  steps and giant steps.  A factor shows up as a value inverse_mod can't invert and the gcd with n
+  gives it.  2 for even n.  NoSolns if no curve finds a factor, BadArgument if n isn't composite,
+  sigma < 6 or b2 < b1 and Overflow if 2n or b2 doesn't fit in T.
+
+Since 2n has to fit in T, n is below 2^126 (about 8.5 * 10^37, 38 digits) for i128, the widest
+supported type.  So factors of 20 digits or more can only be found in n of at most 38 digits and
+the 30 to 40 digit factors ECM is usually run for are out of reach until a wider integer type
+implements Numeric.
+
+# Examples
+
+```
+use number_theory::number_theory::factorization::{self, EcmOptions};
+
+let n: i128 = 1000003 * 1000033;
+let d = factorization::ecm(n, &EcmOptions::default())
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert!(d == 1000003 || d == 1000033);
+```
+*/
+pub fn ecm<T: Numeric>(n: T, options: &EcmOptions) -> Result<T, nt_error::NtError> {
+    if options.sigma < 6 {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    if let Some(two) = check_composite(n, options.b1, options.b2)? {
+        return Ok(two);
+    }
+
+    let b1 = options.b1.max(ECM_D / 2);
+    let primes = primes_up_to(options.b2.max(b1));
+    for i in 0..options.curves {
+        let sigma = options
+            .sigma
+            .checked_add(i as u64)
+            .ok_or(nt_error::NtError::Overflow)?;
+        if let Some(d) = ecm_curve(n, sigma, b1, options.b2, &primes)? {
+            return Ok(d);
+        }
+    }
+    Err(nt_error::NtError::NoSolns)
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+// Pollard p - 1 and Williams p + 1
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Stage 2 primes between gcd checks
+const STAGE2_BLOCK: usize = 256;
+
+// Seeds V_1 for williams_p_plus_1 - the discriminants V_1^2 - 4 are 5, 12 and 32 which are 5, 3
+// and 2 up to squares so each prime has at most a 1 in 8 chance of missing all of them
+const WILLIAMS_SEEDS: [u8; 3] = [3, 4, 6];
+
+// Stage 2 shared by p - 1 and p + 1.  x is the stage 1 result in a group with multiplication mul
+// and identity one and test(y) is 0 mod p when y = 1 mod p.  x^q for consecutive primes q in
+// (b1, b2] are a multiplication apart using a table of x^gap for the even gaps.
+fn stage2<E: Copy, T: Numeric>(
+    x: E,
+    one: E,
+    mul: impl Fn(E, E) -> E,
+    test: impl Fn(E) -> T,
+    n: T,
+    b1: u64,
+    primes: &[u64],
+) -> Option<T> {
+    let primes: Vec<u64> = primes.iter().copied().skip_while(|&q| q <= b1).collect();
+    let first = *primes.first()?;
+    let max_gap = primes.windows(2).map(|w| w[1] - w[0]).max().unwrap_or(0);
+    // gaps[i] = x^(2i)
+    let sqr = mul(x, x);
+    let mut gaps = vec![one];
+    while (gaps.len() as u64) * 2 <= max_gap {
+        let next = mul(gaps[gaps.len() - 1], sqr);
+        gaps.push(next);
+    }
+
+    let mut power = one;
+    for bit in (0..64 - first.leading_zeros()).rev() {
+        power = mul(power, power);
+        if (first >> bit) & 1 == 1 {
+            power = mul(power, x);
+        }
+    }
+    let mut prev = first;
+    let mut acc = T::one();
+    for block in primes.chunks(STAGE2_BLOCK) {
+        for &q in block {
+            power = mul(power, gaps[((q - prev) / 2) as usize]);
+            prev = q;
+            acc = mul_mod(acc, test(power), n);
+        }
+        let g = euclidean::gcd(acc, n);
+        if g != T::one() {
+            return if g == n { None } else { Some(g) };
+        }
+    }
+    None
+}
+
+/**
+Returns a proper factor of n by Pollard's p - 1 method
+
+# Arguments
+
+* `n` - Composite number to split
+* `b1` - Stage 1 bound - at least 2
+* `b2` - Stage 2 bound - at least b1
+
+# Returns
+* A factor d with 1 < d < n, not necessarily prime.  Stage 1 raises 2 to every prime power up to
+  b1 with power_mod::power so a prime p with p - 1 made of those prime powers divides a - 1 for
+  the result a.  Stage 2 allows p - 1 one more prime factor in (b1, b2].  Factors come from
+  euclidean::gcd with n.  2 for even n.  NoSolns if no factor is found, BadArgument if n isn't
+  composite, b1 < 2 or b2 < b1 and Overflow if 2n or b1 doesn't fit in T.
+
+# Examples
+
+```
+use number_theory::number_theory::factorization;
+
+// 1000003 - 1 = 2 * 3 * 166667 but 1000033 - 1 = 2^5 * 3 * 11 * 947
+let d = factorization::pollard_p_minus_1(1000003i64 * 1000033, 40, 1000)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(d, 1000033);
+```
+*/
+pub fn pollard_p_minus_1<T: Numeric>(n: T, b1: u64, b2: u64) -> Result<T, nt_error::NtError> {
+    if b1 < 2 {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    if let Some(two) = check_composite(n, b1, b2)? {
+        return Ok(two);
+    }
+    let primes = primes_up_to(b2);
+
+    let mut a = T::from_u8(2).unwrap();
+    for &p in primes.iter().take_while(|&&p| p <= b1) {
+        let mut pe = p;
+        while pe <= b1 / p {
+            pe *= p;
+        }
+        let pe = T::from_u64(pe).ok_or(nt_error::NtError::Overflow)?;
+        a = pow_mod(a, pe, n)?;
+        // Checking after every prime keeps the factors of n from all turning up at once
+        let g = euclidean::gcd(a - T::one(), n);
+        if g == n {
+            return Err(nt_error::NtError::NoSolns);
+        }
+        if g != T::one() {
+            return Ok(g);
+        }
+    }
+
+    let test = |y: T| (y - T::one()).div_rem(&n).1;
+    stage2(a, T::one(), |x, y| mul_mod(x, y, n), test, n, b1, &primes)
+        .ok_or(nt_error::NtError::NoSolns)
+}
+
+// V_k mod n for the Lucas sequence V_0 = 2, V_1 = v, V_(j+1) = v V_j - V_(j-1)
+fn lucas_v<T: Numeric>(v: T, k: T, n: T) -> Result<T, nt_error::NtError> {
+    let two = T::from_u8(2).unwrap().div_rem(&n).1;
+    let dbl = |x: T| (mul_mod(x, x, n) - two).div_rem(&n).1;
+    // (V_j, V_(j+1)) with V_(2j+1) = V_j V_(j+1) - v
+    let (mut lo, mut hi) = (two, v);
+    for bit in (0..k.bit_length()?).rev() {
+        let cross = (mul_mod(lo, hi, n) - v).div_rem(&n).1;
+        if ((k >> bit as usize) & T::one()) == T::one() {
+            lo = cross;
+            hi = dbl(hi);
+        } else {
+            hi = cross;
+            lo = dbl(lo);
+        }
+    }
+    Ok(lo)
+}
+
+/**
+Returns a proper factor of n by Williams' p + 1 method
+
+# Arguments
+
+* `n` - Composite number to split
+* `b1` - Stage 1 bound - at least 2
+* `b2` - Stage 2 bound - at least b1
+
+# Returns
+* A factor d with 1 < d < n, not necessarily prime.  With alpha a root of x^2 - V_1 x + 1 the
+  Lucas sequence V_k = alpha^k + alpha^(-k) is carried to every prime power up to b1 in stage 1.
+  When V_1^2 - 4 isn't a square mod p, alpha has order dividing p + 1 so p divides V - 2 if p + 1
+  is made of those prime powers.  Stage 2 allows p + 1 one more prime factor in (b1, b2] working
+  with powers of alpha in Z_n[x] / (x^2 - V x + 1).  Seeds V_1 = 3, 4 and 6 are tried in turn -
+  a square discriminant turns the method into p - 1.  2 for even n.  NoSolns if no factor is
+  found, BadArgument if n isn't composite, b1 < 2 or b2 < b1 and Overflow if 2n or b1 doesn't fit
+  in T.
+
+# Examples
+
+```
+use number_theory::number_theory::factorization;
+
+// 1000159 + 1 = 2^5 * 5 * 7 * 19 * 47 but 1003039 - 1 and 1003039 + 1 have large prime factors
+let d = factorization::williams_p_plus_1(1000159i64 * 1003039, 100, 1000)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(d, 1000159);
+```
+*/
+pub fn williams_p_plus_1<T: Numeric>(n: T, b1: u64, b2: u64) -> Result<T, nt_error::NtError> {
+    if b1 < 2 {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    if let Some(two) = check_composite(n, b1, b2)? {
+        return Ok(two);
+    }
+    let primes = primes_up_to(b2);
+    let two = T::from_u8(2).unwrap().div_rem(&n).1;
+
+    'seed: for &seed in WILLIAMS_SEEDS.iter() {
+        let mut v = T::from_u8(seed).unwrap().div_rem(&n).1;
+        for &p in primes.iter().take_while(|&&p| p <= b1) {
+            let mut pe = p;
+            while pe <= b1 / p {
+                pe *= p;
+            }
+            // V_(jk) is V_k of the sequence with V_1 = V_j
+            v = lucas_v(v, T::from_u64(pe).ok_or(nt_error::NtError::Overflow)?, n)?;
+            let g = euclidean::gcd(v - two, n);
+            if g == n {
+                continue 'seed;
+            }
+            if g != T::one() {
+                return Ok(g);
+            }
+        }
+
+        // (a, b) is a + b x.  x^k has b = 0 mod p only if it's +-1 mod p.
+        let mul = |(a, b): (T, T), (c, d): (T, T)| {
+            let bd = mul_mod(b, d, n);
+            (
+                (mul_mod(a, c, n) - bd).div_rem(&n).1,
+                ((mul_mod(a, d, n) + mul_mod(b, c, n)) % n + mul_mod(v, bd, n)) % n,
+            )
+        };
+        let one = (T::one() % n, T::zero());
+        if let Some(d) = stage2((T::zero(), T::one()), one, mul, |(_, b)| b, n, b1, &primes) {
+            return Ok(d);
+        }
+    }
+    Err(nt_error::NtError::NoSolns)
+}
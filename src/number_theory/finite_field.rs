@@ -0,0 +1,555 @@
+use crate::number_theory::euclidean::{self, EuclideanDomain};
+use crate::number_theory::factorization;
+use crate::number_theory::monoid::Monoid;
+use crate::number_theory::poly::{self, Poly};
+use crate::number_theory::totient;
+use crate::utilities::nt_error;
+use crate::utilities::numeric_trait::Numeric;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/**
+Returns whether a polynomial is irreducible by Rabin's test
+
+# Arguments
+
+* `f` - Polynomial mod a prime
+
+# Returns
+* True when f has degree n >= 1 and no factors of lower degree.  Rabin: f is irreducible exactly
+  when x^(p^n) = x (mod f) and x^(p^(n / r)) - x is coprime to f for each prime r dividing n.
+  BadArgument if f is zero.
+
+# Examples
+
+```
+use number_theory::number_theory::finite_field;
+use number_theory::number_theory::poly::Poly;
+
+let f = Poly::new(vec![1, 1, 0, 0, 1], 2).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(finite_field::is_irreducible(&f), Ok(true));
+// x^4 + 1 = (x + 1)^4 mod 2
+let g = Poly::new(vec![1, 0, 0, 0, 1], 2).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(finite_field::is_irreducible(&g), Ok(false));
+```
+*/
+pub fn is_irreducible<T: Numeric>(f: &Poly<T>) -> Result<bool, nt_error::NtError> {
+    let n = match f.degree() {
+        None => return Err(nt_error::NtError::BadArgument),
+        Some(0) => return Ok(false),
+        Some(n) => n,
+    };
+    poly::has_equal_degree_factors(&f.monic(), n)
+}
+
+/**
+The finite field GF(p^k) as polynomials mod p reduced by a monic irreducible of degree k
+
+Elements are made by the field and carry a copy of it so they can be combined without it.  The
+operators panic if the fields differ like those on Poly.  The Monoid implementation is the
+multiplicative group (with zero) and reports BadArgument for mismatched fields.
+
+# Examples
+
+```
+use number_theory::number_theory::finite_field::GaloisField;
+
+// GF(2^8) as used by AES
+let field = GaloisField::new(2, 8).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(field.modulus().coeffs(), &[1, 1, 0, 1, 1, 0, 0, 0, 1]);
+let a = field.element(vec![1, 1, 0, 0, 1, 0, 1]);
+let inv = a.inverse().unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(a * inv, field.one());
+```
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GaloisField<T: Numeric> {
+    p: T,
+    k: u32,
+    order: T,
+    modulus: Poly<T>,
+}
+
+impl<T: Numeric> GaloisField<T> {
+    /**
+    Creates GF(p^k) with the first monic irreducible polynomial of degree k as modulus
+
+    # Returns
+    * The field reduced by the least irreducible x^k + c_(k-1) x^(k-1) + ... + c_0 comparing
+      (c_(k-1), ..., c_0) lexicographically, found with is_irreducible.  These are searched in
+      turn and about one in k is irreducible.  This is a deterministic choice but not the Conway
+      polynomial, which would also need x to be primitive and compatible with the subfields - so
+      fields made here match other systems' GF(p^k) only up to isomorphism.  Use random for a
+      random modulus or with_modulus for a specific one.  BadArgument if p isn't prime or k is
+      zero and Overflow if p^k doesn't fit in T.
+    */
+    pub fn new(p: T, k: u32) -> Result<GaloisField<T>, nt_error::NtError> {
+        let order = GaloisField::check_size(p, k)?;
+        let k_usize = k as usize;
+        // Coefficients below the leading one as base p digits of index
+        let mut index = T::zero();
+        loop {
+            let mut coeffs = vec![T::zero(); k_usize + 1];
+            coeffs[k_usize] = T::one();
+            let mut digits = index;
+            for c in coeffs.iter_mut().take(k_usize) {
+                *c = digits % p;
+                digits = digits / p;
+            }
+            let f = Poly::new(coeffs, p)?;
+            if is_irreducible(&f)? {
+                return Ok(GaloisField {
+                    p,
+                    k,
+                    order,
+                    modulus: f,
+                });
+            }
+            index += T::one();
+        }
+    }
+
+    /**
+    Creates GF(p^k) with a random monic irreducible polynomial of degree k as modulus
+
+    # Arguments
+
+    * `p` - Prime characteristic
+    * `k` - Positive degree
+    * `seed` - Seed for the generator of the coefficients so the field can be made again
+
+    # Returns
+    * The field reduced by the first of a stream of random monic polynomials of degree k which
+      is_irreducible accepts - about one in k is so k tries are expected.  Errors as in new.
+
+    # Examples
+
+    ```
+    use number_theory::number_theory::finite_field::{self, GaloisField};
+
+    let field = GaloisField::random(5i64, 6, 1).unwrap_or_else(|_| panic!("Failed!"));
+    assert_eq!(finite_field::is_irreducible(field.modulus()), Ok(true));
+    let prim = field.primitive_element().unwrap_or_else(|_| panic!("Failed!"));
+    assert_eq!(prim.order(), Ok(15624));
+    ```
+    */
+    pub fn random(p: T, k: u32, seed: u64) -> Result<GaloisField<T>, nt_error::NtError> {
+        let order = GaloisField::check_size(p, k)?;
+        let k_usize = k as usize;
+        // A zero state would stay zero.  Every degree k >= 2 has p^2 dividing p^k so p fits in a
+        // u64 there and x alone is irreducible for k = 1.
+        let mut state = seed ^ 0x2545_f491_4f6c_dd1d;
+        if state == 0 {
+            state = 0x2545_f491_4f6c_dd1d;
+        }
+        let p_u64 = p.to_u64().filter(|_| k > 1);
+        loop {
+            let mut coeffs = vec![T::zero(); k_usize + 1];
+            coeffs[k_usize] = T::one();
+            if let Some(p_u64) = p_u64 {
+                for c in coeffs.iter_mut().take(k_usize) {
+                    *c = T::from_u64(poly::next_random(&mut state) % p_u64).unwrap();
+                }
+            }
+            let f = Poly::new(coeffs, p)?;
+            if is_irreducible(&f)? {
+                return Ok(GaloisField {
+                    p,
+                    k,
+                    order,
+                    modulus: f,
+                });
+            }
+        }
+    }
+
+    /**
+    Creates the field GF(p^k) reduced by a given irreducible polynomial of degree k
+
+    # Returns
+    * The field with the monic multiple of modulus as its modulus.  BadArgument if the modulus
+      isn't prime or the polynomial isn't irreducible and Overflow if p^k doesn't fit in T.
+    */
+    pub fn with_modulus(modulus: Poly<T>) -> Result<GaloisField<T>, nt_error::NtError> {
+        let p = modulus.modulus();
+        let k = match modulus.degree() {
+            Some(k) if k > 0 => k as u32,
+            _ => return Err(nt_error::NtError::BadArgument),
+        };
+        let order = GaloisField::check_size(p, k)?;
+        if !is_irreducible(&modulus)? {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        Ok(GaloisField {
+            p,
+            k,
+            order,
+            modulus: modulus.monic(),
+        })
+    }
+
+    // p^k after checking that p is prime and k positive
+    fn check_size(p: T, k: u32) -> Result<T, nt_error::NtError> {
+        if k == 0 || !factorization::is_prime(p)? {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        totient::prime_power(p, k)
+    }
+
+    pub fn characteristic(&self) -> T {
+        self.p
+    }
+
+    pub fn degree(&self) -> u32 {
+        self.k
+    }
+
+    // Number of elements p^k
+    pub fn order(&self) -> T {
+        self.order
+    }
+
+    pub fn modulus(&self) -> &Poly<T> {
+        &self.modulus
+    }
+
+    /// Element with the given polynomial coefficients, lowest degree first, reduced by the modulus
+    pub fn element(&self, coeffs: Vec<T>) -> FieldElement<T> {
+        let poly = Poly::new(coeffs, self.p).unwrap();
+        self.wrap(poly.div_rem(&self.modulus).1)
+    }
+
+    /// Image of c in the prime subfield
+    pub fn constant(&self, c: T) -> FieldElement<T> {
+        self.element(vec![c])
+    }
+
+    pub fn zero(&self) -> FieldElement<T> {
+        self.constant(T::zero())
+    }
+
+    pub fn one(&self) -> FieldElement<T> {
+        self.constant(T::one())
+    }
+
+    /// The class of x - a root of the modulus generating the field over GF(p)
+    pub fn root(&self) -> FieldElement<T> {
+        self.element(vec![T::zero(), T::one()])
+    }
+
+    /**
+    Returns the n-th element in a fixed enumeration of the field
+
+    # Returns
+    * The element whose coefficients are the base p digits of n, lowest first, so 0 and 1 give
+      zero and one.  BadArgument if n isn't in [0, p^k).
+    */
+    pub fn nth_element(&self, n: T) -> Result<FieldElement<T>, nt_error::NtError> {
+        if n < T::zero() || n >= self.order {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        let mut coeffs = Vec::new();
+        let mut digits = n;
+        while digits > T::zero() {
+            coeffs.push(digits % self.p);
+            digits = digits / self.p;
+        }
+        Ok(self.element(coeffs))
+    }
+
+    /**
+    Returns the first primitive element in the order of nth_element
+
+    # Returns
+    * A generator of the multiplicative group of order p^k - 1.  About phi(p^k - 1) / (p^k - 1)
+      of the elements are primitive.  p^k - 1 is factored by trial division.
+    */
+    pub fn primitive_element(&self) -> Result<FieldElement<T>, nt_error::NtError> {
+        let group_order = self.order - T::one();
+        let factors = factorization::factor(group_order)?;
+        let mut n = T::one();
+        loop {
+            let candidate = self.nth_element(n)?;
+            if candidate.order_with(&factors)? == group_order {
+                return Ok(candidate);
+            }
+            n += T::one();
+        }
+    }
+
+    fn wrap(&self, value: Poly<T>) -> FieldElement<T> {
+        FieldElement {
+            value,
+            field: self.clone(),
+        }
+    }
+}
+
+/**
+Element of a GaloisField
+
+# Examples
+
+```
+use number_theory::number_theory::finite_field::GaloisField;
+
+let field = GaloisField::new(3i64, 4).unwrap_or_else(|_| panic!("Failed!"));
+let a = field.element(vec![2, 0, 1, 1]);
+// The trace is additive and fixed by Frobenius
+assert_eq!(a.frobenius().trace(), a.trace());
+let sq = a.clone() * a.clone();
+let root = sq.sqrt().unwrap_or_else(|_| panic!("Failed!"));
+assert!(root == a || root == -a);
+```
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldElement<T: Numeric> {
+    value: Poly<T>,
+    field: GaloisField<T>,
+}
+
+impl<T: Numeric> FieldElement<T> {
+    /// Coefficients of the representing polynomial of degree less than k, lowest first
+    pub fn coeffs(&self) -> &[T] {
+        self.value.coeffs()
+    }
+
+    pub fn field(&self) -> &GaloisField<T> {
+        &self.field
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.value.is_zero()
+    }
+
+    fn check_field(&self, other: &FieldElement<T>) {
+        assert!(self.field == other.field, "Fields differ");
+    }
+
+    /**
+    Returns the multiplicative inverse
+
+    # Returns
+    * self^-1 from the extended Euclidean algorithm on the representative and the modulus.
+      NoSolns for zero.
+    */
+    pub fn inverse(&self) -> Result<FieldElement<T>, nt_error::NtError> {
+        let inv = euclidean::inverse_mod(self.value.clone(), self.field.modulus.clone())?;
+        Ok(self.field.wrap(inv))
+    }
+
+    /**
+    Returns self^exp
+
+    # Returns
+    * self^exp by square and multiply on the representative - negative exponents invert first.
+      NoSolns if exp is negative and self is zero.
+    */
+    pub fn pow(&self, exp: T) -> Result<FieldElement<T>, nt_error::NtError> {
+        let (base, exp) = if exp < T::zero() {
+            (self.inverse()?, -exp)
+        } else {
+            (self.clone(), exp)
+        };
+        let value = base.value.pow_mod(exp, &self.field.modulus)?;
+        Ok(self.field.wrap(value))
+    }
+
+    /**
+    Returns the Frobenius image self^p
+
+    Frobenius is a field automorphism fixing GF(p) and generating the Galois group of order k.
+    */
+    pub fn frobenius(&self) -> FieldElement<T> {
+        self.pow(self.field.p).unwrap()
+    }
+
+    // self, self^p, ..., self^(p^(k - 1))
+    fn conjugates(&self) -> Vec<FieldElement<T>> {
+        let mut ret = vec![self.clone()];
+        for _ in 1..self.field.k {
+            let next = ret.last().unwrap().frobenius();
+            ret.push(next);
+        }
+        ret
+    }
+
+    /**
+    Returns the trace down to GF(p) - the sum of the k conjugates
+    */
+    pub fn trace(&self) -> T {
+        let sum = self
+            .conjugates()
+            .into_iter()
+            .fold(self.field.zero(), |acc, c| acc + c);
+        *sum.coeffs().first().unwrap_or(&T::zero())
+    }
+
+    /**
+    Returns the norm down to GF(p) - the product of the k conjugates
+    */
+    pub fn norm(&self) -> T {
+        let prod = self
+            .conjugates()
+            .into_iter()
+            .fold(self.field.one(), |acc, c| acc * c);
+        *prod.coeffs().first().unwrap_or(&T::zero())
+    }
+
+    /**
+    Returns the multiplicative order of a nonzero element
+
+    # Returns
+    * The least n > 0 with self^n = 1.  Starting from p^k - 1 each prime factor (found by trial
+      division) is divided out while the power stays 1.  BadArgument for zero.
+    */
+    pub fn order(&self) -> Result<T, nt_error::NtError> {
+        let factors = factorization::factor(self.field.order - T::one())?;
+        self.order_with(&factors)
+    }
+
+    // order given the factorization of p^k - 1
+    fn order_with(&self, factors: &[(T, u32)]) -> Result<T, nt_error::NtError> {
+        if self.is_zero() {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        let one = self.field.one();
+        let mut ret = self.field.order - T::one();
+        for &(q, _) in factors.iter() {
+            while ret % q == T::zero() && self.pow(ret / q)? == one {
+                ret = ret / q;
+            }
+        }
+        Ok(ret)
+    }
+
+    pub fn is_primitive(&self) -> Result<bool, nt_error::NtError> {
+        Ok(!self.is_zero() && self.order()? == self.field.order - T::one())
+    }
+
+    /**
+    Returns whether self is a square - zero is
+    */
+    pub fn is_square(&self) -> Result<bool, nt_error::NtError> {
+        let two = T::from_u8(2).unwrap();
+        if self.is_zero() || self.field.p == two {
+            return Ok(true);
+        }
+        Ok(self.pow((self.field.order - T::one()) / two)? == self.field.one())
+    }
+
+    /**
+    Returns a square root
+
+    # Returns
+    * r with r^2 = self - the other root is -r.  In characteristic 2 squaring is a bijection
+      with inverse self^(2^(k - 1)).  Otherwise Tonelli-Shanks in the multiplicative group with
+      the first non-square in the order of nth_element.  NoSolns if self isn't a square.
+    */
+    pub fn sqrt(&self) -> Result<FieldElement<T>, nt_error::NtError> {
+        let field = &self.field;
+        let two = T::from_u8(2).unwrap();
+        if self.is_zero() {
+            return Ok(self.clone());
+        }
+        if field.p == two {
+            return self.pow(field.order / two);
+        }
+        if !self.is_square()? {
+            return Err(nt_error::NtError::NoSolns);
+        }
+
+        // p^k - 1 = q 2^s with q odd
+        let mut q = field.order - T::one();
+        let mut s = 0u32;
+        while q % two == T::zero() {
+            q = q / two;
+            s += 1;
+        }
+        let one = field.one();
+        let mut n = two;
+        let z = loop {
+            let z = field.nth_element(n)?;
+            if !z.is_square()? {
+                break z;
+            }
+            n += T::one();
+        };
+        let mut m = s;
+        let mut c = z.pow(q)?;
+        let mut t = self.pow(q)?;
+        let mut x = self.pow((q + T::one()) / two)?;
+
+        // Invariant: x^2 = self t with t of order 2^i for some i < m
+        while t != one {
+            let mut i = 0u32;
+            let mut t_pow = t.clone();
+            while t_pow != one {
+                t_pow = t_pow.clone() * t_pow;
+                i += 1;
+            }
+            let mut b = c;
+            for _ in 0..(m - i - 1) {
+                b = b.clone() * b;
+            }
+            m = i;
+            c = b.clone() * b.clone();
+            t = t * c.clone();
+            x = x * b;
+        }
+        Ok(x)
+    }
+}
+
+impl<T: Numeric> Add for FieldElement<T> {
+    type Output = FieldElement<T>;
+
+    fn add(self, other: FieldElement<T>) -> FieldElement<T> {
+        self.check_field(&other);
+        self.field.wrap(self.value + other.value)
+    }
+}
+
+impl<T: Numeric> Neg for FieldElement<T> {
+    type Output = FieldElement<T>;
+
+    fn neg(self) -> FieldElement<T> {
+        self.field.wrap(-self.value)
+    }
+}
+
+impl<T: Numeric> Sub for FieldElement<T> {
+    type Output = FieldElement<T>;
+
+    fn sub(self, other: FieldElement<T>) -> FieldElement<T> {
+        self + (-other)
+    }
+}
+
+impl<T: Numeric> Mul for FieldElement<T> {
+    type Output = FieldElement<T>;
+
+    fn mul(self, other: FieldElement<T>) -> FieldElement<T> {
+        self.check_field(&other);
+        let prod = (self.value * other.value).div_rem(&self.field.modulus).1;
+        self.field.wrap(prod)
+    }
+}
+
+impl<T: Numeric> Monoid for FieldElement<T> {
+    const COMMUTATIVE: bool = true;
+
+    fn identity(&self) -> Self {
+        self.field.one()
+    }
+
+    fn op(&self, other: &Self) -> Result<Self, nt_error::NtError> {
+        if self.field != other.field {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        Ok(self.clone() * other.clone())
+    }
+
+    fn inverse(&self) -> Result<Self, nt_error::NtError> {
+        FieldElement::inverse(self)
+    }
+}
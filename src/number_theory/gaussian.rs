@@ -0,0 +1,288 @@
+use crate::number_theory::continued_fraction::floor_div;
+use crate::number_theory::euclidean::{gcd, EuclideanDomain};
+use crate::number_theory::factorization;
+use crate::number_theory::monoid::Monoid;
+use crate::number_theory::sqrt_mod;
+use crate::utilities::nt_error;
+use crate::utilities::numeric_trait::Numeric;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/**
+Gaussian integer re + im i
+
+The arithmetic operators are unchecked like those on T.  Use checked_mul, norm or the Monoid
+implementation when overflow is a possibility.
+
+# Examples
+
+```
+use number_theory::number_theory::euclidean;
+use number_theory::number_theory::gaussian::GaussianInt;
+
+let a = GaussianInt::new(11, 3);
+let b = GaussianInt::new(1, 8);
+let (gcd, c1, c2) = euclidean::calc_euclidean_ext(a, b);
+assert_eq!(gcd, GaussianInt::new(2, 1));
+assert_eq!(c1 * a + c2 * b, gcd);
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GaussianInt<T: Numeric> {
+    re: T,
+    im: T,
+}
+
+impl<T: Numeric> GaussianInt<T> {
+    pub fn new(re: T, im: T) -> GaussianInt<T> {
+        GaussianInt { re, im }
+    }
+
+    pub fn re(&self) -> T {
+        self.re
+    }
+
+    pub fn im(&self) -> T {
+        self.im
+    }
+
+    pub fn conj(&self) -> GaussianInt<T> {
+        GaussianInt {
+            re: self.re,
+            im: -self.im,
+        }
+    }
+
+    /**
+    Returns the norm re^2 + im^2 or Overflow
+    */
+    pub fn norm(&self) -> Result<T, nt_error::NtError> {
+        self.re
+            .checked_mul(&self.re)
+            .and_then(|re2| {
+                self.im
+                    .checked_mul(&self.im)
+                    .and_then(|im2| re2.checked_add(&im2))
+            })
+            .ok_or(nt_error::NtError::Overflow)
+    }
+
+    pub fn is_unit(&self) -> bool {
+        self.norm() == Ok(T::one())
+    }
+
+    /**
+    Returns self * other or Overflow
+    */
+    pub fn checked_mul(&self, other: &GaussianInt<T>) -> Result<GaussianInt<T>, nt_error::NtError> {
+        let overflow = || nt_error::NtError::Overflow;
+        let prod = |a: T, b: T| a.checked_mul(&b).ok_or_else(overflow);
+        let re = prod(self.re, other.re)?
+            .checked_sub(&prod(self.im, other.im)?)
+            .ok_or_else(overflow)?;
+        let im = prod(self.re, other.im)?
+            .checked_add(&prod(self.im, other.re)?)
+            .ok_or_else(overflow)?;
+        Ok(GaussianInt { re, im })
+    }
+}
+
+impl<T: Numeric> From<T> for GaussianInt<T> {
+    fn from(re: T) -> GaussianInt<T> {
+        GaussianInt::new(re, T::zero())
+    }
+}
+
+impl<T: Numeric> Add for GaussianInt<T> {
+    type Output = GaussianInt<T>;
+
+    fn add(self, other: GaussianInt<T>) -> GaussianInt<T> {
+        GaussianInt::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl<T: Numeric> Sub for GaussianInt<T> {
+    type Output = GaussianInt<T>;
+
+    fn sub(self, other: GaussianInt<T>) -> GaussianInt<T> {
+        GaussianInt::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl<T: Numeric> Mul for GaussianInt<T> {
+    type Output = GaussianInt<T>;
+
+    fn mul(self, other: GaussianInt<T>) -> GaussianInt<T> {
+        GaussianInt::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl<T: Numeric> Neg for GaussianInt<T> {
+    type Output = GaussianInt<T>;
+
+    fn neg(self) -> GaussianInt<T> {
+        GaussianInt::new(-self.re, -self.im)
+    }
+}
+
+impl<T: Numeric + fmt::Display> fmt::Display for GaussianInt<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.im < T::zero() {
+            write!(f, "{}-{}i", self.re, -self.im)
+        } else {
+            write!(f, "{}+{}i", self.re, self.im)
+        }
+    }
+}
+
+// Nearest integer to num / den for den > 0 with halves rounded down.  Works from the remainder so
+// nothing is doubled.
+fn round_div<T: Numeric>(num: T, den: T) -> T {
+    let q = floor_div(num, den);
+    let r = num - q * den;
+    if r > den - r {
+        q + T::one()
+    } else {
+        q
+    }
+}
+
+impl<T: Numeric> EuclideanDomain for GaussianInt<T> {
+    type Norm = T;
+
+    fn zero_like(&self) -> GaussianInt<T> {
+        GaussianInt::new(T::zero(), T::zero())
+    }
+
+    fn one_like(&self) -> GaussianInt<T> {
+        GaussianInt::new(T::one(), T::zero())
+    }
+
+    // Rounding self / other = self * conj(other) / norm(other) to the nearest Gaussian integer
+    // leaves a remainder with at most half the norm of other
+    fn div_rem(&self, other: &GaussianInt<T>) -> (GaussianInt<T>, GaussianInt<T>) {
+        let n = EuclideanDomain::norm(other);
+        let num = *self * other.conj();
+        let q = GaussianInt::new(round_div(num.re, n), round_div(num.im, n));
+        (q, *self - q * *other)
+    }
+
+    // Unchecked unlike the inherent norm
+    fn norm(&self) -> T {
+        self.re * self.re + self.im * self.im
+    }
+
+    // Moves nonzero values into the quadrant re > 0, im >= 0
+    fn normalizing_unit(&self) -> GaussianInt<T> {
+        let (zero, one) = (T::zero(), T::one());
+        if self.re > zero && self.im >= zero || self.is_zero() {
+            GaussianInt::new(one, zero)
+        } else if self.re <= zero && self.im > zero {
+            GaussianInt::new(zero, -one)
+        } else if self.re < zero && self.im <= zero {
+            GaussianInt::new(-one, zero)
+        } else {
+            GaussianInt::new(zero, one)
+        }
+    }
+
+    fn try_mul(&self, other: &GaussianInt<T>) -> Result<GaussianInt<T>, nt_error::NtError> {
+        self.checked_mul(other)
+    }
+}
+
+impl<T: Numeric> Monoid for GaussianInt<T> {
+    const COMMUTATIVE: bool = true;
+
+    fn identity(&self) -> Self {
+        self.one_like()
+    }
+
+    fn op(&self, other: &Self) -> Result<Self, nt_error::NtError> {
+        self.checked_mul(other)
+    }
+}
+
+// Divides out as many factors of prime as possible from z returning the count
+fn remove_factor<T: Numeric>(z: &mut GaussianInt<T>, prime: GaussianInt<T>) -> u32 {
+    let mut count = 0;
+    loop {
+        let (q, r) = z.div_rem(&prime);
+        if !r.is_zero() {
+            return count;
+        }
+        *z = q;
+        count += 1;
+    }
+}
+
+/**
+Returns the factorization of a Gaussian integer into Gaussian primes
+
+# Arguments
+
+* `z` - Nonzero Gaussian integer
+
+# Returns
+* (unit, factors) with z = unit * product of prime^exponent over the factors.  The primes are
+  normalized into the quadrant re > 0, im >= 0 and sorted by norm and then real part.  The norm
+  of z is factored by trial division.  Each rational prime p = 1 (mod 4) splits as
+  gcd(p, x + i) and its conjugate where x is a square root of -1 mod p, primes p = 3 (mod 4) stay
+  prime and 2 ramifies as -i (1 + i)^2.  BadArgument for zero and Overflow if the norm or its
+  square doesn't fit in T.
+
+# Examples
+
+```
+use number_theory::number_theory::gaussian::{self, GaussianInt};
+
+// 5 = (2 + i)(2 - i) = -i (1 + 2i)(2 + i)
+let (unit, factors) = gaussian::factor(GaussianInt::new(5i64, 0))
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(unit, GaussianInt::new(0, -1));
+assert_eq!(
+    factors,
+    vec![(GaussianInt::new(1, 2), 1), (GaussianInt::new(2, 1), 1)]
+);
+```
+*/
+#[allow(clippy::type_complexity)]
+pub fn factor<T: Numeric>(
+    z: GaussianInt<T>,
+) -> Result<(GaussianInt<T>, Vec<(GaussianInt<T>, u32)>), nt_error::NtError> {
+    if z.is_zero() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let one = T::one();
+    let four = T::from_u8(4).unwrap();
+    let mut rest = z;
+    let mut ret = Vec::new();
+    for (p, exp) in factorization::factor(z.norm()?)? {
+        if p == T::from_u8(2).unwrap() {
+            let prime = GaussianInt::new(one, one);
+            ret.push((prime, remove_factor(&mut rest, prime)));
+        } else if p % four == T::from_u8(3).unwrap() {
+            // Norm p^2 so p^exp in the norm means p^(exp / 2) in z
+            let prime = GaussianInt::from(p);
+            ret.push((prime, remove_factor(&mut rest, prime)));
+        } else {
+            let x = sqrt_mod::sqrt_mod_prime(-one, p)?;
+            let pi = gcd(GaussianInt::from(p), GaussianInt::new(x, one));
+            let pi_bar = pi.conj();
+            let pi_bar = pi_bar.normalizing_unit() * pi_bar;
+            let count = remove_factor(&mut rest, pi);
+            if count > 0 {
+                ret.push((pi, count));
+            }
+            if count < exp {
+                ret.push((pi_bar, remove_factor(&mut rest, pi_bar)));
+            }
+        }
+    }
+    ret.sort_by_key(|&(prime, _)| (prime.re * prime.re + prime.im * prime.im, prime.re));
+    debug_assert!(rest.is_unit());
+    Ok((rest, ret))
+}
@@ -0,0 +1,670 @@
+use crate::number_theory::euclidean;
+use crate::utilities::bit_ops::BitOps;
+use crate::utilities::nt_error;
+use crate::utilities::numeric_trait::Numeric;
+
+/**
+An associative operation with an identity
+
+The identity is produced from an existing element since it frequently depends on context carried
+by the element (a modulus, a matrix dimension, a curve...).  The operation is fallible so that
+fixed width implementations can report Overflow and mismatched operands can report BadArgument.
+*/
+pub trait Monoid: Sized + Clone {
+    /// Whether a op b = b op a always.  Simultaneous exponentiation only reorders factors when
+    /// this is set.
+    const COMMUTATIVE: bool = false;
+
+    /// Identity element in the same monoid as self
+    fn identity(&self) -> Self;
+
+    /// The monoid operation self * other
+    fn op(&self, other: &Self) -> Result<Self, nt_error::NtError>;
+
+    /// Inverse of self if it has one.  Only signed digit exponentiation needs this so the default
+    /// simply reports BadArgument.
+    fn inverse(&self) -> Result<Self, nt_error::NtError> {
+        Err(nt_error::NtError::BadArgument)
+    }
+}
+
+/**
+Bits of a nonnegative exponent
+
+Exponentiation only looks at the bits of the exponent so it needn't be the same type as anything
+in the monoid.  Besides the signed integers this is implemented for little endian u64 limbs and
+for bits stored least significant first so that RSA sized exponents can be used with any monoid.
+
+# Examples
+
+```
+use number_theory::number_theory::monoid::{pow_monoid, Exponent, ModInt};
+
+// 2^64 + 3 as limbs
+let limbs: &[u64] = &[3, 1];
+assert_eq!(limbs.bit_length(), Ok(65));
+let base = ModInt::new(3i64, 1000003).unwrap_or_else(|_| panic!("Failed!"));
+let val = pow_monoid(&base, limbs).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(val, pow_monoid(&base, (1i128 << 64) + 3).unwrap_or_else(|_| panic!("Failed!")));
+```
+*/
+pub trait Exponent {
+    /// Number of bits up to and including the leftmost one - BadArgument for negative values
+    fn bit_length(&self) -> Result<usize, nt_error::NtError>;
+
+    /// Bit i with bit 0 the least significant - false beyond the leftmost one
+    fn bit(&self, i: usize) -> bool;
+}
+
+impl<T: Numeric> Exponent for T {
+    fn bit_length(&self) -> Result<usize, nt_error::NtError> {
+        Ok(BitOps::bit_length(*self)? as usize)
+    }
+
+    fn bit(&self, i: usize) -> bool {
+        i < T::BITS as usize && ((*self >> i) & T::one()) != T::zero()
+    }
+}
+
+impl Exponent for &[u64] {
+    fn bit_length(&self) -> Result<usize, nt_error::NtError> {
+        Ok(match self.iter().rposition(|&limb| limb != 0) {
+            None => 0,
+            Some(top) => 64 * top + (64 - self[top].leading_zeros()) as usize,
+        })
+    }
+
+    fn bit(&self, i: usize) -> bool {
+        self.get(i / 64)
+            .is_some_and(|limb| (limb >> (i % 64)) & 1 == 1)
+    }
+}
+
+impl Exponent for &[bool] {
+    fn bit_length(&self) -> Result<usize, nt_error::NtError> {
+        Ok(self.iter().rposition(|&bit| bit).map_or(0, |top| top + 1))
+    }
+
+    fn bit(&self, i: usize) -> bool {
+        self.get(i).copied().unwrap_or(false)
+    }
+}
+
+/**
+Returns base^exp for any monoid by square and multiply
+
+# Arguments
+
+* `base` - Element to be raised to a power
+* `exp` - Nonnegative exponent - any Exponent so it may be wider than the monoid's values
+
+# Returns
+* base op'ed with itself exp times (the identity for exp = 0) or BadArgument if exp is negative.
+  Errors from the monoid operation are passed through.
+
+# Examples
+
+```
+use number_theory::number_theory::monoid::{pow_monoid, Matrix2};
+
+// Fibonacci numbers from powers of [[1, 1], [1, 0]]
+let fib = Matrix2::new([[1i64, 1], [1, 0]]);
+let fib_90 = pow_monoid(&fib, 90).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(fib_90.entries()[0][1], 2880067194370816120);
+```
+*/
+pub fn pow_monoid<M: Monoid, E: Exponent>(base: &M, exp: E) -> Result<M, nt_error::NtError> {
+    pow_binary(base, &exp)
+}
+
+fn pow_binary<M: Monoid, E: Exponent>(base: &M, exp: &E) -> Result<M, nt_error::NtError> {
+    let mut res = base.identity();
+    for bit in (0..exp.bit_length()?).rev() {
+        res = res.op(&res)?;
+        if exp.bit(bit) {
+            res = res.op(base)?;
+        }
+    }
+    Ok(res)
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+// Windowed exponentiation
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Largest window pow_monoid_with will build a table for
+const MAX_WINDOW: u32 = 16;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PowerMethod {
+    /// Left to right square and multiply - one multiply per set bit
+    Binary,
+    /// k-ary method with a table of all 2^k powers - one multiply per k bits
+    FixedWindow(u32),
+    /// Sliding windows of at most k bits with a table of the 2^(k-1) odd powers
+    SlidingWindow(u32),
+    /// Non-adjacent form - signed binary digits which need the inverse of the base.  Binary is
+    /// used instead when Monoid::inverse reports NoSolns or BadArgument for the base.
+    Naf,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PowerOptions {
+    pub method: PowerMethod,
+}
+
+impl Default for PowerOptions {
+    fn default() -> Self {
+        PowerOptions {
+            method: PowerMethod::Binary,
+        }
+    }
+}
+
+/**
+Returns base^exp for any monoid using the exponentiation method given in the options
+
+# Arguments
+
+* `base` - Element to be raised to a power
+* `exp` - Nonnegative exponent - any Exponent as in pow_monoid
+* `options` - Which exponentiation method to use
+
+# Returns
+* base^exp or BadArgument if exp is negative or a window size is outside [1, 16].  Naf falls back
+  on square and multiply when Monoid::inverse reports NoSolns or BadArgument for the base and
+  passes its other errors through.
+
+# Examples
+
+```
+use number_theory::number_theory::monoid::{
+    pow_monoid_with, ModInt, PowerMethod, PowerOptions,
+};
+
+let base = ModInt::new(2357i64, 3599).unwrap_or_else(|_| panic!("Failed!"));
+let options = PowerOptions {
+    method: PowerMethod::SlidingWindow(4),
+};
+let val = pow_monoid_with(&base, 2357, &options).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(val.value(), 3115);
+```
+*/
+pub fn pow_monoid_with<M: Monoid, E: Exponent>(
+    base: &M,
+    exp: E,
+    options: &PowerOptions,
+) -> Result<M, nt_error::NtError> {
+    match options.method {
+        PowerMethod::Binary => pow_binary(base, &exp),
+        PowerMethod::FixedWindow(k) => pow_fixed_window(base, &exp_bits(&exp)?, check_window(k)?),
+        PowerMethod::SlidingWindow(k) => {
+            pow_sliding_window(base, &exp_bits(&exp)?, check_window(k)?)
+        }
+        PowerMethod::Naf => pow_naf(base, &exp),
+    }
+}
+
+fn check_window(k: u32) -> Result<usize, nt_error::NtError> {
+    if k == 0 || k > MAX_WINDOW {
+        Err(nt_error::NtError::BadArgument)
+    } else {
+        Ok(k as usize)
+    }
+}
+
+// Bits of a nonnegative exponent, least significant first
+fn exp_bits<E: Exponent>(exp: &E) -> Result<Vec<bool>, nt_error::NtError> {
+    Ok((0..exp.bit_length()?).map(|bit| exp.bit(bit)).collect())
+}
+
+// res^(2^count)
+fn square_times<M: Monoid>(res: M, count: usize) -> Result<M, nt_error::NtError> {
+    let mut res = res;
+    for _ in 0..count {
+        res = res.op(&res)?;
+    }
+    Ok(res)
+}
+
+fn pow_fixed_window<M: Monoid>(base: &M, bits: &[bool], k: usize) -> Result<M, nt_error::NtError> {
+    // table[i] = base^i
+    let mut table = vec![base.identity()];
+    for i in 1..(1usize << k) {
+        table.push(table[i - 1].op(base)?);
+    }
+
+    let mut res = base.identity();
+    let digits = bits.len().div_ceil(k);
+    for digit in (0..digits).rev() {
+        if digit != digits - 1 {
+            res = square_times(res, k)?;
+        }
+        let low = digit * k;
+        let high = (low + k).min(bits.len());
+        let value = (low..high)
+            .rev()
+            .fold(0, |acc, bit| (acc << 1) | bits[bit] as usize);
+        if value != 0 {
+            res = res.op(&table[value])?;
+        }
+    }
+    Ok(res)
+}
+
+fn pow_sliding_window<M: Monoid>(
+    base: &M,
+    bits: &[bool],
+    k: usize,
+) -> Result<M, nt_error::NtError> {
+    // table[i] = base^(2i + 1)
+    let square = base.op(base)?;
+    let mut table = vec![base.clone()];
+    for i in 1..(1usize << (k - 1)) {
+        table.push(table[i - 1].op(&square)?);
+    }
+
+    let mut res = base.identity();
+    let mut top = bits.len();
+    while top > 0 {
+        let high = top - 1;
+        if !bits[high] {
+            res = res.op(&res)?;
+            top = high;
+            continue;
+        }
+        // Longest window of at most k bits starting at high and ending in a one
+        let mut low = high.saturating_sub(k - 1);
+        while !bits[low] {
+            low += 1;
+        }
+        let value = (low..=high)
+            .rev()
+            .fold(0, |acc, bit| (acc << 1) | bits[bit] as usize);
+        res = square_times(res, high - low + 1)?;
+        res = res.op(&table[value >> 1])?;
+        top = low;
+    }
+    Ok(res)
+}
+
+fn pow_naf<M: Monoid, E: Exponent>(base: &M, exp: &E) -> Result<M, nt_error::NtError> {
+    // Signed digits, least significant first, from the bits plus a carry.  A one followed by
+    // another one becomes -1 with a carry into the next place.
+    let mut digits = Vec::new();
+    let mut carry = false;
+    for bit in 0..=exp.bit_length()? {
+        let digit = match (exp.bit(bit), carry) {
+            (false, false) => 0i8,
+            (true, true) => 0,
+            _ if exp.bit(bit + 1) => {
+                carry = true;
+                -1
+            }
+            _ => {
+                carry = false;
+                1
+            }
+        };
+        digits.push(digit);
+    }
+    if digits.last() == Some(&0) {
+        digits.pop();
+    }
+
+    let base_inv = if digits.contains(&-1) {
+        match base.inverse() {
+            Ok(inv) => Some(inv),
+            // No inverse - NoSolns for elements without one and BadArgument from the default
+            Err(nt_error::NtError::NoSolns) | Err(nt_error::NtError::BadArgument) => {
+                return pow_binary(base, exp)
+            }
+            Err(err) => return Err(err),
+        }
+    } else {
+        None
+    };
+    let mut res = base.identity();
+    for &digit in digits.iter().rev() {
+        res = res.op(&res)?;
+        match (digit, &base_inv) {
+            (1, _) => res = res.op(base)?,
+            (-1, Some(inv)) => res = res.op(inv)?,
+            _ => (),
+        }
+    }
+    Ok(res)
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+// Simultaneous exponentiation
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Up to this many bases use Straus' method with a table of all subset products - beyond it the
+// table gets too large and Pippenger's bucket method takes over
+const STRAUS_MAX: usize = 4;
+
+/**
+Returns the product of base^exp over a list of pairs sharing a single chain of squarings
+
+# Arguments
+
+* `pairs` - Nonempty list of (base, exp) pairs with nonnegative exponents of any Exponent type
+
+# Returns
+* The product of all the base^exp in the order given.  BadArgument if the list is empty or an
+  exponent is negative.  Errors from the monoid operation are passed through.
+
+For commutative monoids a few bases use Straus' interleaving (Shamir's trick for two bases) which
+needs one squaring per bit of the largest exponent in total rather than per base.  More bases use
+Pippenger's method which sorts window digits into buckets so that the number of multiplies grows
+more slowly than the number of bases.  Both reorder the factors so other monoids fall back on
+pow_monoid for each pair.
+
+# Examples
+
+```
+use number_theory::number_theory::monoid::{multi_pow_monoid, ModInt};
+
+let g = ModInt::new(2i64, 1000003).unwrap_or_else(|_| panic!("Failed!"));
+let h = ModInt::new(3i64, 1000003).unwrap_or_else(|_| panic!("Failed!"));
+let val = multi_pow_monoid(&[(g, 100), (h, 200)]).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(val.value(), 608872);
+```
+*/
+pub fn multi_pow_monoid<M: Monoid, E: Exponent>(pairs: &[(M, E)]) -> Result<M, nt_error::NtError> {
+    if pairs.is_empty() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let bits = pairs
+        .iter()
+        .map(|(_, exp)| exp_bits(exp))
+        .collect::<Result<Vec<Vec<bool>>, nt_error::NtError>>()?;
+    if !M::COMMUTATIVE {
+        let mut res = pairs[0].0.identity();
+        for ((base, _), b) in pairs.iter().zip(bits.iter()) {
+            res = res.op(&pow_binary(base, &b.as_slice())?)?;
+        }
+        return Ok(res);
+    }
+    let bases: Vec<&M> = pairs.iter().map(|(base, _)| base).collect();
+    if pairs.len() <= STRAUS_MAX {
+        multi_pow_straus(&bases, &bits)
+    } else {
+        multi_pow_pippenger(&bases, &bits)
+    }
+}
+
+fn multi_pow_straus<M: Monoid>(bases: &[&M], bits: &[Vec<bool>]) -> Result<M, nt_error::NtError> {
+    // table[mask] = product of the bases whose bits are set in mask
+    let mut table = vec![bases[0].identity()];
+    for mask in 1usize..(1 << bases.len()) {
+        let low = mask.trailing_zeros() as usize;
+        let entry = table[mask & (mask - 1)].op(bases[low])?;
+        table.push(entry);
+    }
+
+    let mut res = bases[0].identity();
+    let top = bits.iter().map(|b| b.len()).max().unwrap();
+    for bit in (0..top).rev() {
+        res = res.op(&res)?;
+        let mask = bits
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| bit < b.len() && b[bit])
+            .fold(0, |acc, (i, _)| acc | (1 << i));
+        if mask != 0 {
+            res = res.op(&table[mask])?;
+        }
+    }
+    Ok(res)
+}
+
+fn multi_pow_pippenger<M: Monoid>(
+    bases: &[&M],
+    bits: &[Vec<bool>],
+) -> Result<M, nt_error::NtError> {
+    let window = (usize::BITS - 1 - bases.len().leading_zeros()) as usize;
+    let top = bits.iter().map(|b| b.len()).max().unwrap();
+    let windows = top.div_ceil(window);
+
+    let mut res = bases[0].identity();
+    for w in (0..windows).rev() {
+        if w != windows - 1 {
+            res = square_times(res, window)?;
+        }
+        // buckets[d - 1] = product of the bases whose digit in this window is d
+        let mut buckets: Vec<Option<M>> = vec![None; (1 << window) - 1];
+        for (base, b) in bases.iter().zip(bits.iter()) {
+            let low = w * window;
+            let high = (low + window).min(b.len());
+            let digit = (low..high)
+                .rev()
+                .fold(0, |acc, bit| (acc << 1) | b[bit] as usize);
+            if digit != 0 {
+                buckets[digit - 1] = Some(match &buckets[digit - 1] {
+                    None => (*base).clone(),
+                    Some(val) => val.op(base)?,
+                });
+            }
+        }
+        // sum of d * bucket[d] from running partial products taken from the top down
+        let mut running: Option<M> = None;
+        for bucket in buckets.iter().rev() {
+            if let Some(val) = bucket {
+                running = Some(match &running {
+                    None => val.clone(),
+                    Some(run) => run.op(val)?,
+                });
+            }
+            if let Some(run) = &running {
+                res = res.op(run)?;
+            }
+        }
+    }
+    Ok(res)
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+// Integers mod m under multiplication
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ModInt<T: Numeric> {
+    value: T,
+    modulo: T,
+}
+
+impl<T: Numeric> ModInt<T> {
+    /// value mod modulo reduced to [0, modulo) - BadArgument if modulo isn't positive
+    pub fn new(value: T, modulo: T) -> Result<ModInt<T>, nt_error::NtError> {
+        if modulo <= T::zero() {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        let mut value = value % modulo;
+        if value < T::zero() {
+            value += modulo;
+        }
+        Ok(ModInt { value, modulo })
+    }
+
+    pub fn value(&self) -> T {
+        self.value
+    }
+
+    pub fn modulo(&self) -> T {
+        self.modulo
+    }
+}
+
+impl<T: Numeric> Monoid for ModInt<T> {
+    const COMMUTATIVE: bool = true;
+
+    fn identity(&self) -> Self {
+        ModInt {
+            value: T::one() % self.modulo,
+            modulo: self.modulo,
+        }
+    }
+
+    fn op(&self, other: &Self) -> Result<Self, nt_error::NtError> {
+        if self.modulo != other.modulo {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        match self.value.checked_mul(&other.value) {
+            None => Err(nt_error::NtError::Overflow),
+            Some(val) => Ok(ModInt {
+                value: val % self.modulo,
+                modulo: self.modulo,
+            }),
+        }
+    }
+
+    fn inverse(&self) -> Result<Self, nt_error::NtError> {
+        Ok(ModInt {
+            value: euclidean::inverse_mod(self.value, self.modulo)?,
+            modulo: self.modulo,
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+// 2x2 matrices under multiplication
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Matrix2<T: Numeric> {
+    entries: [[T; 2]; 2],
+    // Entries are reduced mod this if present
+    modulo: Option<T>,
+}
+
+impl<T: Numeric> Matrix2<T> {
+    /// Matrix over the integers - products report Overflow if they don't fit in T
+    pub fn new(entries: [[T; 2]; 2]) -> Matrix2<T> {
+        Matrix2 {
+            entries,
+            modulo: None,
+        }
+    }
+
+    /// Matrix with entries reduced mod modulo - BadArgument if modulo isn't positive
+    pub fn with_modulus(entries: [[T; 2]; 2], modulo: T) -> Result<Matrix2<T>, nt_error::NtError> {
+        if modulo <= T::zero() {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        let mut entries = entries;
+        for row in entries.iter_mut() {
+            for entry in row.iter_mut() {
+                *entry = ModInt::new(*entry, modulo)?.value();
+            }
+        }
+        Ok(Matrix2 {
+            entries,
+            modulo: Some(modulo),
+        })
+    }
+
+    pub fn entries(&self) -> [[T; 2]; 2] {
+        self.entries
+    }
+
+    // a * b + c * d reduced by the modulus if there is one
+    fn dot(&self, a: T, b: T, c: T, d: T) -> Result<T, nt_error::NtError> {
+        let (ab, cd) = match (a.checked_mul(&b), c.checked_mul(&d)) {
+            (Some(ab), Some(cd)) => (ab, cd),
+            _ => return Err(nt_error::NtError::Overflow),
+        };
+        let (ab, cd) = match self.modulo {
+            None => (ab, cd),
+            Some(m) => (ab % m, cd % m),
+        };
+        let sum = ab.checked_add(&cd).ok_or(nt_error::NtError::Overflow)?;
+        Ok(match self.modulo {
+            None => sum,
+            Some(m) => sum % m,
+        })
+    }
+}
+
+impl<T: Numeric> Monoid for Matrix2<T> {
+    fn identity(&self) -> Self {
+        let one = match self.modulo {
+            Some(m) => T::one() % m,
+            None => T::one(),
+        };
+        Matrix2 {
+            entries: [[one, T::zero()], [T::zero(), one]],
+            modulo: self.modulo,
+        }
+    }
+
+    fn op(&self, other: &Self) -> Result<Self, nt_error::NtError> {
+        if self.modulo != other.modulo {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        let [[a, b], [c, d]] = self.entries;
+        let [[e, f], [g, h]] = other.entries;
+        Ok(Matrix2 {
+            entries: [
+                [self.dot(a, e, b, g)?, self.dot(a, f, b, h)?],
+                [self.dot(c, e, d, g)?, self.dot(c, f, d, h)?],
+            ],
+            modulo: self.modulo,
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+// Permutations under composition
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Permutation {
+    // i is sent to images[i]
+    images: Vec<usize>,
+}
+
+impl Permutation {
+    /// Permutation sending i to images[i] - BadArgument if images isn't a permutation of 0..n
+    pub fn new(images: Vec<usize>) -> Result<Permutation, nt_error::NtError> {
+        let mut seen = vec![false; images.len()];
+        for &image in images.iter() {
+            if image >= images.len() || seen[image] {
+                return Err(nt_error::NtError::BadArgument);
+            }
+            seen[image] = true;
+        }
+        Ok(Permutation { images })
+    }
+
+    pub fn images(&self) -> &[usize] {
+        &self.images
+    }
+}
+
+impl Monoid for Permutation {
+    fn identity(&self) -> Self {
+        Permutation {
+            images: (0..self.images.len()).collect(),
+        }
+    }
+
+    // Applies other first and then self
+    fn op(&self, other: &Self) -> Result<Self, nt_error::NtError> {
+        if self.images.len() != other.images.len() {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        Ok(Permutation {
+            images: other.images.iter().map(|&i| self.images[i]).collect(),
+        })
+    }
+
+    fn inverse(&self) -> Result<Self, nt_error::NtError> {
+        let mut images = vec![0; self.images.len()];
+        for (i, &image) in self.images.iter().enumerate() {
+            images[image] = i;
+        }
+        Ok(Permutation { images })
+    }
+}
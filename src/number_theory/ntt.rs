@@ -0,0 +1,403 @@
+use crate::number_theory::euclidean::{self, EuclideanDomain};
+use crate::number_theory::factorization;
+use crate::number_theory::power_mod::power;
+use crate::utilities::nt_error;
+use crate::utilities::numeric_trait::Numeric;
+
+// Primes c 2^k + 1 with k >= 23 whose product exceeds 2^86 so they pin down any convolution of
+// length up to 2^23 with coefficients below 2^30
+const NTT_PRIMES: [u32; 3] = [998244353, 167772161, 469762049];
+
+fn mul_mod<T: Numeric>(a: T, b: T, modulo: T) -> Result<T, nt_error::NtError> {
+    match a.checked_mul(&b) {
+        None => Err(nt_error::NtError::Overflow),
+        Some(val) => Ok(val % modulo),
+    }
+}
+
+/**
+Returns the smallest primitive root modulo a prime
+
+# Arguments
+
+* `p` - Prime modulus
+
+# Returns
+* The least g generating the multiplicative group mod p.  p - 1 is factored by trial division and
+  g is a generator when g^((p - 1) / q) isn't 1 for any prime q dividing p - 1.  BadArgument if
+  p is less than 2 or is detected not to be prime and Overflow if p^2 doesn't fit in T.
+
+# Examples
+
+```
+use number_theory::number_theory::ntt;
+
+assert_eq!(ntt::primitive_root(998244353i64), Ok(3));
+assert_eq!(ntt::primitive_root(7), Ok(3));
+```
+*/
+pub fn primitive_root<T: Numeric>(p: T) -> Result<T, nt_error::NtError> {
+    if p < T::from_u8(2).unwrap() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let order = p - T::one();
+    let factors = factorization::factor(order)?;
+    let mut g = T::one();
+    while g < p {
+        // Fermat fails so p can't be prime
+        if power(g, order, p)? != T::one() {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        let mut is_generator = true;
+        for &(q, _) in factors.iter() {
+            if power(g, order / q, p)? == T::one() {
+                is_generator = false;
+                break;
+            }
+        }
+        if is_generator {
+            return Ok(g);
+        }
+        g += T::one();
+    }
+    Err(nt_error::NtError::BadArgument)
+}
+
+// Root of unity of order n mod p for n a power of two - BadArgument unless n divides p - 1
+fn root_of_unity<T: Numeric>(n: usize, p: T) -> Result<T, nt_error::NtError> {
+    let bad = || nt_error::NtError::BadArgument;
+    if !n.is_power_of_two() {
+        return Err(bad());
+    }
+    let n = T::from_usize(n).ok_or_else(bad)?;
+    if p < T::from_u8(2).unwrap() || (p - T::one()) % n != T::zero() {
+        return Err(bad());
+    }
+    power(primitive_root(p)?, (p - T::one()) / n, p)
+}
+
+// In place iterative radix 2 transform with root a root of unity of order a.len()
+fn transform<T: Numeric>(a: &mut [T], root: T, p: T) -> Result<(), nt_error::NtError> {
+    let n = a.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j ^= bit;
+        if i < j {
+            a.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        // Twiddle factors for this level are powers of a root of unity of order len
+        let w_len = power(root, T::from_usize(n / len).unwrap(), p)?;
+        for start in (0..n).step_by(len) {
+            let mut w = T::one();
+            for k in start..start + len / 2 {
+                let u = a[k];
+                let v = mul_mod(a[k + len / 2], w, p)?;
+                a[k] = (u + v) % p;
+                a[k + len / 2] = (u + p - v) % p;
+                w = mul_mod(w, w_len, p)?;
+            }
+        }
+        len <<= 1;
+    }
+    Ok(())
+}
+
+// Inverse of transform with the same root - transform with the inverse root and divide by n
+fn inverse_transform<T: Numeric>(a: &mut [T], root: T, p: T) -> Result<(), nt_error::NtError> {
+    transform(a, euclidean::inverse_mod(root, p)?, p)?;
+    let n_inv = euclidean::inverse_mod(T::from_usize(a.len()).unwrap(), p)?;
+    for x in a.iter_mut() {
+        *x = mul_mod(*x, n_inv, p)?;
+    }
+    Ok(())
+}
+
+// Values reduced into [0, p)
+fn reduced<T: Numeric>(a: &[T], p: T) -> Vec<T> {
+    a.iter().map(|x| x.div_rem(&p).1).collect()
+}
+
+/**
+Number theoretic transform in place
+
+# Arguments
+
+* `a` - Values mod p whose length n is a power of two dividing p - 1
+* `p` - Prime modulus such as 998244353 = 119 * 2^23 + 1
+
+# Returns
+* Replaces a with A_k = sum of a_j w^(jk) mod p where w = g^((p - 1) / n) for g the
+  primitive_root of p.  Radix 2 and iterative with the twiddle factors from power_mod::power.
+  BadArgument if n isn't a power of two dividing p - 1 and Overflow if p^2 doesn't fit in T.
+
+# Examples
+
+```
+use number_theory::number_theory::ntt;
+
+let mut a = vec![1i64, 2, 3, 4];
+ntt::ntt(&mut a, 998244353).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(a[0], 10);
+ntt::inverse_ntt(&mut a, 998244353).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(a, vec![1, 2, 3, 4]);
+```
+*/
+pub fn ntt<T: Numeric>(a: &mut [T], p: T) -> Result<(), nt_error::NtError> {
+    let root = root_of_unity(a.len(), p)?;
+    for x in a.iter_mut() {
+        *x = x.div_rem(&p).1;
+    }
+    transform(a, root, p)
+}
+
+/**
+Inverse number theoretic transform in place
+
+# Arguments
+
+* `a` - Transformed values mod p
+* `p` - Prime modulus
+
+# Returns
+* Undoes ntt with the same p so a_j = n^-1 times the sum of A_k w^(-jk).  Errors as for ntt.
+*/
+pub fn inverse_ntt<T: Numeric>(a: &mut [T], p: T) -> Result<(), nt_error::NtError> {
+    let root = root_of_unity(a.len(), p)?;
+    for x in a.iter_mut() {
+        *x = x.div_rem(&p).1;
+    }
+    inverse_transform(a, root, p)
+}
+
+/**
+Returns the convolution of two sequences mod an NTT friendly prime
+
+# Arguments
+
+* `a`, `b` - Sequences
+* `p` - Prime with 2^k dividing p - 1 for 2^k at least a.len() + b.len() - 1
+
+# Returns
+* c with c_k = sum of a_i b_j over i + j = k mod p - the coefficients of the product of the
+  polynomials with coefficients a and b.  Both are padded to a power of two and multiplied
+  pointwise after transforming.  Empty if either sequence is.  BadArgument if p has no root of
+  unity of the needed order and Overflow if p^2 doesn't fit in T.
+
+# Examples
+
+```
+use number_theory::number_theory::ntt;
+
+let c = ntt::convolution(&[1i64, 2, 3], &[4, 5], 998244353)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(c, vec![4, 13, 22, 15]);
+```
+*/
+pub fn convolution<T: Numeric>(a: &[T], b: &[T], p: T) -> Result<Vec<T>, nt_error::NtError> {
+    if a.is_empty() || b.is_empty() {
+        return Ok(vec![]);
+    }
+    let len = a.len() + b.len() - 1;
+    let n = len.next_power_of_two();
+    let root = root_of_unity(n, p)?;
+    let mut fa = reduced(a, p);
+    let mut fb = reduced(b, p);
+    fa.resize(n, T::zero());
+    fb.resize(n, T::zero());
+    transform(&mut fa, root, p)?;
+    transform(&mut fb, root, p)?;
+    for (x, &y) in fa.iter_mut().zip(fb.iter()) {
+        *x = mul_mod(*x, y, p)?;
+    }
+    inverse_transform(&mut fa, root, p)?;
+    fa.truncate(len);
+    Ok(fa)
+}
+
+/**
+Returns the convolution of two sequences mod any modulus
+
+# Arguments
+
+* `a`, `b` - Sequences
+* `modulo` - Positive modulus
+
+# Returns
+* c with c_k = sum of a_i b_j over i + j = k mod modulo.  The exact convolution of the reduced
+  sequences is found mod the three primes 998244353, 167772161 and 469762049 and recombined by
+  Garner's form of the Chinese remainder theorem.  That is exact while min(a.len(), b.len())
+  (modulo - 1)^2 is below the product of the primes (about 2^86) - 2^23 terms with a modulus
+  below 2^30 for instance.  Empty if either sequence is.  BadArgument if modulo isn't positive or
+  the product is longer than 2^23 and Overflow if the bound fails or T can't hold the squares of
+  modulo and the primes.
+
+# Examples
+
+```
+use number_theory::number_theory::ntt;
+
+// Digits of 1234 * 5678 = 7006652 least significant first before carrying
+let c = ntt::convolution_any_mod(&[4i64, 3, 2, 1], &[8, 7, 6, 5], 1000000007)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(c, vec![32, 52, 61, 60, 34, 16, 5]);
+let c = ntt::convolution_any_mod(&[999999999i64, 2], &[999999999, 3], 1000000007)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(c, vec![64, 999999967, 6]);
+```
+*/
+pub fn convolution_any_mod<T: Numeric>(
+    a: &[T],
+    b: &[T],
+    modulo: T,
+) -> Result<Vec<T>, nt_error::NtError> {
+    let overflow = || nt_error::NtError::Overflow;
+    if modulo <= T::zero() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    modulo.checked_mul(&modulo).ok_or_else(overflow)?;
+    if a.is_empty() || b.is_empty() {
+        return Ok(vec![]);
+    }
+    let primes = NTT_PRIMES
+        .iter()
+        .map(|&q| T::from_u32(q).ok_or_else(overflow))
+        .collect::<Result<Vec<T>, nt_error::NtError>>()?;
+    let bound = a.len().min(b.len()) as f64 * (modulo - T::one()).to_f64().unwrap().powi(2);
+    let capacity = NTT_PRIMES.iter().map(|&q| q as f64).product::<f64>();
+    if bound >= capacity {
+        return Err(overflow());
+    }
+
+    let a = reduced(a, modulo);
+    let b = reduced(b, modulo);
+    let residues = primes
+        .iter()
+        .map(|&q| convolution(&a, &b, q))
+        .collect::<Result<Vec<Vec<T>>, nt_error::NtError>>()?;
+
+    // Garner: x = x0 + m0 x1 + m0 m1 x2 with each xi in [0, mi)
+    let (m0, m1, m2) = (primes[0], primes[1], primes[2]);
+    let m0_inv = euclidean::inverse_mod(m0, m1)?;
+    let m0m1_inv = euclidean::inverse_mod(mul_mod(m0, m1, m2)?, m2)?;
+    let m0_mod = m0 % modulo;
+    let m0m1_mod = mul_mod(m0_mod, m1 % modulo, modulo)?;
+    let mut ret = Vec::with_capacity(residues[0].len());
+    for ((&r0, &r1), &r2) in residues[0].iter().zip(&residues[1]).zip(&residues[2]) {
+        let x0 = r0;
+        let x1 = mul_mod((r1 - x0 % m1 + m1) % m1, m0_inv, m1)?;
+        let partial = (x0 % m2 + mul_mod(m0 % m2, x1, m2)?) % m2;
+        let x2 = mul_mod((r2 - partial + m2) % m2, m0m1_inv, m2)?;
+        let c = (x0 % modulo
+            + mul_mod(m0_mod, x1 % modulo, modulo)?
+            + mul_mod(m0m1_mod, x2 % modulo, modulo)?)
+            % modulo;
+        ret.push(c);
+    }
+    Ok(ret)
+}
+
+/**
+Returns the cyclic convolution of two sequences mod an NTT friendly prime
+
+# Arguments
+
+* `a`, `b` - Sequences of the same length n, a power of two dividing p - 1
+* `p` - Prime modulus
+
+# Returns
+* c with c_k = sum of a_i b_j over i + j = k (mod n) mod p - the product modulo x^n - 1.  A
+  single length n transform of each.  BadArgument if the lengths differ or n isn't a power of two
+  dividing p - 1 and Overflow if p^2 doesn't fit in T.
+
+# Examples
+
+```
+use number_theory::number_theory::ntt;
+
+let c = ntt::cyclic_convolution(&[1i64, 2, 3, 4], &[0, 1, 0, 0], 998244353)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(c, vec![4, 1, 2, 3]);
+```
+*/
+pub fn cyclic_convolution<T: Numeric>(a: &[T], b: &[T], p: T) -> Result<Vec<T>, nt_error::NtError> {
+    if a.len() != b.len() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let root = root_of_unity(a.len(), p)?;
+    let mut fa = reduced(a, p);
+    let mut fb = reduced(b, p);
+    transform(&mut fa, root, p)?;
+    transform(&mut fb, root, p)?;
+    for (x, &y) in fa.iter_mut().zip(fb.iter()) {
+        *x = mul_mod(*x, y, p)?;
+    }
+    inverse_transform(&mut fa, root, p)?;
+    Ok(fa)
+}
+
+/**
+Returns the negacyclic convolution of two sequences mod an NTT friendly prime
+
+# Arguments
+
+* `a`, `b` - Sequences of the same length n, a power of two with 2n dividing p - 1
+* `p` - Prime modulus
+
+# Returns
+* The product modulo x^n + 1 so terms wrapping past x^n come back negated.  Weighting a_i and b_i
+  by psi^i for psi a root of unity of order 2n turns it into a cyclic convolution.  BadArgument
+  if the lengths differ or 2n isn't a power of two dividing p - 1 and Overflow if p^2 doesn't
+  fit in T.
+
+# Examples
+
+```
+use number_theory::number_theory::ntt;
+
+// x^3 * (1 + 2x + 3x^2 + 4x^3) = -2 - 3x - 4x^2 + x^3 (mod x^4 + 1)
+let c = ntt::negacyclic_convolution(&[1i64, 2, 3, 4], &[0, 0, 0, 1], 998244353)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(c, vec![998244351, 998244350, 998244349, 1]);
+```
+*/
+pub fn negacyclic_convolution<T: Numeric>(
+    a: &[T],
+    b: &[T],
+    p: T,
+) -> Result<Vec<T>, nt_error::NtError> {
+    let n = a.len();
+    if n != b.len() || n == 0 {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let psi = root_of_unity(2 * n, p)?;
+    let root = mul_mod(psi, psi, p)?;
+    let mut fa = reduced(a, p);
+    let mut fb = reduced(b, p);
+    let mut weight = T::one();
+    for (x, y) in fa.iter_mut().zip(fb.iter_mut()) {
+        *x = mul_mod(*x, weight, p)?;
+        *y = mul_mod(*y, weight, p)?;
+        weight = mul_mod(weight, psi, p)?;
+    }
+    transform(&mut fa, root, p)?;
+    transform(&mut fb, root, p)?;
+    for (x, &y) in fa.iter_mut().zip(fb.iter()) {
+        *x = mul_mod(*x, y, p)?;
+    }
+    inverse_transform(&mut fa, root, p)?;
+    let psi_inv = euclidean::inverse_mod(psi, p)?;
+    let mut weight = T::one();
+    for x in fa.iter_mut() {
+        *x = mul_mod(*x, weight, p)?;
+        weight = mul_mod(weight, psi_inv, p)?;
+    }
+    Ok(fa)
+}
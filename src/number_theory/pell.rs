@@ -0,0 +1,291 @@
+use crate::utilities::nt_error;
+use num::integer::Roots;
+use num::{CheckedAdd, CheckedMul, CheckedSub, FromPrimitive, Integer, Signed};
+
+/**
+Integers the Pell solvers work over
+
+Solutions grow exponentially in the period of the continued fraction of sqrt(d) so besides the
+fixed width signed integers, which report Overflow when a solution doesn't fit, this covers
+num::BigInt which never overflows.
+
+# Examples
+
+```
+use num::BigInt;
+use number_theory::number_theory::pell;
+
+let (x, y) = pell::pell_fundamental(BigInt::from(991))
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(&x * &x - 991 * &y * &y, BigInt::from(1));
+assert!(pell::pell_fundamental(991i64).is_err());
+```
+*/
+pub trait PellInt:
+    Clone + Ord + Integer + Signed + Roots + CheckedAdd + CheckedSub + CheckedMul + FromPrimitive
+{
+}
+
+impl<T> PellInt for T where
+    T: Clone
+        + Ord
+        + Integer
+        + Signed
+        + Roots
+        + CheckedAdd
+        + CheckedSub
+        + CheckedMul
+        + FromPrimitive
+{
+}
+
+// (x + y sqrt(d)) * (u + v sqrt(d)) or Overflow
+fn mul_quadratic<T: PellInt>(
+    d: &T,
+    (x, y): &(T, T),
+    (u, v): &(T, T),
+) -> Result<(T, T), nt_error::NtError> {
+    let overflow = || nt_error::NtError::Overflow;
+    let xu = x.checked_mul(u).ok_or_else(overflow)?;
+    let yv = y.checked_mul(v).ok_or_else(overflow)?;
+    let dyv = d.checked_mul(&yv).ok_or_else(overflow)?;
+    let xv = x.checked_mul(v).ok_or_else(overflow)?;
+    let yu = y.checked_mul(u).ok_or_else(overflow)?;
+    Ok((
+        xu.checked_add(&dyv).ok_or_else(overflow)?,
+        xv.checked_add(&yu).ok_or_else(overflow)?,
+    ))
+}
+
+// Smallest positive solution of x^2 - d y^2 = -1 or 1 from the continued fraction of sqrt(d).
+// Returns the solution and the norm it solves.
+//
+// The partial quotients a_k come from (m_k + sqrt(d)) / q_k with m_(k+1) = q_k a_k - m_k and
+// q_(k+1) = (d - m_(k+1)^2) / q_k.  These stay below 2 sqrt(d) so only the convergents p / q
+// can overflow.  The period ends at the first a_k = 2 a_0 and the convergent before it solves
+// the equation with norm (-1)^(period length).
+fn smallest_solution<T: PellInt>(d: &T) -> Result<((T, T), T), nt_error::NtError> {
+    if !d.is_positive() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let a0 = d.sqrt();
+    if a0.clone() * a0.clone() == *d {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let overflow = || nt_error::NtError::Overflow;
+    let two_a0 = a0.clone() + a0.clone();
+    let (mut m, mut q, mut a) = (T::zero(), T::one(), a0.clone());
+    let (mut p_prev, mut p_cur) = (T::one(), a0.clone());
+    let (mut q_prev, mut q_cur) = (T::zero(), T::one());
+    let mut norm = -T::one();
+    loop {
+        m = q.clone() * a - m;
+        q = (d.clone() - m.clone() * m.clone()) / q;
+        a = (a0.clone() + m.clone()) / q.clone();
+        if a == two_a0 {
+            return Ok(((p_cur, q_cur), norm));
+        }
+        norm = -norm;
+        let p_next = a
+            .checked_mul(&p_cur)
+            .and_then(|ap| ap.checked_add(&p_prev))
+            .ok_or_else(overflow)?;
+        let q_next = a
+            .checked_mul(&q_cur)
+            .and_then(|aq| aq.checked_add(&q_prev))
+            .ok_or_else(overflow)?;
+        p_prev = std::mem::replace(&mut p_cur, p_next);
+        q_prev = std::mem::replace(&mut q_cur, q_next);
+    }
+}
+
+/**
+Returns the fundamental solution of Pell's equation x^2 - d y^2 = 1
+
+# Arguments
+
+* `d` - Positive non-square coefficient
+
+# Returns
+* The smallest solution (x, y) with x, y > 0 - found from the continued fraction of sqrt(d).
+  BadArgument if d isn't positive or is a perfect square.  Solutions grow exponentially in the
+  period of the continued fraction so Overflow is reported if it doesn't fit in a fixed width T -
+  use num::BigInt when it doesn't.
+
+# Examples
+
+```
+use number_theory::number_theory::pell;
+
+let (x, y) = pell::pell_fundamental(61i64)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!((x, y), (1766319049, 226153980));
+```
+*/
+pub fn pell_fundamental<T: PellInt>(d: T) -> Result<(T, T), nt_error::NtError> {
+    let (soln, norm) = smallest_solution(&d)?;
+    if norm.is_one() {
+        Ok(soln)
+    } else {
+        // Squaring a solution of the negative equation gives the positive one
+        mul_quadratic(&d, &soln, &soln)
+    }
+}
+
+/**
+Returns the fundamental solution of the negative Pell equation x^2 - d y^2 = -1
+
+# Arguments
+
+* `d` - Positive non-square coefficient
+
+# Returns
+* The smallest solution (x, y) with x, y > 0.  NoSolns if the period of the continued fraction
+  of sqrt(d) is even since then there are no solutions.  Other errors as in pell_fundamental.
+
+# Examples
+
+```
+use number_theory::number_theory::pell;
+
+let (x, y) = pell::negative_pell_fundamental(13)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!((x, y), (18, 5));
+assert!(pell::negative_pell_fundamental(3).is_err());
+```
+*/
+pub fn negative_pell_fundamental<T: PellInt>(d: T) -> Result<(T, T), nt_error::NtError> {
+    let (soln, norm) = smallest_solution(&d)?;
+    if norm.is_one() {
+        Err(nt_error::NtError::NoSolns)
+    } else {
+        Ok(soln)
+    }
+}
+
+/**
+Iterator over the solutions of x^2 - d y^2 = n in a single class
+
+Starting from a seed solution (x0, y0) this yields (x0 + y0 sqrt(d)) * (u + v sqrt(d))^k for
+k = 0, 1, 2... where (u, v) is a solution of the equation with n = 1.  Overflow is yielded once if
+a solution doesn't fit in a fixed width T after which the iterator is finished.
+
+# Examples
+
+```
+use number_theory::number_theory::pell::{pell_fundamental, PellSolutions};
+
+// All positive solutions of x^2 - 2y^2 = 1
+let unit = pell_fundamental(2).unwrap_or_else(|_| panic!("Failed!"));
+let solns: Vec<(i64, i64)> = PellSolutions::new(2, unit, unit)
+    .take(4)
+    .map(|s| s.unwrap_or_else(|_| panic!("Failed!")))
+    .collect();
+assert_eq!(solns, vec![(3, 2), (17, 12), (99, 70), (577, 408)]);
+```
+*/
+pub struct PellSolutions<T: PellInt> {
+    d: T,
+    unit: (T, T),
+    next: Option<Result<(T, T), nt_error::NtError>>,
+}
+
+impl<T: PellInt> PellSolutions<T> {
+    pub fn new(d: T, seed: (T, T), unit: (T, T)) -> PellSolutions<T> {
+        PellSolutions {
+            d,
+            unit,
+            next: Some(Ok(seed)),
+        }
+    }
+}
+
+impl<T: PellInt> Iterator for PellSolutions<T> {
+    type Item = Result<(T, T), nt_error::NtError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ret = self.next.take()?;
+        if let Ok(soln) = &ret {
+            self.next = Some(mul_quadratic(&self.d, soln, &self.unit));
+        }
+        Some(ret)
+    }
+}
+
+/**
+Returns the fundamental solutions of the generalized Pell equation x^2 - d y^2 = n
+
+# Arguments
+
+* `d` - Positive non-square coefficient
+* `n` - Right hand side
+
+# Returns
+* One solution from each class of solutions.  Every solution is +/- a member of the class of
+  one of these times a power of the fundamental solution of x^2 - d y^2 = 1 so PellSolutions
+  generates the rest.  Empty if there are no solutions.  Errors as in pell_fundamental and
+  Overflow if n + d y^2 doesn't fit for some y within the bound.
+
+Found by brute force over y up to Nagell's bounds sqrt(n (x1 - 1) / 2d) for positive n and
+sqrt(|n| (x1 + 1) / 2d) for negative n where x1 comes from the fundamental solution of the unit
+equation.  This is fine for small d and n but the bound grows with the fundamental solution.
+
+# Examples
+
+```
+use number_theory::number_theory::pell;
+
+let mut solns = pell::generalized_pell(13i64, 27)
+    .unwrap_or_else(|_| panic!("Failed!"));
+solns.sort();
+assert_eq!(solns, vec![(-40, 11), (-12, 3), (12, 3), (40, 11)]);
+```
+*/
+pub fn generalized_pell<T: PellInt>(d: T, n: T) -> Result<Vec<(T, T)>, nt_error::NtError> {
+    let (x1, _) = pell_fundamental(d.clone())?;
+    if n.is_zero() {
+        return Ok(vec![(T::zero(), T::zero())]);
+    }
+    let overflow = || nt_error::NtError::Overflow;
+    let two_d = d.checked_add(&d).ok_or_else(overflow)?;
+    let bound = if n.is_positive() {
+        n.checked_mul(&(x1 - T::one()))
+    } else {
+        T::zero()
+            .checked_sub(&n)
+            .and_then(|abs_n| abs_n.checked_mul(&(x1 + T::one())))
+    };
+    let y_max = (bound.ok_or_else(overflow)? / two_d).sqrt();
+
+    // Two solutions are in the same class when (x x' - d y y', x y' - y x') = 0 (mod n)
+    let same_class = |xy: &(T, T), (x2, y2): &(T, T)| -> Result<bool, nt_error::NtError> {
+        let (a, b) = mul_quadratic(&d, xy, &(x2.clone(), -y2.clone()))?;
+        Ok(a.is_multiple_of(&n) && b.is_multiple_of(&n))
+    };
+    let mut ret: Vec<(T, T)> = Vec::new();
+    let mut y = T::zero();
+    while y <= y_max {
+        let x2 = y
+            .checked_mul(&y)
+            .and_then(|y2| y2.checked_mul(&d))
+            .and_then(|dy2| n.checked_add(&dy2))
+            .ok_or_else(overflow)?;
+        // Negative for small y when n < 0
+        if !x2.is_negative() {
+            let x = x2.sqrt();
+            if x.clone() * x.clone() == x2 {
+                for cand in [(x.clone(), y.clone()), (-x, y.clone())].iter() {
+                    let mut found = false;
+                    for old in ret.iter() {
+                        found = found || same_class(old, cand)?;
+                    }
+                    if !found {
+                        ret.push(cand.clone());
+                    }
+                }
+            }
+        }
+        y = y + T::one();
+    }
+    Ok(ret)
+}
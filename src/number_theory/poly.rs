@@ -0,0 +1,860 @@
+use crate::number_theory::euclidean::{self, gcd, EuclideanDomain};
+use crate::number_theory::factorization;
+use crate::number_theory::monoid::{pow_monoid, Monoid};
+use crate::number_theory::ntt;
+use crate::utilities::nt_error;
+use crate::utilities::numeric_trait::Numeric;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/**
+Polynomial with coefficients in the integers mod a prime p
+
+Coefficients are stored lowest degree first, reduced to [0, p) and without trailing zeros so
+equal polynomials compare equal.  p is checked to be prime and p^2 to fit in T on construction
+so every nonzero leading coefficient is invertible and the arithmetic can't overflow.  The
+operators panic if the moduli differ while the functions returning a Result report BadArgument.
+Since Poly is a EuclideanDomain, gcd, calc_euclidean_ext and inverse_mod from the euclidean
+module work on it directly.
+
+# Examples
+
+```
+use number_theory::number_theory::euclidean;
+use number_theory::number_theory::poly::Poly;
+
+// x^2 + 1 is irreducible mod 7 so x + 1 has an inverse modulo it
+let modulus = Poly::new(vec![1, 0, 1], 7).unwrap_or_else(|_| panic!("Failed!"));
+let a = Poly::new(vec![1, 1], 7).unwrap_or_else(|_| panic!("Failed!"));
+let inv = euclidean::inverse_mod(a.clone(), modulus.clone())
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(inv.coeffs(), &[4, 3]);
+let (_, rem) = euclidean::EuclideanDomain::div_rem(&(a * inv), &modulus);
+assert_eq!(rem.coeffs(), &[1]);
+```
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Poly<T: Numeric> {
+    coeffs: Vec<T>,
+    modulus: T,
+}
+
+impl<T: Numeric> Poly<T> {
+    /**
+    Creates a polynomial from its coefficients, lowest degree first
+
+    # Returns
+    * The polynomial with its coefficients reduced mod modulus.  BadArgument if modulus isn't
+      prime and Overflow if modulus^2 doesn't fit in T.
+    */
+    pub fn new(coeffs: Vec<T>, modulus: T) -> Result<Poly<T>, nt_error::NtError> {
+        if modulus < T::from_u8(2).unwrap() {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        if modulus.checked_mul(&modulus).is_none() {
+            return Err(nt_error::NtError::Overflow);
+        }
+        if !factorization::is_prime(modulus)? {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        let coeffs = coeffs.iter().map(|c| c.div_rem(&modulus).1).collect();
+        Ok(Poly::from_reduced(coeffs, modulus))
+    }
+
+    // Coefficients already in [0, modulus)
+    fn from_reduced(mut coeffs: Vec<T>, modulus: T) -> Poly<T> {
+        while coeffs.last() == Some(&T::zero()) {
+            coeffs.pop();
+        }
+        Poly { coeffs, modulus }
+    }
+
+    pub fn coeffs(&self) -> &[T] {
+        &self.coeffs
+    }
+
+    pub fn modulus(&self) -> T {
+        self.modulus
+    }
+
+    // None for the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.coeffs.len().checked_sub(1)
+    }
+
+    pub fn leading_coeff(&self) -> T {
+        *self.coeffs.last().unwrap_or(&T::zero())
+    }
+
+    /**
+    Returns the value of the polynomial at x mod the modulus by Horner's rule
+    */
+    pub fn eval(&self, x: T) -> T {
+        let x = x.div_rem(&self.modulus).1;
+        self.coeffs
+            .iter()
+            .rev()
+            .fold(T::zero(), |acc, &c| (acc * x + c) % self.modulus)
+    }
+
+    /**
+    Returns self * other by the schoolbook method - quadratic in the degrees
+    */
+    pub fn mul_schoolbook(&self, other: &Poly<T>) -> Poly<T> {
+        self.assert_modulus(other);
+        Poly::from_reduced(
+            schoolbook(&self.coeffs, &other.coeffs, self.modulus),
+            self.modulus,
+        )
+    }
+
+    /**
+    Returns self * other by Karatsuba's method - three half size products in place of four
+    */
+    pub fn mul_karatsuba(&self, other: &Poly<T>) -> Poly<T> {
+        self.assert_modulus(other);
+        Poly::from_reduced(
+            karatsuba(&self.coeffs, &other.coeffs, self.modulus),
+            self.modulus,
+        )
+    }
+
+    /**
+    Returns self * other by the number theoretic transform
+
+    # Returns
+    * The product from ntt::convolution when 2^k divides p - 1 for 2^k at least the length of
+      the product, as it does for p = 998244353 = 119 * 2^23 + 1, and from
+      ntt::convolution_any_mod otherwise.  BadArgument if the moduli differ and errors from
+      those are passed through.
+
+    # Examples
+
+    ```
+    use number_theory::number_theory::poly::Poly;
+
+    let a = Poly::new(vec![1i64, 2, 3], 998244353).unwrap_or_else(|_| panic!("Failed!"));
+    let b = Poly::new(vec![4, 5], 998244353).unwrap_or_else(|_| panic!("Failed!"));
+    let prod = a.mul_ntt(&b).unwrap_or_else(|_| panic!("Failed!"));
+    assert_eq!(prod.coeffs(), &[4, 13, 22, 15]);
+    ```
+    */
+    pub fn mul_ntt(&self, other: &Poly<T>) -> Result<Poly<T>, nt_error::NtError> {
+        self.check_modulus(other)?;
+        let p = self.modulus;
+        let coeffs = match ntt::convolution(&self.coeffs, &other.coeffs, p) {
+            Err(nt_error::NtError::BadArgument) => {
+                ntt::convolution_any_mod(&self.coeffs, &other.coeffs, p)?
+            }
+            res => res?,
+        };
+        Ok(Poly::from_reduced(coeffs, p))
+    }
+
+    /**
+    Returns the formal derivative
+    */
+    pub fn derivative(&self) -> Poly<T> {
+        let p = self.modulus;
+        let coeffs = self
+            .coeffs
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(i, &c)| T::from_usize(i).unwrap() % p * c % p)
+            .collect();
+        Poly::from_reduced(coeffs, p)
+    }
+
+    /**
+    Returns the monic multiple of self by a constant - zero stays zero
+    */
+    pub fn monic(&self) -> Poly<T> {
+        self.normalizing_unit() * self.clone()
+    }
+
+    /**
+    Returns self^exp modulo modulus by square and multiply on PolyMod
+
+    # Arguments
+
+    * `exp` - Nonnegative exponent
+    * `modulus` - Nonzero polynomial to reduce by
+
+    # Returns
+    * The remainder of self^exp on division by modulus.  BadArgument if exp is negative, modulus
+      is zero or the coefficient moduli differ.
+
+    # Examples
+
+    ```
+    use number_theory::number_theory::poly::Poly;
+
+    // x^7 = x (mod x^3 - x) by Fermat
+    let x = Poly::new(vec![0, 1], 7).unwrap_or_else(|_| panic!("Failed!"));
+    let f = Poly::new(vec![0, -1, 0, 1], 7).unwrap_or_else(|_| panic!("Failed!"));
+    assert_eq!(x.pow_mod(7, &f), Ok(x));
+    ```
+    */
+    pub fn pow_mod(&self, exp: T, modulus: &Poly<T>) -> Result<Poly<T>, nt_error::NtError> {
+        let base = PolyMod::new(self.clone(), modulus.clone())?;
+        Ok(pow_monoid(&base, exp)?.value)
+    }
+
+    /**
+    Returns self(g) modulo modulus
+
+    # Arguments
+
+    * `g` - Polynomial substituted for x
+    * `modulus` - Nonzero polynomial to reduce by
+
+    # Returns
+    * The remainder of self(g) on division by modulus by Horner's rule, reducing after each
+      step.  BadArgument if modulus is zero or the coefficient moduli differ.
+
+    # Examples
+
+    ```
+    use number_theory::number_theory::poly::Poly;
+
+    // (x + 1)^2 evaluated at x^2 is x^4 + 2x^2 + 1 = 3x^2 + 1 (mod x^3 - x)
+    let f = Poly::new(vec![1, 2, 1], 5).unwrap_or_else(|_| panic!("Failed!"));
+    let g = Poly::new(vec![0, 0, 1], 5).unwrap_or_else(|_| panic!("Failed!"));
+    let modulus = Poly::new(vec![0, -1, 0, 1], 5).unwrap_or_else(|_| panic!("Failed!"));
+    let composed = f.compose_mod(&g, &modulus).unwrap_or_else(|_| panic!("Failed!"));
+    assert_eq!(composed.coeffs(), &[1, 0, 3]);
+    ```
+    */
+    pub fn compose_mod(
+        &self,
+        g: &Poly<T>,
+        modulus: &Poly<T>,
+    ) -> Result<Poly<T>, nt_error::NtError> {
+        self.check_modulus(g)?;
+        self.check_modulus(modulus)?;
+        if modulus.is_zero() {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        let g = g.div_rem(modulus).1;
+        let mut res = self.zero_like();
+        for &c in self.coeffs.iter().rev() {
+            let constant = Poly::from_reduced(vec![c], self.modulus);
+            res = (res * g.clone() + constant).div_rem(modulus).1;
+        }
+        Ok(res)
+    }
+
+    // The polynomial x
+    fn x_like(&self) -> Poly<T> {
+        Poly::from_reduced(vec![T::zero(), T::one()], self.modulus)
+    }
+
+    // BadArgument unless the coefficient moduli agree
+    fn check_modulus(&self, other: &Poly<T>) -> Result<(), nt_error::NtError> {
+        if self.modulus == other.modulus {
+            Ok(())
+        } else {
+            Err(nt_error::NtError::BadArgument)
+        }
+    }
+
+    // The same for the operators which can't report an error
+    fn assert_modulus(&self, other: &Poly<T>) {
+        assert!(self.modulus == other.modulus, "Polynomial moduli differ");
+    }
+}
+
+/**
+Polynomial reduced modulo a fixed nonzero polynomial - the ring F_p[x] / (modulus)
+
+A Monoid under multiplication so monoid::pow_monoid and friends work on it.  op reports
+BadArgument if the moduli differ and inverse passes on NoSolns from euclidean::inverse_mod when
+the value shares a factor with the modulus.
+
+# Examples
+
+```
+use number_theory::number_theory::monoid::{pow_monoid, Monoid};
+use number_theory::number_theory::poly::{Poly, PolyMod};
+
+// x generates the multiplicative group of F_2[x] / (x^3 + x + 1) which has order 7
+let x = Poly::new(vec![0, 1], 2).unwrap_or_else(|_| panic!("Failed!"));
+let modulus = Poly::new(vec![1, 1, 0, 1], 2).unwrap_or_else(|_| panic!("Failed!"));
+let a = PolyMod::new(x, modulus).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(pow_monoid(&a, 7), Ok(a.identity()));
+assert_eq!(pow_monoid(&a, 3).map(|b| b.value().coeffs().to_vec()), Ok(vec![1, 1]));
+```
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolyMod<T: Numeric> {
+    value: Poly<T>,
+    modulus: Poly<T>,
+}
+
+impl<T: Numeric> PolyMod<T> {
+    /// value reduced modulo modulus - BadArgument if modulus is zero or the coefficient moduli
+    /// differ
+    pub fn new(value: Poly<T>, modulus: Poly<T>) -> Result<PolyMod<T>, nt_error::NtError> {
+        value.check_modulus(&modulus)?;
+        if modulus.is_zero() {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        Ok(PolyMod {
+            value: value.div_rem(&modulus).1,
+            modulus,
+        })
+    }
+
+    pub fn value(&self) -> &Poly<T> {
+        &self.value
+    }
+
+    pub fn modulus(&self) -> &Poly<T> {
+        &self.modulus
+    }
+}
+
+impl<T: Numeric> Monoid for PolyMod<T> {
+    const COMMUTATIVE: bool = true;
+
+    fn identity(&self) -> Self {
+        PolyMod {
+            value: self.modulus.one_like().div_rem(&self.modulus).1,
+            modulus: self.modulus.clone(),
+        }
+    }
+
+    fn op(&self, other: &Self) -> Result<Self, nt_error::NtError> {
+        if self.modulus != other.modulus {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        Ok(PolyMod {
+            value: (self.value.clone() * other.value.clone())
+                .div_rem(&self.modulus)
+                .1,
+            modulus: self.modulus.clone(),
+        })
+    }
+
+    fn inverse(&self) -> Result<Self, nt_error::NtError> {
+        Ok(PolyMod {
+            value: euclidean::inverse_mod(self.value.clone(), self.modulus.clone())?,
+            modulus: self.modulus.clone(),
+        })
+    }
+}
+
+impl<T: Numeric> Add for Poly<T> {
+    type Output = Poly<T>;
+
+    fn add(self, other: Poly<T>) -> Poly<T> {
+        self.assert_modulus(&other);
+        let len = self.coeffs.len().max(other.coeffs.len());
+        let coeffs = (0..len)
+            .map(|i| {
+                let a = *self.coeffs.get(i).unwrap_or(&T::zero());
+                let b = *other.coeffs.get(i).unwrap_or(&T::zero());
+                (a + b) % self.modulus
+            })
+            .collect();
+        Poly::from_reduced(coeffs, self.modulus)
+    }
+}
+
+impl<T: Numeric> Neg for Poly<T> {
+    type Output = Poly<T>;
+
+    fn neg(self) -> Poly<T> {
+        let modulus = self.modulus;
+        let coeffs = self
+            .coeffs
+            .iter()
+            .map(|&c| (modulus - c) % modulus)
+            .collect();
+        Poly::from_reduced(coeffs, modulus)
+    }
+}
+
+impl<T: Numeric> Sub for Poly<T> {
+    type Output = Poly<T>;
+
+    fn sub(self, other: Poly<T>) -> Poly<T> {
+        self + (-other)
+    }
+}
+
+impl<T: Numeric> Mul for Poly<T> {
+    type Output = Poly<T>;
+
+    // Karatsuba which falls back on schoolbook below KARATSUBA_CUTOFF
+    fn mul(self, other: Poly<T>) -> Poly<T> {
+        self.mul_karatsuba(&other)
+    }
+}
+
+impl<T: Numeric> EuclideanDomain for Poly<T> {
+    // Degree with the zero polynomial below everything
+    type Norm = Option<usize>;
+
+    fn zero_like(&self) -> Poly<T> {
+        Poly::from_reduced(vec![], self.modulus)
+    }
+
+    fn one_like(&self) -> Poly<T> {
+        Poly::from_reduced(vec![T::one()], self.modulus)
+    }
+
+    // Schoolbook long division.  Panics on a zero divisor like integer division.
+    fn div_rem(&self, other: &Poly<T>) -> (Poly<T>, Poly<T>) {
+        self.assert_modulus(other);
+        let p = self.modulus;
+        let other_deg = other.degree().expect("Polynomial division by zero");
+        let lead_inv = euclidean::inverse_mod(other.leading_coeff(), p)
+            .unwrap_or_else(|_| panic!("Leading coefficient not invertible"));
+        let mut rem = self.coeffs.clone();
+        if rem.len() <= other_deg {
+            return (self.zero_like(), self.clone());
+        }
+        let mut quot = vec![T::zero(); rem.len() - other_deg];
+        for i in (0..quot.len()).rev() {
+            let c = rem[i + other_deg] * lead_inv % p;
+            quot[i] = c;
+            for (j, &b) in other.coeffs.iter().enumerate() {
+                rem[i + j] = (rem[i + j] + p - c * b % p) % p;
+            }
+        }
+        rem.truncate(other_deg);
+        (Poly::from_reduced(quot, p), Poly::from_reduced(rem, p))
+    }
+
+    fn norm(&self) -> Option<usize> {
+        self.degree()
+    }
+
+    // Makes nonzero polynomials monic
+    fn normalizing_unit(&self) -> Poly<T> {
+        if self.coeffs.is_empty() {
+            return self.one_like();
+        }
+        let inv = euclidean::inverse_mod(self.leading_coeff(), self.modulus)
+            .unwrap_or_else(|_| panic!("Leading coefficient not invertible"));
+        Poly::from_reduced(vec![inv], self.modulus)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+// Multiplication
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Below this many coefficients Karatsuba's bookkeeping costs more than it saves
+const KARATSUBA_CUTOFF: usize = 32;
+
+fn schoolbook<T: Numeric>(a: &[T], b: &[T], p: T) -> Vec<T> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let mut ret = vec![T::zero(); a.len() + b.len() - 1];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            ret[i + j] = (ret[i + j] + x * y % p) % p;
+        }
+    }
+    ret
+}
+
+fn add_slices<T: Numeric>(a: &[T], b: &[T], p: T) -> Vec<T> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut ret = long.to_vec();
+    for (r, &x) in ret.iter_mut().zip(short.iter()) {
+        *r = (*r + x) % p;
+    }
+    ret
+}
+
+// Same product as schoolbook
+fn karatsuba<T: Numeric>(a: &[T], b: &[T], p: T) -> Vec<T> {
+    if a.len() < KARATSUBA_CUTOFF || b.len() < KARATSUBA_CUTOFF {
+        return schoolbook(a, b, p);
+    }
+    // a = a0 + a1 x^half and b = b0 + b1 x^half.  Either top half may be empty when the lengths
+    // are unbalanced.
+    let half = a.len().max(b.len()) / 2;
+    let (a0, a1) = a.split_at(half.min(a.len()));
+    let (b0, b1) = b.split_at(half.min(b.len()));
+    let z0 = karatsuba(a0, b0, p);
+    let z2 = karatsuba(a1, b1, p);
+    let mut z1 = karatsuba(&add_slices(a0, a1, p), &add_slices(b0, b1, p), p);
+    // z1 = (a0 + a1)(b0 + b1) - z0 - z2 = a0 b1 + a1 b0
+    for (i, &x) in z0.iter().enumerate().chain(z2.iter().enumerate()) {
+        z1[i] = (z1[i] + p - x) % p;
+    }
+
+    let mut ret = vec![T::zero(); a.len() + b.len() - 1];
+    for (shift, part) in [(0, &z0), (half, &z1), (2 * half, &z2)].iter() {
+        for (i, &x) in part.iter().enumerate() {
+            if x != T::zero() {
+                ret[shift + i] = (ret[shift + i] + x) % p;
+            }
+        }
+    }
+    ret
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+// Factorization
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// xorshift64 for picking the random polynomials in Cantor-Zassenhaus.  A fixed seed keeps the
+// output reproducible and the factors are sorted anyway.
+pub(crate) fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+fn check_nonzero<T: Numeric>(f: &Poly<T>) -> Result<(), nt_error::NtError> {
+    if f.is_zero() {
+        Err(nt_error::NtError::BadArgument)
+    } else {
+        Ok(())
+    }
+}
+
+/**
+Returns the square-free factorization of a polynomial
+
+# Arguments
+
+* `f` - Nonzero polynomial
+
+# Returns
+* (g, k) pairs with the g monic, square-free, pairwise coprime and nonconstant and f equal to
+  its leading coefficient times the product of the g^k.  Sorted by k.  Works from gcd(f, f')
+  and takes p-th roots of the parts whose derivative vanishes.  BadArgument if f is zero.
+
+# Examples
+
+```
+use number_theory::number_theory::poly::{self, Poly};
+
+// x^2 (x + 1)^3 mod 3 where (x + 1)^3 = x^3 + 1 has zero derivative
+let f = Poly::new(vec![0, 0, 1, 0, 0, 1], 3).unwrap_or_else(|_| panic!("Failed!"));
+let parts = poly::square_free_factorization(&f).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(parts.len(), 2);
+assert_eq!((parts[0].0.coeffs(), parts[0].1), (&[0, 1][..], 2));
+assert_eq!((parts[1].0.coeffs(), parts[1].1), (&[1, 1][..], 3));
+```
+*/
+pub fn square_free_factorization<T: Numeric>(
+    f: &Poly<T>,
+) -> Result<Vec<(Poly<T>, u32)>, nt_error::NtError> {
+    check_nonzero(f)?;
+    let mut ret = Vec::new();
+    square_free_help(&f.monic(), 1, &mut ret)?;
+    ret.sort_by_key(|(_, k)| *k);
+    Ok(ret)
+}
+
+// Square-free parts of the monic f with multiplicities scaled by mult
+fn square_free_help<T: Numeric>(
+    f: &Poly<T>,
+    mult: u32,
+    ret: &mut Vec<(Poly<T>, u32)>,
+) -> Result<(), nt_error::NtError> {
+    let one = f.one_like();
+    // w is the product of the factors whose multiplicity isn't divisible by p and c holds what
+    // is left of f once w has been peeled off i times
+    let mut c = gcd(f.clone(), f.derivative());
+    let mut w = f.div_rem(&c).0;
+    let mut i = 1;
+    while w != one {
+        let y = gcd(w.clone(), c.clone());
+        let fac = w.div_rem(&y).0;
+        if fac != one {
+            ret.push((fac, i * mult));
+        }
+        c = c.div_rem(&y).0;
+        w = y;
+        i += 1;
+    }
+    if c != one {
+        // c' = 0 so c(x) = g(x^p) = g(x)^p since a^p = a in F_p
+        let p = f.modulus.to_usize().ok_or(nt_error::NtError::Overflow)?;
+        let root = c.coeffs.iter().step_by(p).copied().collect();
+        let mult = mult
+            .checked_mul(p as u32)
+            .ok_or(nt_error::NtError::Overflow)?;
+        square_free_help(&Poly::from_reduced(root, f.modulus), mult, ret)?;
+    }
+    Ok(())
+}
+
+/**
+Returns the distinct-degree factorization of a square-free polynomial
+
+# Arguments
+
+* `f` - Nonzero square-free polynomial
+
+# Returns
+* (g, d) pairs with g monic and the product of all the degree d irreducible factors of f in
+  increasing order of d.  x^(p^d) - x is the product of the monic irreducibles of degree
+  dividing d so g is its gcd with what is left of f.  BadArgument if f is zero.  The result is
+  meaningless if f isn't square-free.
+
+# Examples
+
+```
+use number_theory::number_theory::poly::{self, Poly};
+
+// x (x + 1) (x^2 + 1) mod 3
+let f = Poly::new(vec![0, 1, 1, 1, 1], 3).unwrap_or_else(|_| panic!("Failed!"));
+let parts = poly::distinct_degree_factorization(&f).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(parts.len(), 2);
+assert_eq!((parts[0].0.coeffs(), parts[0].1), (&[0, 1, 1][..], 1));
+assert_eq!((parts[1].0.coeffs(), parts[1].1), (&[1, 0, 1][..], 2));
+```
+*/
+pub fn distinct_degree_factorization<T: Numeric>(
+    f: &Poly<T>,
+) -> Result<Vec<(Poly<T>, usize)>, nt_error::NtError> {
+    check_nonzero(f)?;
+    let p = f.modulus;
+    let one = f.one_like();
+    let x = f.x_like();
+    let mut rest = f.monic();
+    let mut h = x.clone();
+    let mut ret = Vec::new();
+    let mut d = 1;
+    while rest.degree().unwrap_or(0) >= 2 * d {
+        // h = x^(p^d) mod rest
+        h = h.pow_mod(p, &rest)?;
+        let g = gcd(rest.clone(), h.clone() - x.clone());
+        if g != one {
+            rest = rest.div_rem(&g).0;
+            h = h.div_rem(&rest).1;
+            ret.push((g, d));
+        }
+        d += 1;
+    }
+    if let Some(deg) = rest.degree().filter(|&deg| deg > 0) {
+        ret.push((rest, deg));
+    }
+    Ok(ret)
+}
+
+/**
+Splits a product of distinct irreducibles of the same degree by Cantor-Zassenhaus
+
+# Arguments
+
+* `f` - Nonzero square-free polynomial whose irreducible factors all have degree d
+* `d` - Degree of the factors
+
+# Returns
+* The monic irreducible factors of f sorted by their coefficients, highest degree first.  For a
+  random a, a^((p^d - 1) / 2) is +1 or -1 modulo each factor (for p = 2 the trace
+  a + a^2 + ... + a^(2^(d - 1)) is 0 or 1) so its gcd with f usually splits f.  BadArgument if f
+  is zero, d is zero or the factors of f aren't distinct of degree d - checked up front as f
+  dividing x^(p^d) - x and being coprime to x^(p^(d / r)) - x for the primes r dividing d.
+
+# Examples
+
+```
+use number_theory::number_theory::poly::{self, Poly};
+
+// x^2 + 1 and x^2 + x + 2 are irreducible mod 3
+let f = Poly::new(vec![2, 1, 0, 1, 1], 3).unwrap_or_else(|_| panic!("Failed!"));
+let factors = poly::equal_degree_factorization(&f, 2).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(factors[0].coeffs(), &[1, 0, 1]);
+assert_eq!(factors[1].coeffs(), &[2, 1, 1]);
+```
+*/
+pub fn equal_degree_factorization<T: Numeric>(
+    f: &Poly<T>,
+    d: usize,
+) -> Result<Vec<Poly<T>>, nt_error::NtError> {
+    check_nonzero(f)?;
+    let deg = f.degree().unwrap();
+    if d == 0 || !deg.is_multiple_of(d) {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let f = f.monic();
+    if deg == 0 {
+        return Ok(vec![]);
+    }
+    // Without this the random splitting would never finish on bad input
+    if !has_equal_degree_factors(&f, d)? {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let mut ret = Vec::new();
+    let mut state = 0x2545_f491_4f6c_dd1d;
+    equal_degree_help(&f, d, &mut state, &mut ret)?;
+    ret.sort_by(|a, b| a.coeffs.iter().rev().cmp(b.coeffs.iter().rev()));
+    ret.sort_by_key(|g| g.degree());
+    Ok(ret)
+}
+
+// Whether the nonconstant f is a product of distinct irreducibles of degree d - Rabin's test when d
+// is the degree of f
+pub(crate) fn has_equal_degree_factors<T: Numeric>(
+    f: &Poly<T>,
+    d: usize,
+) -> Result<bool, nt_error::NtError> {
+    let p = f.modulus;
+    let x = f.x_like().div_rem(f).1;
+    // frobenius[j] = x^(p^j) mod f
+    let mut frobenius = vec![x.clone()];
+    for j in 1..=d {
+        let next = frobenius[j - 1].pow_mod(p, f)?;
+        frobenius.push(next);
+    }
+    if frobenius[d] != x {
+        return Ok(false);
+    }
+    for (r, _) in factorization::factor(d as i64)? {
+        let g = gcd(f.clone(), frobenius[d / r as usize].clone() - x.clone());
+        if g.degree() != Some(0) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn equal_degree_help<T: Numeric>(
+    f: &Poly<T>,
+    d: usize,
+    state: &mut u64,
+    ret: &mut Vec<Poly<T>>,
+) -> Result<(), nt_error::NtError> {
+    let deg = f.degree().unwrap();
+    if deg <= d {
+        if deg > 0 {
+            ret.push(f.clone());
+        }
+        return Ok(());
+    }
+    let p = f.modulus;
+    // p^2 fits in T so p fits in a u64
+    let p_u64 = p.to_u64().unwrap();
+    let one = f.one_like();
+    let two = T::from_u8(2).unwrap();
+    loop {
+        let coeffs = (0..deg)
+            .map(|_| T::from_u64(next_random(state) % p_u64).unwrap())
+            .collect();
+        let a = Poly::from_reduced(coeffs, p);
+        let g = gcd(f.clone(), a.clone());
+        let g = if g != one {
+            g
+        } else if p == two {
+            let mut trace = a.clone();
+            let mut term = a;
+            for _ in 1..d {
+                term = term.pow_mod(two, f)?;
+                trace = trace + term.clone();
+            }
+            gcd(f.clone(), trace)
+        } else {
+            // a^((p^d - 1) / 2) = (a^(1 + p + ... + p^(d - 1)))^((p - 1) / 2) keeps the
+            // exponents below p
+            let mut norm = a.clone();
+            let mut term = a;
+            for _ in 1..d {
+                term = term.pow_mod(p, f)?;
+                norm = (norm * term.clone()).div_rem(f).1;
+            }
+            let b = norm.pow_mod((p - T::one()) / two, f)?;
+            gcd(f.clone(), b - one.clone())
+        };
+        if g != one && g.degree() != f.degree() {
+            equal_degree_help(&g, d, state, ret)?;
+            return equal_degree_help(&f.div_rem(&g).0, d, state, ret);
+        }
+    }
+}
+
+/**
+Returns the factorization of a polynomial into irreducibles
+
+# Arguments
+
+* `f` - Nonzero polynomial
+
+# Returns
+* (c, factors) with c the leading coefficient of f and factors the (g, k) with g monic and
+  irreducible and f = c times the product of the g^k.  The factors are sorted by degree and then
+  by coefficients highest degree first.  Runs square-free, distinct-degree and Cantor-Zassenhaus
+  factorization in turn.  BadArgument if f is zero.
+
+# Examples
+
+```
+use number_theory::number_theory::poly::{self, Poly};
+
+// 2x^3 + 2 = 2 (x + 1) (x^2 + 4x + 1) mod 5
+let f = Poly::new(vec![2, 0, 0, 2], 5).unwrap_or_else(|_| panic!("Failed!"));
+let (lead, factors) = poly::factor(&f).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(lead, 2);
+assert_eq!(factors.len(), 2);
+assert_eq!((factors[0].0.coeffs(), factors[0].1), (&[1, 1][..], 1));
+assert_eq!((factors[1].0.coeffs(), factors[1].1), (&[1, 4, 1][..], 1));
+```
+*/
+#[allow(clippy::type_complexity)]
+pub fn factor<T: Numeric>(f: &Poly<T>) -> Result<(T, Vec<(Poly<T>, u32)>), nt_error::NtError> {
+    check_nonzero(f)?;
+    let mut ret = Vec::new();
+    for (part, k) in square_free_factorization(f)? {
+        for (same_degree, d) in distinct_degree_factorization(&part)? {
+            for g in equal_degree_factorization(&same_degree, d)? {
+                ret.push((g, k));
+            }
+        }
+    }
+    ret.sort_by(|(a, _), (b, _)| a.coeffs.iter().rev().cmp(b.coeffs.iter().rev()));
+    ret.sort_by_key(|(g, _)| g.degree());
+    Ok((f.leading_coeff(), ret))
+}
+
+/**
+Returns the distinct roots of a polynomial mod p
+
+# Arguments
+
+* `f` - Nonzero polynomial
+
+# Returns
+* The x in [0, p) with f(x) = 0 (mod p) in increasing order.  gcd(f, x^p - x) is the product of
+  the x - r over the roots r which Cantor-Zassenhaus splits into linear factors.  BadArgument if
+  f is zero.
+
+# Examples
+
+```
+use number_theory::number_theory::poly::{self, Poly};
+
+// x^3 - 2 has three cube roots of 2 mod 31
+let f = Poly::new(vec![-2, 0, 0, 1], 31).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(poly::roots(&f), Ok(vec![4, 7, 20]));
+```
+*/
+pub fn roots<T: Numeric>(f: &Poly<T>) -> Result<Vec<T>, nt_error::NtError> {
+    check_nonzero(f)?;
+    let p = f.modulus;
+    if f.degree() == Some(0) {
+        return Ok(vec![]);
+    }
+    let x = f.x_like();
+    let linear = gcd(f.clone(), x.pow_mod(p, f)? - x);
+    if linear.degree() == Some(0) {
+        return Ok(vec![]);
+    }
+    let mut ret: Vec<T> = equal_degree_factorization(&linear, 1)?
+        .iter()
+        .map(|g| (p - g.coeffs[0]) % p)
+        .collect();
+    ret.sort();
+    Ok(ret)
+}
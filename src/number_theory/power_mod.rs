@@ -1,32 +1,490 @@
+use crate::number_theory::euclidean;
+use crate::number_theory::factorization;
+use crate::number_theory::monoid;
+use crate::number_theory::monoid::{Exponent, Monoid, PowerOptions};
+use crate::number_theory::totient;
 use crate::utilities::nt_error;
 use crate::utilities::numeric_trait::Numeric;
-use crate::utilities::utilities;
 
+/**
+Returns x^n (mod modulo)
+
+# Arguments
+
+* `x` - Base - negative values are taken mod modulo
+* `n` - Exponent - negative values raise the inverse of x mod modulo
+* `modulo` - Modulus - must be positive
+
+# Returns
+* x^n reduced to [0, modulo).  BadArgument if modulo isn't positive, NoSolns if n is negative
+  and x isn't invertible mod modulo and Overflow if modulo^2 doesn't fit in T.
+
+# Examples
+
+```
+use number_theory::number_theory::power_mod;
+
+let val = power_mod::power(2357, 2357, 3599)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(val, 3115);
+let inv = power_mod::power(2357, -2357, 3599)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(inv * val % 3599, 1);
+```
+*/
 pub fn power<T: Numeric>(x: T, n: T, modulo: T) -> Result<T, nt_error::NtError> {
-    if n == T::zero() {
-        return Ok(T::one());
+    power_with(x, n, modulo, &PowerOptions::default())
+}
+
+/**
+Returns x^n (mod modulo) using the exponentiation method given in the options
+
+# Arguments
+
+* `x`, `n`, `modulo` - As in power
+* `options` - Which exponentiation method to use.  Windowed methods pay for a table up front and
+  save multiplies on large exponents.
+
+# Returns
+* Same as power.  BadArgument also covers window sizes outside [1, 16].
+
+# Examples
+
+```
+use number_theory::number_theory::monoid::{PowerMethod, PowerOptions};
+use number_theory::number_theory::power_mod;
+
+let options = PowerOptions {
+    method: PowerMethod::FixedWindow(3),
+};
+let val = power_mod::power_with(2357, 2357, 3599, &options)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(val, 3115);
+```
+*/
+pub fn power_with<T: Numeric>(
+    x: T,
+    n: T,
+    modulo: T,
+    options: &PowerOptions,
+) -> Result<T, nt_error::NtError> {
+    if modulo <= T::zero() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    if modulo == T::one() {
+        return Ok(T::zero());
+    }
+    let mut x = x % modulo;
+    if x < T::zero() {
+        x += modulo;
     }
 
     if n < T::zero() {
+        let inv = euclidean::inverse_mod(x, modulo)?;
+        // x^n = inv^-n = inv^(-(n + 1)) * inv which avoids negating T's minimum value
+        let res = power_nonneg(inv, -(n + T::one()), modulo, options)?;
+        return match res.checked_mul(&inv) {
+            None => Err(nt_error::NtError::Overflow),
+            Some(val) => Ok(val % modulo),
+        };
+    }
+    power_nonneg(x, n, modulo, options)
+}
+
+/**
+Returns x^n (mod modulo) for a nonnegative exponent which may be wider than T
+
+# Arguments
+
+* `x` - Base - negative values are taken mod modulo
+* `n` - Nonnegative exponent as any monoid::Exponent such as little endian u64 limbs
+* `modulo` - Modulus - must be positive
+* `options` - Which exponentiation method to use
+
+# Returns
+* x^n reduced to [0, modulo).  BadArgument if modulo isn't positive, n is negative or a window
+  size is outside [1, 16] and Overflow if modulo^2 doesn't fit in T.
+
+# Examples
+
+```
+use number_theory::number_theory::monoid::PowerOptions;
+use number_theory::number_theory::power_mod;
+
+// 2^65 as limbs so 3^(2^65) = (3^(2^64))^2
+let limbs: &[u64] = &[0, 2];
+let val = power_mod::power_with_exponent(3i128, limbs, 1000003, &PowerOptions::default())
+    .unwrap_or_else(|_| panic!("Failed!"));
+let half = power_mod::power(3i128, 1 << 64, 1000003).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(val, half * half % 1000003);
+```
+*/
+pub fn power_with_exponent<T: Numeric, E: Exponent>(
+    x: T,
+    n: E,
+    modulo: T,
+    options: &PowerOptions,
+) -> Result<T, nt_error::NtError> {
+    if modulo <= T::zero() {
         return Err(nt_error::NtError::BadArgument);
     }
-    let mut mask = utilities::top_bit_mask(n);
-    let mut res = T::one();
-    while mask != T::zero() {
-        let opt = res.checked_mul(&res);
-        res = match opt {
-            None => return Err(nt_error::NtError::Overflow),
-            Some(val) => val % modulo,
-        };
+    if modulo == T::one() {
+        n.bit_length()?;
+        return Ok(T::zero());
+    }
+    let mut x = x % modulo;
+    if x < T::zero() {
+        x += modulo;
+    }
+    power_nonneg(x, n, modulo, options)
+}
+
+// x^n (mod modulo) for 0 <= x < modulo and n >= 0
+fn power_nonneg<T: Numeric, E: Exponent>(
+    x: T,
+    n: E,
+    modulo: T,
+    options: &PowerOptions,
+) -> Result<T, nt_error::NtError> {
+    let res = monoid::pow_monoid_with(&monoid::ModInt::new(x, modulo)?, n, options)?;
+    Ok(res.value())
+}
+
+/**
+Returns the product of base^exp (mod modulo) over a list of pairs
+
+# Arguments
+
+* `pairs` - List of (base, exp) pairs.  Negative exponents raise the inverse of the base.
+* `modulo` - Modulus - must be positive
+
+# Returns
+* The product reduced to [0, modulo) computed with monoid::multi_pow_monoid so that the squarings
+  are shared between all the bases.  Errors as in power.
+
+# Examples
+
+```
+use number_theory::number_theory::power_mod;
+
+// g^a * h^b as in verifying a Pedersen commitment
+let val = power_mod::multi_power(&[(2i64, 100), (3, 200)], 1000003)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(val, 608872);
+```
+*/
+pub fn multi_power<T: Numeric>(pairs: &[(T, T)], modulo: T) -> Result<T, nt_error::NtError> {
+    if modulo <= T::zero() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    if pairs.is_empty() {
+        return Ok(T::one() % modulo);
+    }
+
+    let mut mod_pairs = Vec::with_capacity(pairs.len());
+    // Inverses pulled out of negative exponents as in power
+    let mut extra = Vec::new();
+    for &(base, exp) in pairs {
+        let base = monoid::ModInt::new(base, modulo)?;
+        if exp < T::zero() {
+            let inv = base.inverse()?;
+            mod_pairs.push((inv, -(exp + T::one())));
+            extra.push(inv);
+        } else {
+            mod_pairs.push((base, exp));
+        }
+    }
+    let mut res = monoid::multi_pow_monoid(&mod_pairs)?;
+    for inv in extra.iter() {
+        res = res.op(inv)?;
+    }
+    Ok(res.value())
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+// Power towers
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/**
+Returns a0^(a1^(a2^...)) (mod modulo)
+
+# Arguments
+
+* `tower` - Nonnegative entries of the tower from the bottom up
+* `modulo` - Modulus - must be positive
+
+# Returns
+* The value of the tower reduced to [0, modulo).  The empty tower is 1.  BadArgument if modulo
+  isn't positive or an entry is negative.  Overflow if modulo^2 doesn't fit in T.
+
+Exponents are reduced mod Carmichael's lambda of the modulus.  This is only valid for bases
+sharing factors with the modulus when the exponent is at least the largest exponent k in the
+factorization of the modulus, so the reduced exponent is kept at or above k whenever the real
+one is.  Small parts of the tower are evaluated exactly to decide which case applies.
+
+# Examples
+
+```
+use number_theory::number_theory::power_mod;
+
+// 2^(3^(4^5)) has far too many digits to ever compute
+let val = power_mod::power_tower(&[2, 3, 4, 5], 1000)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(val, 352);
+```
+*/
+pub fn power_tower<T: Numeric>(tower: &[T], modulo: T) -> Result<T, nt_error::NtError> {
+    if modulo <= T::zero() || tower.iter().any(|&val| val < T::zero()) {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    if tower.is_empty() {
+        return Ok(T::one() % modulo);
+    }
+    power_tower_help(tower, modulo)
+}
 
-        if (mask & n) != T::zero() {
-            let opt = res.checked_mul(&x);
-            res = match opt {
-                None => return Err(nt_error::NtError::Overflow),
-                Some(val) => val % modulo,
-            };
+// Nonempty tower, positive modulus
+fn power_tower_help<T: Numeric>(tower: &[T], modulo: T) -> Result<T, nt_error::NtError> {
+    if modulo == T::one() {
+        return Ok(T::zero());
+    }
+    if let Some(val) = tower_below(tower, modulo) {
+        return Ok(val);
+    }
+    if tower.len() == 1 {
+        return Ok(tower[0] % modulo);
+    }
+
+    let factors = factorization::factor(modulo)?;
+    let lambda = totient::carmichael_lambda_factored(&factors)?;
+    let max_exp = factors.iter().map(|&(_, exp)| exp).max().unwrap();
+    let max_exp = T::from_u32(max_exp).unwrap();
+    // Smallest multiple of lambda which is at least max_exp
+    let exp_modulo = (max_exp + lambda - T::one()) / lambda * lambda;
+
+    let exp = match tower_below(&tower[1..], exp_modulo) {
+        Some(exp) => exp,
+        None => power_tower_help(&tower[1..], exp_modulo)? + exp_modulo,
+    };
+    power(tower[0], exp, modulo)
+}
+
+// Exact value of a nonempty tower if it's less than cap, else None
+fn tower_below<T: Numeric>(tower: &[T], cap: T) -> Option<T> {
+    let (&top, rest) = tower.split_last().unwrap();
+    let mut val = if top < cap { Some(top) } else { None };
+    for &base in rest.iter().rev() {
+        val = power_below(base, val, cap);
+    }
+    val
+}
+
+// Exact value of base^exp if it's less than cap, else None.  exp of None is some value >= cap.
+fn power_below<T: Numeric>(base: T, exp: Option<T>, cap: T) -> Option<T> {
+    let val = if base == T::zero() {
+        match exp {
+            Some(exp) if exp == T::zero() => T::one(),
+            _ => T::zero(),
         }
-        mask = mask >> 1;
+    } else if base == T::one() {
+        T::one()
+    } else {
+        // Exponents are < cap and base >= 2 so this runs at most log2(cap) times
+        let mut val = T::one();
+        let mut count = exp?;
+        while count > T::zero() {
+            val = val.checked_mul(&base)?;
+            if val >= cap {
+                return None;
+            }
+            count = count - T::one();
+        }
+        val
+    };
+    if val < cap {
+        Some(val)
+    } else {
+        None
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+// Constant time operations
+//
+// These never branch on or index by secret values and always do the same amount of work for a
+// given T.  Conditionals are done by multiplying by a 0/1 value instead and secrets are never
+// divided since hardware division takes a data dependent time and i128 division is a software
+// loop.  They are only as constant time as T's own arithmetic and comparisons - fine for the
+// native integers, not for UToI.
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// 1 if val is negative, 0 otherwise
+#[inline]
+fn ct_is_negative<T: Numeric>(val: T) -> T {
+    T::from_u8(val.is_negative() as u8).unwrap()
+}
+
+// Reduces 0 <= val < 2 * modulo to [0, modulo)
+#[inline]
+fn ct_reduce_once<T: Numeric>(val: T, modulo: T) -> T {
+    let diff = val - modulo;
+    diff + ct_is_negative(diff) * modulo
+}
+
+// Swaps a and b if bit is 1 and leaves them alone if it's 0
+#[inline]
+fn ct_swap<T: Numeric>(bit: T, a: &mut T, b: &mut T) {
+    let delta = bit * (*a - *b);
+    *a = *a - delta;
+    *b += delta;
+}
+
+// Reduces any value to [0, modulo) by binary long division over every bit of T - 2 * modulo
+// must fit.  Negative values reduce their complement -(val + 1) instead, whose bits are those of
+// val flipped, and take modulo - 1 - r since val = -r - 1 (mod modulo).
+fn ct_normalize<T: Numeric>(val: T, modulo: T) -> T {
+    let negative = ct_is_negative(val);
+    let mut res = T::zero();
+    for bit in (0..T::BITS as usize).rev() {
+        let b = (val >> bit) & T::one();
+        // b xor negative
+        let b = b + negative - (b + b) * negative;
+        res = ct_reduce_once(res + res + b, modulo);
+    }
+    res + negative * (modulo - T::one() - res - res)
+}
+
+// Products are built from doublings so 2 * modulo must fit in T
+fn ct_check_modulus<T: Numeric>(modulo: T) -> Result<(), nt_error::NtError> {
+    if modulo <= T::zero() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    match modulo.checked_add(&modulo) {
+        None => Err(nt_error::NtError::Overflow),
+        Some(_) => Ok(()),
+    }
+}
+
+// a * b (mod modulo) for a, b already in [0, modulo)
+pub(crate) fn mul_mod_ct_reduced<T: Numeric>(a: T, b: T, modulo: T) -> T {
+    let mut res = T::zero();
+    for bit in (0..T::BITS as usize).rev() {
+        res = ct_reduce_once(res + res, modulo);
+        res = ct_reduce_once(res + ((b >> bit) & T::one()) * a, modulo);
+    }
+    res
+}
+
+/**
+Returns a * b (mod modulo) in constant time
+
+# Arguments
+
+* `a`, `b` - Values to multiply - reduced mod modulo first
+* `modulo` - Modulus - must be positive
+
+# Returns
+* a * b reduced to [0, modulo).  The product is formed by doubling and adding over every bit of T
+  so it never overflows as long as 2 * modulo fits in T.  BadArgument if modulo isn't positive and
+  Overflow if 2 * modulo doesn't fit.
+
+# Examples
+
+```
+use number_theory::number_theory::power_mod;
+
+let modulo: i64 = 4611686018427387847;
+let val = power_mod::mul_mod_ct(modulo - 1, modulo - 1, modulo)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(val, 1);
+```
+*/
+pub fn mul_mod_ct<T: Numeric>(a: T, b: T, modulo: T) -> Result<T, nt_error::NtError> {
+    ct_check_modulus(modulo)?;
+    Ok(mul_mod_ct_reduced(
+        ct_normalize(a, modulo),
+        ct_normalize(b, modulo),
+        modulo,
+    ))
+}
+
+/**
+Returns x^n (mod modulo) in constant time
+
+# Arguments
+
+* `x` - Base - reduced mod modulo first
+* `n` - Nonnegative exponent, typically a private key
+* `modulo` - Modulus - must be positive
+
+# Returns
+* x^n reduced to [0, modulo).  Uses a Montgomery ladder over every bit of T with branch free
+  conditional swaps so neither the timing nor the memory access pattern depends on n.
+  BadArgument if n is negative or modulo isn't positive, Overflow if 2 * modulo doesn't fit in T.
+
+# Examples
+
+```
+use number_theory::number_theory::power_mod;
+
+let val = power_mod::power_ct(2357, 2357, 3599)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(val, 3115);
+```
+*/
+pub fn power_ct<T: Numeric>(x: T, n: T, modulo: T) -> Result<T, nt_error::NtError> {
+    ct_check_modulus(modulo)?;
+    if n < T::zero() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+
+    // Invariant: r1 = r0 * x
+    let mut r0 = T::one() % modulo;
+    let mut r1 = ct_normalize(x, modulo);
+    for bit in (0..T::BITS as usize).rev() {
+        let b = (n >> bit) & T::one();
+        ct_swap(b, &mut r0, &mut r1);
+        r1 = mul_mod_ct_reduced(r0, r1, modulo);
+        r0 = mul_mod_ct_reduced(r0, r0, modulo);
+        ct_swap(b, &mut r0, &mut r1);
+    }
+    Ok(r0)
+}
+
+/**
+Returns x^-1 (mod p) in constant time for prime p
+
+# Arguments
+
+* `x` - Value to invert
+* `p` - Prime modulus.  Primality isn't checked - for composite p use euclidean::inverse_mod.
+
+# Returns
+* x^(p - 2) (mod p) by Fermat's little theorem, computed with power_ct.  NoSolns if x is
+  divisible by p - the only thing the timing reveals about x since that case returns early.
+  Other errors as in power_ct.
+
+# Examples
+
+```
+use number_theory::number_theory::power_mod;
+
+let inverse = power_mod::inverse_mod_ct(3, 11)
+    .unwrap_or_else(|_| panic!("Failed"));
+assert_eq!(inverse, 4);
+```
+*/
+pub fn inverse_mod_ct<T: Numeric>(x: T, p: T) -> Result<T, nt_error::NtError> {
+    let two = T::one() + T::one();
+    if p < two {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let res = power_ct(x, p - two, p)?;
+    // x^(p - 2) is 1 rather than 0 for p = 2 so test x itself - 1 exactly when x = 0 (mod p).
+    // Branching on it only leaks whether x = 0 (mod p), which the error reveals anyway.
+    let divisible = ct_is_negative(ct_normalize(x, p) - T::one());
+    if divisible == T::one() {
+        return Err(nt_error::NtError::NoSolns);
     }
     Ok(res)
 }
@@ -0,0 +1,451 @@
+use crate::number_theory::euclidean::{self, EuclideanDomain};
+use crate::number_theory::monoid::Monoid;
+use crate::utilities::nt_error;
+use crate::utilities::numeric_trait::Numeric;
+use crate::utilities::utilities;
+
+fn overflow() -> nt_error::NtError {
+    nt_error::NtError::Overflow
+}
+
+fn mul<T: Numeric>(a: T, b: T) -> Result<T, nt_error::NtError> {
+    a.checked_mul(&b).ok_or_else(overflow)
+}
+
+fn add<T: Numeric>(a: T, b: T) -> Result<T, nt_error::NtError> {
+    a.checked_add(&b).ok_or_else(overflow)
+}
+
+fn sub<T: Numeric>(a: T, b: T) -> Result<T, nt_error::NtError> {
+    a.checked_sub(&b).ok_or_else(overflow)
+}
+
+/**
+Binary quadratic form a x^2 + b xy + c y^2
+
+Equivalence is proper equivalence - under substitutions of determinant 1 - so for positive
+discriminants the classes are those of the narrow class group.  Positive definite forms
+(discriminant < 0 and a > 0) reduce by Gauss's algorithm and indefinite forms with a non-square
+discriminant by cycling the rho operator.  Forms of other kinds report BadArgument.
+
+# Examples
+
+```
+use number_theory::number_theory::quadratic_forms::BinaryQuadraticForm;
+
+let f = BinaryQuadraticForm::new(6, 5, 2);
+let reduced = f.reduce().unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(reduced, BinaryQuadraticForm::new(2, -1, 3));
+assert_eq!(f.equivalent(&BinaryQuadraticForm::new(2, 1, 3)), Ok(false));
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BinaryQuadraticForm<T: Numeric> {
+    pub a: T,
+    pub b: T,
+    pub c: T,
+}
+
+impl<T: Numeric> BinaryQuadraticForm<T> {
+    pub fn new(a: T, b: T, c: T) -> BinaryQuadraticForm<T> {
+        BinaryQuadraticForm { a, b, c }
+    }
+
+    /**
+    Returns the reduced form of discriminant d corresponding to the identity of the class group
+
+    # Returns
+    * (1, b, (b^2 - d) / 4) with b chosen so the form is reduced.  BadArgument if d isn't 0 or 1
+      (mod 4) or is a perfect square and Overflow if b^2 - d doesn't fit in T.
+    */
+    pub fn principal(d: T) -> Result<BinaryQuadraticForm<T>, nt_error::NtError> {
+        let four = T::from_u8(4).unwrap();
+        let d_mod_4 = d.div_rem(&four).1;
+        if d_mod_4 > T::one() || utilities::is_square(d) {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        let b = if d < T::zero() {
+            d_mod_4
+        } else {
+            // Largest b < sqrt(d) with the parity of d
+            let s = utilities::isqrt(d)?;
+            if (s - d_mod_4).div_rem(&T::from_u8(2).unwrap()).1 == T::zero() {
+                s
+            } else {
+                s - T::one()
+            }
+        };
+        Ok(BinaryQuadraticForm::new(
+            T::one(),
+            b,
+            sub(mul(b, b)?, d)? / four,
+        ))
+    }
+
+    /**
+    Returns the discriminant b^2 - 4ac or Overflow
+    */
+    pub fn discriminant(&self) -> Result<T, nt_error::NtError> {
+        let four_ac = mul(mul(T::from_u8(4).unwrap(), self.a)?, self.c)?;
+        mul(self.b, self.b)?
+            .checked_sub(&four_ac)
+            .ok_or_else(overflow)
+    }
+
+    pub fn is_positive_definite(&self) -> bool {
+        self.a > T::zero() && self.discriminant().is_ok_and(|d| d < T::zero())
+    }
+
+    pub fn is_primitive(&self) -> bool {
+        euclidean::gcd(euclidean::gcd(self.a, self.b), self.c) == T::one()
+    }
+
+    /**
+    Returns whether the form is reduced
+
+    # Returns
+    * For positive definite forms |b| <= a <= c with b >= 0 if |b| = a or a = c.  For indefinite
+      forms |sqrt(D) - 2|a|| < b < sqrt(D).  BadArgument for other kinds of form.
+    */
+    pub fn is_reduced(&self) -> Result<bool, nt_error::NtError> {
+        let (a, b, c) = (self.a, self.b, self.c);
+        let d = self.discriminant()?;
+        if d < T::zero() {
+            if a <= T::zero() {
+                return Err(nt_error::NtError::BadArgument);
+            }
+            let abs_b = num::abs(b);
+            Ok(abs_b <= a && a <= c && (b >= T::zero() || (abs_b != a && a != c)))
+        } else {
+            let s = isqrt_non_square(d)?;
+            // sqrt(D) is irrational so the strict inequalities become these on floor(sqrt(D))
+            let two_a = mul(T::from_u8(2).unwrap(), num::abs(a))?;
+            Ok(b > T::zero() && b <= s && s - b < two_a && two_a <= s + b)
+        }
+    }
+
+    /**
+    Returns the reduced form properly equivalent to self
+
+    # Returns
+    * The unique reduced form in the class for positive definite forms.  For indefinite forms the
+      first reduced form reached by the rho operator - the reduced forms of a class make up one
+      rho cycle.  BadArgument for other kinds of form and Overflow if the arithmetic doesn't fit.
+
+    # Examples
+
+    ```
+    use number_theory::number_theory::quadratic_forms::BinaryQuadraticForm;
+
+    let f = BinaryQuadraticForm::new(10i64, 23, 11).reduce()
+        .unwrap_or_else(|_| panic!("Failed!"));
+    assert_eq!(f.discriminant(), Ok(89));
+    assert_eq!(f.is_reduced(), Ok(true));
+    ```
+    */
+    pub fn reduce(&self) -> Result<BinaryQuadraticForm<T>, nt_error::NtError> {
+        let d = self.discriminant()?;
+        if d < T::zero() {
+            if self.a <= T::zero() {
+                return Err(nt_error::NtError::BadArgument);
+            }
+            reduce_definite(*self, d)
+        } else {
+            let s = isqrt_non_square(d)?;
+            let mut f = *self;
+            while !f.is_reduced()? {
+                f = rho(f, d, s)?;
+            }
+            Ok(f)
+        }
+    }
+
+    /**
+    Returns whether self and other are properly equivalent
+
+    # Returns
+    * False for forms of different discriminants.  Positive definite forms are compared by their
+      reduced forms and indefinite forms by searching the rho cycle of one for the reduced form of
+      the other.  Errors as in reduce.
+    */
+    pub fn equivalent(&self, other: &BinaryQuadraticForm<T>) -> Result<bool, nt_error::NtError> {
+        let d = self.discriminant()?;
+        if d != other.discriminant()? {
+            return Ok(false);
+        }
+        let f = self.reduce()?;
+        let g = other.reduce()?;
+        if d < T::zero() {
+            return Ok(f == g);
+        }
+        Ok(rho_cycle(f, d)?.contains(&g))
+    }
+
+    /**
+    Returns the composition of self and other reduced
+
+    # Returns
+    * The reduced form of the composite class by Cohen's algorithm 5.4.7 (Dirichlet composition
+      via united forms) with the gcds and Bezout coefficients from euclidean::calc_euclidean_ext.
+      BadArgument if the discriminants differ or the forms can't be reduced and Overflow if the
+      arithmetic doesn't fit.
+
+    # Examples
+
+    ```
+    use number_theory::number_theory::quadratic_forms::BinaryQuadraticForm;
+
+    // The class group of discriminant -23 is cyclic of order 3
+    let f = BinaryQuadraticForm::new(2i64, 1, 3);
+    let f2 = f.compose(&f).unwrap_or_else(|_| panic!("Failed!"));
+    assert_eq!(f2, BinaryQuadraticForm::new(2, -1, 3));
+    let f3 = f2.compose(&f).unwrap_or_else(|_| panic!("Failed!"));
+    assert_eq!(f3, BinaryQuadraticForm::new(1, 1, 6));
+    ```
+    */
+    pub fn compose(
+        &self,
+        other: &BinaryQuadraticForm<T>,
+    ) -> Result<BinaryQuadraticForm<T>, nt_error::NtError> {
+        let d = self.discriminant()?;
+        if d != other.discriminant()? {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        let (f1, f2) = if num::abs(self.a) > num::abs(other.a) {
+            (other, self)
+        } else {
+            (self, other)
+        };
+        let two = T::from_u8(2).unwrap();
+        let s = add(f1.b, f2.b)? / two;
+        let n = f2.b - s;
+
+        // u a2 + v a1 = d0 = gcd(a1, a2)
+        let (y1, d0) = if f2.a.div_rem(&f1.a).1 == T::zero() {
+            (T::zero(), num::abs(f1.a))
+        } else {
+            let (d0, u, _) = euclidean::calc_euclidean_ext(f2.a, f1.a);
+            (u, d0)
+        };
+        // x2 s + y2 d0 = d1 = gcd(s, d0) with y2 negated
+        let (x2, y2, d1) = if s.div_rem(&d0).1 == T::zero() {
+            (T::zero(), -T::one(), d0)
+        } else {
+            let (d1, x2, y2) = euclidean::calc_euclidean_ext(s, d0);
+            (x2, -y2, d1)
+        };
+
+        let v1 = f1.a / d1;
+        let v2 = f2.a / d1;
+        let r = mul(mul(y1, y2)?, n)?
+            .checked_sub(&mul(x2, f2.c)?)
+            .ok_or_else(overflow)?
+            .div_rem(&v1)
+            .1;
+        let b3 = add(f2.b, mul(mul(two, v2)?, r)?)?;
+        let a3 = mul(v1, v2)?;
+        let c3 =
+            mul(b3, b3)?.checked_sub(&d).ok_or_else(overflow)? / mul(T::from_u8(4).unwrap(), a3)?;
+        BinaryQuadraticForm::new(a3, b3, c3).reduce()
+    }
+
+    /**
+    Returns the class of the inverse (a, -b, c) reduced
+    */
+    pub fn inverse(&self) -> Result<BinaryQuadraticForm<T>, nt_error::NtError> {
+        BinaryQuadraticForm::new(self.a, sub(T::zero(), self.b)?, self.c).reduce()
+    }
+}
+
+// floor(sqrt(d)) for positive non-square d
+fn isqrt_non_square<T: Numeric>(d: T) -> Result<T, nt_error::NtError> {
+    if d <= T::zero() || utilities::is_square(d) {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    utilities::isqrt(d)
+}
+
+// Gauss reduction - normalize b into (-a, a] then swap a and c while a > c
+fn reduce_definite<T: Numeric>(
+    f: BinaryQuadraticForm<T>,
+    d: T,
+) -> Result<BinaryQuadraticForm<T>, nt_error::NtError> {
+    let two = T::from_u8(2).unwrap();
+    let four = T::from_u8(4).unwrap();
+    // c recomputed from the discriminant once b has been normalized
+    let normalize = |a: T, b: T| -> Result<BinaryQuadraticForm<T>, nt_error::NtError> {
+        let two_a = mul(two, a)?;
+        let mut b = b.div_rem(&two_a).1;
+        if b > a {
+            b = b - two_a;
+        }
+        let c = mul(b, b)?.checked_sub(&d).ok_or_else(overflow)? / mul(four, a)?;
+        Ok(BinaryQuadraticForm::new(a, b, c))
+    };
+    let mut f = normalize(f.a, f.b)?;
+    while f.a > f.c {
+        f = normalize(f.c, -f.b)?;
+    }
+    if f.a == f.c && f.b < T::zero() {
+        f.b = -f.b;
+    }
+    Ok(f)
+}
+
+// rho(a, b, c) = (c, r, (r^2 - D) / 4c) with r = -b (mod 2c) normalized as in Cohen 5.6.4
+fn rho<T: Numeric>(
+    f: BinaryQuadraticForm<T>,
+    d: T,
+    s: T,
+) -> Result<BinaryQuadraticForm<T>, nt_error::NtError> {
+    let abs_c = num::abs(f.c);
+    let two_c = mul(T::from_u8(2).unwrap(), abs_c)?;
+    // Lower end of the half open window (low, low + 2|c|] that r has to land in
+    let low = if abs_c > s { -abs_c } else { s - two_c };
+    let mut r = (-f.b - low).div_rem(&two_c).1 + low;
+    if r == low {
+        r += two_c;
+    }
+    let c = mul(r, r)?.checked_sub(&d).ok_or_else(overflow)? / mul(T::from_u8(4).unwrap(), f.c)?;
+    Ok(BinaryQuadraticForm::new(f.c, r, c))
+}
+
+// The reduced forms properly equivalent to the reduced indefinite form f in rho order
+fn rho_cycle<T: Numeric>(
+    f: BinaryQuadraticForm<T>,
+    d: T,
+) -> Result<Vec<BinaryQuadraticForm<T>>, nt_error::NtError> {
+    let s = isqrt_non_square(d)?;
+    let mut ret = vec![f];
+    let mut g = rho(f, d, s)?;
+    while g != f {
+        ret.push(g);
+        g = rho(g, d, s)?;
+    }
+    Ok(ret)
+}
+
+/**
+Returns the primitive reduced forms of discriminant d
+
+# Arguments
+
+* `d` - Discriminant - 0 or 1 (mod 4) and not a perfect square
+
+# Returns
+* The primitive reduced forms sorted by (a, b, c).  For d < 0 these are positive definite and
+  there is exactly one per class.  For d > 0 each class contributes a whole rho cycle.
+  BadArgument for an invalid discriminant and Overflow if the forms don't fit in T.
+
+# Examples
+
+```
+use number_theory::number_theory::quadratic_forms::{self, BinaryQuadraticForm};
+
+let forms = quadratic_forms::reduced_forms(-20)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(forms, vec![BinaryQuadraticForm::new(1, 0, 5), BinaryQuadraticForm::new(2, 2, 3)]);
+```
+*/
+pub fn reduced_forms<T: Numeric>(d: T) -> Result<Vec<BinaryQuadraticForm<T>>, nt_error::NtError> {
+    // Validates d
+    BinaryQuadraticForm::principal(d)?;
+    let (two, four) = (T::from_u8(2).unwrap(), T::from_u8(4).unwrap());
+    let mut ret = Vec::new();
+    let mut push = |a: T, b: T| -> Result<(), nt_error::NtError> {
+        // ac = (b^2 - d) / 4 so a must divide that
+        let (c, rem) = sub(mul(b, b)?, d)?.div_rem(&mul(four, a)?);
+        let f = BinaryQuadraticForm::new(a, b, c);
+        if rem == T::zero() && f.is_primitive() && f.is_reduced()? {
+            ret.push(f);
+        }
+        Ok(())
+    };
+    if d < T::zero() {
+        // |b| <= a <= c gives 3a^2 <= |d|
+        let abs_d = sub(T::zero(), d)?;
+        let mut a = T::one();
+        while mul(mul(T::from_u8(3).unwrap(), a)?, a)? <= abs_d {
+            let mut b = -a;
+            while b <= a {
+                push(a, b)?;
+                b += T::one();
+            }
+            a += T::one();
+        }
+    } else {
+        // 0 < b < sqrt(d) and 0 < 2|a| < sqrt(d) + b < 2 sqrt(d)
+        let s = isqrt_non_square(d)?;
+        let mut b = T::one();
+        while b <= s {
+            let mut a = T::one();
+            while mul(two, a)? <= add(s, b)? {
+                push(a, b)?;
+                push(-a, b)?;
+                a += T::one();
+            }
+            b += T::one();
+        }
+    }
+    ret.sort_by_key(|f| (f.a, f.b, f.c));
+    Ok(ret)
+}
+
+/**
+Returns the class number h(d) - the number of proper equivalence classes of primitive forms
+
+# Arguments
+
+* `d` - Discriminant - 0 or 1 (mod 4) and not a perfect square
+
+# Returns
+* For d < 0 the number of positive definite classes which is the number of reduced forms.  For
+  d > 0 the number of rho cycles among the reduced forms - the narrow class number.  Errors as in
+  reduced_forms.
+
+# Examples
+
+```
+use number_theory::number_theory::quadratic_forms;
+
+assert_eq!(quadratic_forms::class_number(-23), Ok(3));
+assert_eq!(quadratic_forms::class_number(229), Ok(3));
+```
+*/
+pub fn class_number<T: Numeric>(d: T) -> Result<usize, nt_error::NtError> {
+    let forms = reduced_forms(d)?;
+    if d < T::zero() {
+        return Ok(forms.len());
+    }
+    let mut seen = vec![false; forms.len()];
+    let mut ret = 0;
+    for i in 0..forms.len() {
+        if seen[i] {
+            continue;
+        }
+        ret += 1;
+        for f in rho_cycle(forms[i], d)? {
+            if let Ok(idx) = forms.binary_search_by_key(&(f.a, f.b, f.c), |g| (g.a, g.b, g.c)) {
+                seen[idx] = true;
+            }
+        }
+    }
+    Ok(ret)
+}
+
+impl<T: Numeric> Monoid for BinaryQuadraticForm<T> {
+    // The principal form of the same discriminant - self is returned if that doesn't exist
+    fn identity(&self) -> Self {
+        self.discriminant()
+            .and_then(BinaryQuadraticForm::principal)
+            .unwrap_or(*self)
+    }
+
+    fn op(&self, other: &Self) -> Result<Self, nt_error::NtError> {
+        self.compose(other)
+    }
+
+    fn inverse(&self) -> Result<Self, nt_error::NtError> {
+        BinaryQuadraticForm::inverse(self)
+    }
+}
@@ -0,0 +1,474 @@
+use crate::number_theory::continued_fraction;
+use crate::number_theory::euclidean::gcd;
+use crate::utilities::nt_error;
+use crate::utilities::numeric_trait::Numeric;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/**
+Exact rational number kept in lowest terms with a positive denominator
+
+Since the representation is unique equal values compare equal field by field.  Arithmetic is
+overflow checked and cancels common factors before multiplying so intermediate values stay as
+small as possible.  Ordering compares continued fractions so it never overflows.
+
+# Examples
+
+```
+use number_theory::number_theory::rational::Rational;
+
+let a = Rational::new(6i64, -8).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!((a.numer(), a.denom()), (-3, 4));
+let b: Rational<i64> = "5/6".parse().unwrap_or_else(|_| panic!("Failed!"));
+let sum = a.checked_add(&b).unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(sum.to_string(), "1/12");
+assert!(a < b);
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rational<T: Numeric> {
+    num: T,
+    den: T,
+}
+
+// -n or Overflow for the most negative value
+fn neg_or_overflow<T: Numeric>(n: T) -> Result<T, nt_error::NtError> {
+    T::zero().checked_sub(&n).ok_or(nt_error::NtError::Overflow)
+}
+
+// Floor and the nonnegative remainder of num / den for den > 0.  Unlike floor_div followed by a
+// multiplication this can't overflow.
+fn floor_div_rem<T: Numeric>(num: T, den: T) -> (T, T) {
+    let (q, r) = (num / den, num % den);
+    if r < T::zero() {
+        (q - T::one(), r + den)
+    } else {
+        (q, r)
+    }
+}
+
+// 2^e or Overflow if that doesn't fit in T
+fn pow2<T: Numeric>(e: u32) -> Result<T, nt_error::NtError> {
+    if e + 2 > T::BITS {
+        return Err(nt_error::NtError::Overflow);
+    }
+    Ok(T::one() << e as usize)
+}
+
+// (negative, mantissa, exponent) with |x| = mantissa * 2^exponent
+fn decode_f64(x: f64) -> Result<(bool, u64, i32), nt_error::NtError> {
+    if !x.is_finite() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let bits = x.to_bits();
+    let exp_bits = ((bits >> 52) & 0x7ff) as i32;
+    let frac = bits & ((1u64 << 52) - 1);
+    let (mantissa, exp) = if exp_bits == 0 {
+        // Subnormal
+        (frac, -1074)
+    } else {
+        (frac | (1u64 << 52), exp_bits - 1075)
+    };
+    Ok((bits >> 63 == 1, mantissa, exp))
+}
+
+impl<T: Numeric> Rational<T> {
+    /**
+    Creates the rational num / den in lowest terms
+
+    # Returns
+    * The rational or BadArgument if den is zero.  Overflow if the result doesn't fit - only when
+      den is negative and the reduced num or den is the most negative value of T as in MIN / -1.
+    */
+    pub fn new(num: T, den: T) -> Result<Rational<T>, nt_error::NtError> {
+        if den == T::zero() {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        // gcd can't return the magnitude of the most negative value or divide it by -1 so take
+        // care of those cases first
+        if num == T::zero() || num == den {
+            return Ok(Rational::from_integer(if num == T::zero() {
+                T::zero()
+            } else {
+                T::one()
+            }));
+        }
+        if den == -T::one() {
+            return Ok(Rational::from_integer(neg_or_overflow(num)?));
+        }
+        // Reduce before fixing the sign so only results which really don't fit overflow
+        let g = gcd(num, den);
+        let (num, den) = (num / g, den / g);
+        if den < T::zero() {
+            return Ok(Rational {
+                num: neg_or_overflow(num)?,
+                den: neg_or_overflow(den)?,
+            });
+        }
+        Ok(Rational { num, den })
+    }
+
+    pub fn from_integer(n: T) -> Rational<T> {
+        Rational {
+            num: n,
+            den: T::one(),
+        }
+    }
+
+    pub fn numer(&self) -> T {
+        self.num
+    }
+
+    pub fn denom(&self) -> T {
+        self.den
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == T::one()
+    }
+
+    /**
+    Returns -self or Overflow
+    */
+    pub fn checked_neg(&self) -> Result<Rational<T>, nt_error::NtError> {
+        Ok(Rational {
+            num: neg_or_overflow(self.num)?,
+            den: self.den,
+        })
+    }
+
+    /**
+    Returns 1 / self
+
+    # Returns
+    * The reciprocal or BadArgument if self is zero
+    */
+    pub fn recip(&self) -> Result<Rational<T>, nt_error::NtError> {
+        if self.num == T::zero() {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        if self.num < T::zero() {
+            Ok(Rational {
+                num: neg_or_overflow(self.den)?,
+                den: neg_or_overflow(self.num)?,
+            })
+        } else {
+            Ok(Rational {
+                num: self.den,
+                den: self.num,
+            })
+        }
+    }
+
+    /**
+    Returns self + other
+
+    # Returns
+    * The sum or Overflow.  Only the gcd of the denominators is multiplied out so this succeeds
+      whenever the reduced numerator and the lcm of the denominators fit in T.
+    */
+    pub fn checked_add(&self, other: &Rational<T>) -> Result<Rational<T>, nt_error::NtError> {
+        let overflow = || nt_error::NtError::Overflow;
+        let g = gcd(self.den, other.den);
+        let (b, d) = (self.den / g, other.den / g);
+        let num = self
+            .num
+            .checked_mul(&d)
+            .and_then(|ad| other.num.checked_mul(&b).and_then(|cb| ad.checked_add(&cb)))
+            .ok_or_else(overflow)?;
+        if num == T::zero() {
+            return Ok(Rational::from_integer(num));
+        }
+        // Any common factor of the sum and the new denominator divides g
+        let g2 = gcd(num, g);
+        let den = b.checked_mul(&(other.den / g2)).ok_or_else(overflow)?;
+        Ok(Rational { num: num / g2, den })
+    }
+
+    /**
+    Returns self - other
+
+    # Returns
+    * The difference or Overflow
+    */
+    pub fn checked_sub(&self, other: &Rational<T>) -> Result<Rational<T>, nt_error::NtError> {
+        self.checked_add(&other.checked_neg()?)
+    }
+
+    /**
+    Returns self * other
+
+    # Returns
+    * The product or Overflow.  Numerators are cross reduced against denominators first so this
+      succeeds whenever the result in lowest terms fits in T.
+    */
+    pub fn checked_mul(&self, other: &Rational<T>) -> Result<Rational<T>, nt_error::NtError> {
+        let overflow = || nt_error::NtError::Overflow;
+        if self.num == T::zero() || other.num == T::zero() {
+            return Ok(Rational::from_integer(T::zero()));
+        }
+        let g1 = gcd(self.num, other.den);
+        let g2 = gcd(other.num, self.den);
+        let num = (self.num / g1)
+            .checked_mul(&(other.num / g2))
+            .ok_or_else(overflow)?;
+        let den = (self.den / g2)
+            .checked_mul(&(other.den / g1))
+            .ok_or_else(overflow)?;
+        Ok(Rational { num, den })
+    }
+
+    /**
+    Returns self / other
+
+    # Returns
+    * The quotient, BadArgument if other is zero or Overflow
+    */
+    pub fn checked_div(&self, other: &Rational<T>) -> Result<Rational<T>, nt_error::NtError> {
+        self.checked_mul(&other.recip()?)
+    }
+
+    /**
+    Returns the closest rational to self with a bounded denominator
+
+    # Arguments
+
+    * `max_den` - Largest allowed denominator - must be positive
+
+    # Returns
+    * The best approximation as in continued_fraction::best_approximation
+    */
+    pub fn limit_denominator(&self, max_den: T) -> Result<Rational<T>, nt_error::NtError> {
+        let (num, den) = continued_fraction::best_approximation(self.num, self.den, max_den)?;
+        Ok(Rational { num, den })
+    }
+
+    /**
+    Returns the exact value of an f64
+
+    Every finite f64 is a dyadic rational m / 2^k so this is exact.
+
+    # Returns
+    * The rational, BadArgument for infinities and NaN or Overflow if the value doesn't fit
+
+    # Examples
+
+    ```
+    use number_theory::number_theory::rational::Rational;
+
+    let r = Rational::<i64>::from_f64(-2.375).unwrap_or_else(|_| panic!("Failed!"));
+    assert_eq!((r.numer(), r.denom()), (-19, 8));
+    ```
+    */
+    pub fn from_f64(x: f64) -> Result<Rational<T>, nt_error::NtError> {
+        let (neg, mantissa, exp) = decode_f64(x)?;
+        Rational::from_dyadic(neg, mantissa, exp)
+    }
+
+    /**
+    Returns the best approximation to an f64 with a bounded denominator
+
+    # Arguments
+
+    * `x` - Value to approximate
+    * `max_den` - Largest allowed denominator - must be positive
+
+    # Returns
+    * The closest rational to x with denominator at most max_den.  If the exact value of x
+      doesn't fit in T it's first rounded to the nearest dyadic rational which does.  Errors as in
+      from_f64 and limit_denominator.
+
+    # Examples
+
+    ```
+    use number_theory::number_theory::rational::Rational;
+
+    let pi = Rational::<i64>::from_f64_approx(std::f64::consts::PI, 1000)
+        .unwrap_or_else(|_| panic!("Failed!"));
+    assert_eq!((pi.numer(), pi.denom()), (355, 113));
+    ```
+    */
+    pub fn from_f64_approx(x: f64, max_den: T) -> Result<Rational<T>, nt_error::NtError> {
+        let (neg, mut mantissa, mut exp) = decode_f64(x)?;
+        loop {
+            match Rational::from_dyadic(neg, mantissa, exp) {
+                // Drop a bit of precision rounding to nearest and try again
+                Err(nt_error::NtError::Overflow) if exp < 0 => {
+                    mantissa = (mantissa + 1) >> 1;
+                    exp += 1;
+                }
+                ret => return ret?.limit_denominator(max_den),
+            }
+        }
+    }
+
+    fn from_dyadic(neg: bool, mantissa: u64, exp: i32) -> Result<Rational<T>, nt_error::NtError> {
+        if mantissa == 0 {
+            return Ok(Rational::from_integer(T::zero()));
+        }
+        let overflow = || nt_error::NtError::Overflow;
+        let shift = mantissa.trailing_zeros();
+        let (mantissa, exp) = (mantissa >> shift, exp + shift as i32);
+        let mut num = T::from_u64(mantissa).ok_or_else(overflow)?;
+        if neg {
+            num = -num;
+        }
+        if exp >= 0 {
+            let num = num.checked_mul(&pow2(exp as u32)?).ok_or_else(overflow)?;
+            Ok(Rational::from_integer(num))
+        } else {
+            // The mantissa is odd so this is already in lowest terms
+            Ok(Rational {
+                num,
+                den: pow2((-exp) as u32)?,
+            })
+        }
+    }
+
+    /**
+    Returns the nearest f64 to self
+
+    Both parts are converted and then divided so the result is correctly rounded when they're
+    exactly representable (at most 2^53 in absolute value).
+    */
+    pub fn to_f64(&self) -> f64 {
+        let num = self.num.to_f64().unwrap_or(f64::NAN);
+        let den = self.den.to_f64().unwrap_or(f64::NAN);
+        num / den
+    }
+}
+
+impl<T: Numeric> From<T> for Rational<T> {
+    fn from(n: T) -> Rational<T> {
+        Rational::from_integer(n)
+    }
+}
+
+impl<T: Numeric> PartialOrd for Rational<T> {
+    fn partial_cmp(&self, other: &Rational<T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Numeric> Ord for Rational<T> {
+    // Walk the continued fractions of both values in step.  The first differing partial quotient
+    // decides and the sense of the comparison flips at each level.
+    fn cmp(&self, other: &Rational<T>) -> Ordering {
+        let (mut a, mut b) = (self.num, self.den);
+        let (mut c, mut d) = (other.num, other.den);
+        let mut flipped = false;
+        loop {
+            let (q1, r1) = floor_div_rem(a, b);
+            let (q2, r2) = floor_div_rem(c, d);
+            let ord = if q1 != q2 {
+                q1.cmp(&q2)
+            } else if r1 == T::zero() || r2 == T::zero() {
+                // A finished expansion is smaller than one which goes on
+                (r2 == T::zero()).cmp(&(r1 == T::zero()))
+            } else {
+                // Compare b / r1 with d / r2 which reverses the comparison
+                a = b;
+                b = r1;
+                c = d;
+                d = r2;
+                flipped = !flipped;
+                continue;
+            };
+            return if flipped { ord.reverse() } else { ord };
+        }
+    }
+}
+
+impl<T: Numeric + fmt::Display> fmt::Display for Rational<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.den == T::one() {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+// Integer from decimal digits, negated if neg.  Negative values are accumulated downwards so that
+// T's minimum value can be parsed.
+fn parse_digits<T: Numeric>(digits: &str, neg: bool) -> Result<T, nt_error::NtError> {
+    let ten = T::from_u8(10).unwrap();
+    let mut ret = T::zero();
+    for c in digits.chars() {
+        let digit = c
+            .to_digit(10)
+            .and_then(T::from_u32)
+            .ok_or(nt_error::NtError::BadArgument)?;
+        ret = ret
+            .checked_mul(&ten)
+            .and_then(|r| {
+                if neg {
+                    r.checked_sub(&digit)
+                } else {
+                    r.checked_add(&digit)
+                }
+            })
+            .ok_or(nt_error::NtError::Overflow)?;
+    }
+    Ok(ret)
+}
+
+// Signed integer or decimal like "-12", "+3.25" or ".5"
+fn parse_decimal<T: Numeric>(s: &str) -> Result<Rational<T>, nt_error::NtError> {
+    let (neg, s) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (int_part, frac_part) = match s.find('.') {
+        Some(idx) => (&s[..idx], &s[idx + 1..]),
+        None => (s, ""),
+    };
+    if int_part.is_empty() && frac_part.is_empty() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let overflow = || nt_error::NtError::Overflow;
+    let ten = T::from_u8(10).unwrap();
+    let mut den = T::one();
+    for _ in 0..frac_part.len() {
+        den = den.checked_mul(&ten).ok_or_else(overflow)?;
+    }
+    let frac = parse_digits::<T>(frac_part, neg)?;
+    let num = parse_digits::<T>(int_part, neg)?
+        .checked_mul(&den)
+        .and_then(|n| n.checked_add(&frac))
+        .ok_or_else(overflow)?;
+    Rational::new(num, den)
+}
+
+/**
+Parses "a/b" where a and b are integers or decimals, or a single integer or decimal
+
+Errors are BadArgument for malformed input or a zero denominator and Overflow if a value doesn't
+fit in T.
+
+# Examples
+
+```
+use number_theory::number_theory::rational::Rational;
+
+let r: Rational<i64> = "-1.25".parse().unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!((r.numer(), r.denom()), (-5, 4));
+let r: Rational<i64> = " 10/-4 ".parse().unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!((r.numer(), r.denom()), (-5, 2));
+```
+*/
+impl<T: Numeric> FromStr for Rational<T> {
+    type Err = nt_error::NtError;
+
+    fn from_str(s: &str) -> Result<Rational<T>, nt_error::NtError> {
+        match s.find('/') {
+            Some(idx) => {
+                let num = parse_decimal(s[..idx].trim())?;
+                let den = parse_decimal(s[idx + 1..].trim())?;
+                num.checked_div(&den)
+            }
+            None => parse_decimal(s.trim()),
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use crate::number_theory::euclidean::{self, EuclideanDomain};
+use crate::number_theory::factorization;
+use crate::number_theory::power_mod::power;
+use crate::number_theory::totient;
+use crate::utilities::nt_error;
+use crate::utilities::numeric_trait::Numeric;
+
+fn mul_mod<T: Numeric>(a: T, b: T, modulo: T) -> Result<T, nt_error::NtError> {
+    match a.checked_mul(&b) {
+        None => Err(nt_error::NtError::Overflow),
+        Some(val) => Ok(val % modulo),
+    }
+}
+
+/**
+Returns the Legendre symbol (a / p) by Euler's criterion
+
+# Arguments
+
+* `a` - Any number
+* `p` - Odd prime
+
+# Returns
+* 0 if p divides a, 1 if a is a nonzero square mod p and -1 otherwise.  BadArgument if p is
+  less than 3 and Overflow if p^2 doesn't fit in T.
+
+# Examples
+
+```
+use number_theory::number_theory::sqrt_mod;
+
+assert_eq!(sqrt_mod::legendre_symbol(2, 7), Ok(1));
+assert_eq!(sqrt_mod::legendre_symbol(3, 7), Ok(-1));
+assert_eq!(sqrt_mod::legendre_symbol(14, 7), Ok(0));
+```
+*/
+pub fn legendre_symbol<T: Numeric>(a: T, p: T) -> Result<i32, nt_error::NtError> {
+    if p < T::from_u8(3).unwrap() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let euler = power(a, (p - T::one()) >> 1, p)?;
+    Ok(if euler == T::zero() {
+        0
+    } else if euler == T::one() {
+        1
+    } else {
+        -1
+    })
+}
+
+/**
+Returns a square root of a modulo a prime by Tonelli-Shanks
+
+# Arguments
+
+* `a` - Number whose square root is wanted
+* `p` - Prime modulus
+
+# Returns
+* x in [0, p) with x^2 = a (mod p).  The other root is p - x.  NoSolns if a isn't a square mod p,
+  BadArgument if p is less than 2 or is detected not to be prime and Overflow if p^2 doesn't fit
+  in T.
+
+# Examples
+
+```
+use number_theory::number_theory::sqrt_mod;
+
+// A square root of -1 mod 13
+let x = sqrt_mod::sqrt_mod_prime(-1, 13)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(x * x % 13, 12);
+```
+*/
+pub fn sqrt_mod_prime<T: Numeric>(a: T, p: T) -> Result<T, nt_error::NtError> {
+    let two = T::from_u8(2).unwrap();
+    if p < two {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let a = power(a, T::one(), p)?;
+    if p == two || a == T::zero() {
+        return Ok(a);
+    }
+    if legendre_symbol(a, p)? != 1 {
+        return Err(nt_error::NtError::NoSolns);
+    }
+
+    // p - 1 = q 2^s with q odd
+    let mut q = p - T::one();
+    let mut s = 0u32;
+    while (q & T::one()) == T::zero() {
+        q = q >> 1;
+        s += 1;
+    }
+    if s == 1 {
+        // p = 3 (mod 4) so a^((p + 1) / 4) works directly
+        return power(a, (p + T::one()) >> 2, p);
+    }
+
+    // Any non-residue z gives a generator c of the 2-Sylow subgroup.  Half of [2, p) are
+    // non-residues so failing to find one means p isn't prime.
+    let mut z = two;
+    while legendre_symbol(z, p)? != -1 {
+        z += T::one();
+        if z == p {
+            return Err(nt_error::NtError::BadArgument);
+        }
+    }
+    let mut m = s;
+    let mut c = power(z, q, p)?;
+    let mut t = power(a, q, p)?;
+    let mut x = power(a, (q + T::one()) >> 1, p)?;
+
+    // Invariant: x^2 = a t with t of order 2^i for some i < m
+    while t != T::one() {
+        let mut i = 0u32;
+        let mut t_pow = t;
+        while t_pow != T::one() {
+            t_pow = mul_mod(t_pow, t_pow, p)?;
+            i += 1;
+            if i == m {
+                return Err(nt_error::NtError::BadArgument);
+            }
+        }
+        let mut b = c;
+        for _ in 0..(m - i - 1) {
+            b = mul_mod(b, b, p)?;
+        }
+        m = i;
+        c = mul_mod(b, b, p)?;
+        t = mul_mod(t, c, p)?;
+        x = mul_mod(x, b, p)?;
+    }
+    Ok(x)
+}
+
+// x with x = r1 (mod m1) and x = r2 (mod m2) in [0, m1 m2) for coprime m1 and m2
+fn crt_pair<T: Numeric>(r1: T, m1: T, r2: T, m2: T) -> Result<T, nt_error::NtError> {
+    let inv = euclidean::inverse_mod(m1, m2)?;
+    let diff = (r2 - r1).div_rem(&m2).1;
+    let t = mul_mod(diff, inv, m2)?;
+    m1.checked_mul(&t)
+        .and_then(|m1t| m1t.checked_add(&r1))
+        .ok_or(nt_error::NtError::Overflow)
+}
+
+// All square roots of the odd a mod 2^k
+fn sqrt_mod_two_power<T: Numeric>(a: T, k: u32) -> Result<Vec<T>, nt_error::NtError> {
+    let two = T::from_u8(2).unwrap();
+    // Every root mod 2^j reduces to a root mod 2^(j - 1) so lift by trying both preimages
+    let mut roots = vec![T::one()];
+    let mut half = T::one();
+    for _ in 1..k {
+        let modulo = half * two * two;
+        let target = a.div_rem(&modulo).1;
+        let mut lifted = Vec::new();
+        for &r in roots.iter() {
+            for &cand in [r, r + half * two].iter() {
+                if mul_mod(cand, cand, modulo)? == target {
+                    lifted.push(cand);
+                }
+            }
+        }
+        roots = lifted;
+        half *= two;
+    }
+    Ok(roots)
+}
+
+// All square roots of a mod p^k in increasing order - possibly none
+fn sqrt_mod_prime_power<T: Numeric>(a: T, p: T, k: u32) -> Result<Vec<T>, nt_error::NtError> {
+    let two = T::from_u8(2).unwrap();
+    let pk = totient::prime_power(p, k)?;
+    let a = a.div_rem(&pk).1;
+
+    // a = p^j u with u a unit.  Any root is p^(j / 2) times a root of u mod p^(k - j) for even j
+    // and x = 0 works modulo p^k when p^ceil(k / 2) divides it.
+    let mut j = 0;
+    let mut u = a;
+    while j < k && u.div_rem(&p).1 == T::zero() {
+        u = u / p;
+        j += 1;
+    }
+    let (h, m) = if j == k {
+        (k.div_ceil(2), 0)
+    } else {
+        (j / 2, k - j)
+    };
+    if j < k && j % 2 == 1 {
+        return Ok(vec![]);
+    }
+    let unit_roots = if m == 0 {
+        vec![T::zero()]
+    } else if p == two {
+        sqrt_mod_two_power(u, m)?
+    } else {
+        // Hensel lifting - the derivative 2r is a unit so each root lifts uniquely
+        let mut r = match sqrt_mod_prime(u, p) {
+            Ok(r) => r,
+            Err(nt_error::NtError::NoSolns) => return Ok(vec![]),
+            Err(e) => return Err(e),
+        };
+        let mut pi = p;
+        for _ in 1..m {
+            pi *= p;
+            let err = (mul_mod(r, r, pi)? - u).div_rem(&pi).1;
+            let inv = euclidean::inverse_mod(two * r, pi)?;
+            r = (r - mul_mod(err, inv, pi)?).div_rem(&pi).1;
+        }
+        vec![r, pi - r]
+    };
+
+    // The roots are p^h y for y running over the unit roots mod p^m and all their lifts mod
+    // p^(k - h)
+    let ph = totient::prime_power(p, h)?;
+    let pm = totient::prime_power(p, m)?;
+    let mut ret = Vec::new();
+    for &y in unit_roots.iter() {
+        let mut lift = y;
+        while lift * ph < pk {
+            ret.push(lift * ph);
+            lift += pm;
+        }
+    }
+    ret.sort();
+    ret.dedup();
+    Ok(ret)
+}
+
+/**
+Returns all the square roots of a modulo any n
+
+# Arguments
+
+* `a` - Number whose square roots are wanted
+* `n` - Positive modulus
+
+# Returns
+* The x in [0, n) with x^2 = a (mod n) in increasing order.  n is factored by trial division and
+  the roots mod each prime power combined by the Chinese remainder theorem.  Roots of units mod
+  odd prime powers are Hensel lifts of sqrt_mod_prime and mod powers of 2 are lifted a bit at a
+  time.  When p^j exactly divides a the roots are p^(j / 2) times lifts of roots of a / p^j so
+  there can be many.  NoSolns if there are no roots, BadArgument if n isn't positive and Overflow
+  if n^2 doesn't fit in T.
+
+# Examples
+
+```
+use number_theory::number_theory::sqrt_mod;
+
+let roots = sqrt_mod::sqrt_mod(-1, 65)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(roots, vec![8, 18, 47, 57]);
+let roots = sqrt_mod::sqrt_mod(0, 12)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(roots, vec![0, 6]);
+```
+*/
+pub fn sqrt_mod<T: Numeric>(a: T, n: T) -> Result<Vec<T>, nt_error::NtError> {
+    if n <= T::zero() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    if n.checked_mul(&n).is_none() {
+        return Err(nt_error::NtError::Overflow);
+    }
+
+    let mut roots = vec![T::zero()];
+    let mut modulo = T::one();
+    for (p, k) in factorization::factor(n)? {
+        let pk_roots = sqrt_mod_prime_power(a, p, k)?;
+        if pk_roots.is_empty() {
+            return Err(nt_error::NtError::NoSolns);
+        }
+        let pk = totient::prime_power(p, k)?;
+        let mut combined = Vec::new();
+        for &r1 in roots.iter() {
+            for &r2 in pk_roots.iter() {
+                combined.push(crt_pair(r1, modulo, r2, pk)?);
+            }
+        }
+        roots = combined;
+        modulo *= pk;
+    }
+    roots.sort();
+    Ok(roots)
+}
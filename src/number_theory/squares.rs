@@ -0,0 +1,392 @@
+use crate::number_theory::euclidean::{self, EuclideanDomain};
+use crate::number_theory::factorization;
+use crate::number_theory::sqrt_mod;
+use crate::utilities::nt_error;
+use crate::utilities::numeric_trait::Numeric;
+use crate::utilities::utilities;
+
+// Cornacchia's algorithm for x^2 + d y^2 = m given a square root r of -d mod m.  Runs the
+// Euclidean algorithm on (m, r) until the remainder drops below sqrt(m) and checks that what is
+// left over is d times a square.
+fn cornacchia_root<T: Numeric>(d: T, m: T, r: T) -> Result<Option<(T, T)>, nt_error::NtError> {
+    let (mut a, mut b) = (m, r);
+    while b.checked_mul(&b).ok_or(nt_error::NtError::Overflow)? > m {
+        let rem = a % b;
+        a = b;
+        b = rem;
+    }
+    let (y2, rem) = (m - b * b).div_rem(&d);
+    if rem != T::zero() || !utilities::is_square(y2) {
+        return Ok(None);
+    }
+    Ok(Some((b, utilities::isqrt(y2)?)))
+}
+
+/**
+Returns the primitive solutions of x^2 + d y^2 = m
+
+# Arguments
+
+* `d` - Positive coefficient
+* `m` - Positive number to represent
+
+# Returns
+* All (x, y) with x^2 + d y^2 = m, x, y >= 0 and gcd(x, y) = 1 in increasing order.  Every
+  primitive solution has x = r y (mod m) for a square root r of -d mod m and changing the sign of
+  y swaps r and -r so Cornacchia's algorithm is run on each root from sqrt_mod::sqrt_mod with
+  r <= m / 2.  Empty if there are none.  BadArgument if d or m isn't positive and Overflow if
+  m^2 doesn't fit in T.
+
+# Examples
+
+```
+use number_theory::number_theory::squares;
+
+// 1 + 2 * 36 = 49 + 2 * 12 = 73 - the root 3 of -2 mod 73 gives nothing
+let sols = squares::cornacchia(2, 73)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(sols, vec![(1, 6)]);
+let sols = squares::cornacchia(3, 28)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(sols, vec![(1, 3), (5, 1)]);
+```
+*/
+pub fn cornacchia<T: Numeric>(d: T, m: T) -> Result<Vec<(T, T)>, nt_error::NtError> {
+    if d <= T::zero() || m <= T::zero() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let roots = match sqrt_mod::sqrt_mod(-d, m) {
+        Ok(roots) => roots,
+        Err(nt_error::NtError::NoSolns) => vec![],
+        Err(e) => return Err(e),
+    };
+    let mut ret = Vec::new();
+    for r in roots {
+        // r and m - r give the same solutions
+        if r > m - r {
+            continue;
+        }
+        if let Some((x, y)) = cornacchia_root(d, m, r)? {
+            if euclidean::gcd(x, y) == T::one() {
+                ret.push((x, y));
+                // Multiplying x + yi by i swaps them without changing the root
+                if d == T::one() {
+                    ret.push((y, x));
+                }
+            }
+        }
+    }
+    // The reduction stops at x = 0 for m = 1 so add the other solution with y = 0
+    if m == T::one() {
+        ret.push((T::one(), T::zero()));
+    }
+    ret.sort();
+    ret.dedup();
+    Ok(ret)
+}
+
+// (x, y) with x >= y
+fn ordered<T: Numeric>((x, y): (T, T)) -> (T, T) {
+    if x >= y {
+        (x, y)
+    } else {
+        (y, x)
+    }
+}
+
+/**
+Returns whether n is a sum of two squares
+
+# Arguments
+
+* `n` - Number to test
+
+# Returns
+* True exactly when every prime p = 3 (mod 4) divides n to an even power - n is factored by trial
+  division.  Negative numbers are never sums of two squares.
+
+# Examples
+
+```
+use number_theory::number_theory::squares;
+
+assert_eq!(squares::is_sum_of_two_squares(45), Ok(true));
+assert_eq!(squares::is_sum_of_two_squares(21), Ok(false));
+```
+*/
+pub fn is_sum_of_two_squares<T: Numeric>(n: T) -> Result<bool, nt_error::NtError> {
+    if n <= T::zero() {
+        return Ok(n == T::zero());
+    }
+    let three = T::from_u8(3).unwrap();
+    let four = T::from_u8(4).unwrap();
+    Ok(factorization::factor(n)?
+        .iter()
+        .all(|&(p, exp)| p % four != three || exp % 2 == 0))
+}
+
+/**
+Returns every representation of n as a sum of two squares
+
+# Arguments
+
+* `n` - Number to represent
+
+# Returns
+* All (x, y) with x^2 + y^2 = n and x >= y >= 0 in increasing order of y.  For each square g^2
+  dividing n the primitive representations of n / g^2 come from cornacchia with d = 1.  Empty
+  if there are none.  Overflow if n^2 doesn't fit in T.
+
+# Examples
+
+```
+use number_theory::number_theory::squares;
+
+let reps = squares::sum_of_two_squares(325)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(reps, vec![(18, 1), (17, 6), (15, 10)]);
+```
+*/
+pub fn sum_of_two_squares<T: Numeric>(n: T) -> Result<Vec<(T, T)>, nt_error::NtError> {
+    if n <= T::zero() {
+        return Ok(if n == T::zero() {
+            vec![(T::zero(), T::zero())]
+        } else {
+            vec![]
+        });
+    }
+    if !is_sum_of_two_squares(n)? {
+        return Ok(vec![]);
+    }
+    let mut ret = Vec::new();
+    let mut g = T::one();
+    let g_max = utilities::isqrt(n)?;
+    while g <= g_max {
+        let (m, rem) = n.div_rem(&(g * g));
+        if rem == T::zero() {
+            for (x, y) in cornacchia(T::one(), m)? {
+                ret.push(ordered((g * x, g * y)));
+            }
+        }
+        g += T::one();
+    }
+    ret.sort_by_key(|&(_, y)| y);
+    ret.dedup();
+    Ok(ret)
+}
+
+/**
+Returns a representation of n as a sum of three squares
+
+# Arguments
+
+* `n` - Nonnegative number to represent
+
+# Returns
+* (x, y, z) with x^2 + y^2 + z^2 = n.  By Legendre's theorem these exist unless n has the form
+  4^a (8b + 7) in which case NoSolns is returned.  Factors of 4 are taken out and then z is
+  tried downward from sqrt(n) until n - z^2 is a sum of two squares.  BadArgument if n is
+  negative.
+
+# Examples
+
+```
+use number_theory::number_theory::squares;
+
+let (x, y, z) = squares::sum_of_three_squares(1000)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(x * x + y * y + z * z, 1000);
+assert!(squares::sum_of_three_squares(28).is_err());
+```
+*/
+pub fn sum_of_three_squares<T: Numeric>(n: T) -> Result<(T, T, T), nt_error::NtError> {
+    if n < T::zero() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    if n == T::zero() {
+        return Ok((T::zero(), T::zero(), T::zero()));
+    }
+    let (m, scale) = remove_fours(n);
+    if m % T::from_u8(8).unwrap() == T::from_u8(7).unwrap() {
+        return Err(nt_error::NtError::NoSolns);
+    }
+    let mut z = utilities::isqrt(m)?;
+    loop {
+        let rest = m - z * z;
+        if is_sum_of_two_squares(rest)? {
+            let (x, y) = sum_of_two_squares(rest)?[0];
+            return Ok((x * scale, y * scale, z * scale));
+        }
+        z = z - T::one();
+    }
+}
+
+// (m, 2^a) with n = 4^a m and 4 not dividing m
+fn remove_fours<T: Numeric>(n: T) -> (T, T) {
+    let four = T::from_u8(4).unwrap();
+    let (mut m, mut scale) = (n, T::one());
+    while m != T::zero() && m % four == T::zero() {
+        m = m / four;
+        scale = scale + scale;
+    }
+    (m, scale)
+}
+
+/**
+Returns a representation of n as a sum of four squares
+
+# Arguments
+
+* `n` - Nonnegative number to represent
+
+# Returns
+* (w, x, y, z) with w^2 + x^2 + y^2 + z^2 = n which always exists by Lagrange's theorem.  Follows
+  Rabin and Shallit: after taking out factors of 4, x and y are chosen so that p = n - x^2 - y^2
+  is a prime = 1 (mod 4) (by factorization::is_prime) and p is split with a single modular
+  square root and Cornacchia's algorithm.  The search is deterministic, trying large x and y
+  first.  BadArgument if n is negative.
+
+# Examples
+
+```
+use number_theory::number_theory::squares;
+
+let (w, x, y, z) = squares::sum_of_four_squares(123456789i64)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(w * w + x * x + y * y + z * z, 123456789);
+```
+*/
+pub fn sum_of_four_squares<T: Numeric>(n: T) -> Result<(T, T, T, T), nt_error::NtError> {
+    if n < T::zero() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    let zero = T::zero();
+    let one = T::one();
+    let two = T::from_u8(2).unwrap();
+    let four = T::from_u8(4).unwrap();
+    let (m, scale) = remove_fours(n);
+    let scaled = |w: T, x: T, y: T, z: T| Ok((w * scale, x * scale, y * scale, z * scale));
+
+    let mut x = utilities::isqrt(m)?;
+    while x >= zero {
+        let rest_x = m - x * x;
+        let mut y = utilities::isqrt(rest_x)?;
+        while y >= zero {
+            let p = rest_x - y * y;
+            if p <= two {
+                // 0 = 0 + 0, 1 = 1 + 0 and 2 = 1 + 1
+                let (a, b) = if p == two { (one, one) } else { (p, zero) };
+                return scaled(x, y, a, b);
+            }
+            if p % four == one && factorization::is_prime(p)? {
+                let r = sqrt_mod::sqrt_mod_prime(-one, p)?;
+                if let Some((a, b)) = cornacchia_root(one, p, r)? {
+                    return scaled(x, y, a, b);
+                }
+            }
+            y = y - one;
+        }
+        x = x - one;
+    }
+    // Lagrange doesn't promise a prime turns up so fall back on three squares.  m isn't 0 (mod 4)
+    // so m or m - 1 avoids the form 8b + 7.
+    let w = if m % T::from_u8(8).unwrap() == T::from_u8(7).unwrap() {
+        one
+    } else {
+        zero
+    };
+    let (x, y, z) = sum_of_three_squares(m - w)?;
+    scaled(w, x, y, z)
+}
+
+/**
+Returns r2(n) - the number of (x, y) with x^2 + y^2 = n counting signs and order
+
+# Arguments
+
+* `n` - Nonnegative number
+
+# Returns
+* 4 times the product of (e + 1) over the primes p = 1 (mod 4) dividing n to the power e, or zero
+  if some prime = 3 (mod 4) divides n to an odd power.  r2(0) = 1.  BadArgument for negative n
+  and Overflow if the count doesn't fit.
+
+# Examples
+
+```
+use number_theory::number_theory::squares;
+
+assert_eq!(squares::r2(25), Ok(12));
+assert_eq!(squares::r2(3), Ok(0));
+```
+*/
+pub fn r2<T: Numeric>(n: T) -> Result<T, nt_error::NtError> {
+    if n < T::zero() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    if n == T::zero() {
+        return Ok(T::one());
+    }
+    let overflow = || nt_error::NtError::Overflow;
+    let four = T::from_u8(4).unwrap();
+    let mut ret = four;
+    for (p, exp) in factorization::factor(n)? {
+        if p % four == T::one() {
+            let count = T::from_u32(exp + 1).ok_or_else(overflow)?;
+            ret = ret.checked_mul(&count).ok_or_else(overflow)?;
+        } else if p % four == T::from_u8(3).unwrap() && exp % 2 == 1 {
+            return Ok(T::zero());
+        }
+    }
+    Ok(ret)
+}
+
+/**
+Returns r4(n) - the number of (w, x, y, z) with w^2 + x^2 + y^2 + z^2 = n counting signs and order
+
+# Arguments
+
+* `n` - Nonnegative number
+
+# Returns
+* By Jacobi's theorem 8 times the sum of the divisors of n not divisible by 4.  r4(0) = 1.
+  BadArgument for negative n and Overflow if the count doesn't fit.
+
+# Examples
+
+```
+use number_theory::number_theory::squares;
+
+assert_eq!(squares::r4(1), Ok(8));
+assert_eq!(squares::r4(4), Ok(24));
+```
+*/
+pub fn r4<T: Numeric>(n: T) -> Result<T, nt_error::NtError> {
+    if n < T::zero() {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    if n == T::zero() {
+        return Ok(T::one());
+    }
+    let overflow = || nt_error::NtError::Overflow;
+    let mut sigma = T::one();
+    for (p, exp) in factorization::factor(n)? {
+        // Divisors not divisible by 4 only take 1 or 2 from the power of two
+        let sum = if p == T::from_u8(2).unwrap() {
+            T::from_u8(3).unwrap()
+        } else {
+            // (p^(e + 1) - 1) / (p - 1) without forming p^(e + 1)
+            let mut sum = T::one();
+            let mut pk = T::one();
+            for _ in 0..exp {
+                pk = pk.checked_mul(&p).ok_or_else(overflow)?;
+                sum = sum.checked_add(&pk).ok_or_else(overflow)?;
+            }
+            sum
+        };
+        sigma = sigma.checked_mul(&sum).ok_or_else(overflow)?;
+    }
+    sigma
+        .checked_mul(&T::from_u8(8).unwrap())
+        .ok_or_else(overflow)
+}
@@ -0,0 +1,84 @@
+use crate::number_theory::euclidean;
+use crate::number_theory::factorization;
+use crate::utilities::nt_error;
+use crate::utilities::numeric_trait::Numeric;
+
+// p^exp or Overflow
+pub(crate) fn prime_power<T: Numeric>(p: T, exp: u32) -> Result<T, nt_error::NtError> {
+    let mut ret = T::one();
+    for _ in 0..exp {
+        ret = ret.checked_mul(&p).ok_or(nt_error::NtError::Overflow)?;
+    }
+    Ok(ret)
+}
+
+/**
+Returns Euler's totient of a number
+
+# Arguments
+
+* `n` - Positive number
+
+# Returns
+* The count of numbers in [1, n] relatively prime to n or BadArgument if n isn't positive
+
+# Examples
+
+```
+use number_theory::number_theory::totient;
+
+let phi = totient::totient(36)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(phi, 12);
+```
+*/
+pub fn totient<T: Numeric>(n: T) -> Result<T, nt_error::NtError> {
+    let mut ret = T::one();
+    for (p, exp) in factorization::factor(n)? {
+        ret *= prime_power(p, exp - 1)? * (p - T::one());
+    }
+    Ok(ret)
+}
+
+/**
+Returns Carmichael's lambda of a number
+
+# Arguments
+
+* `n` - Positive number
+
+# Returns
+* The exponent of the multiplicative group mod n - the smallest m with a^m = 1 (mod n) for every
+  a relatively prime to n.  BadArgument if n isn't positive.
+
+# Examples
+
+```
+use number_theory::number_theory::totient;
+
+let lambda = totient::carmichael_lambda(36)
+    .unwrap_or_else(|_| panic!("Failed!"));
+assert_eq!(lambda, 6);
+```
+*/
+pub fn carmichael_lambda<T: Numeric>(n: T) -> Result<T, nt_error::NtError> {
+    carmichael_lambda_factored(&factorization::factor(n)?)
+}
+
+// Carmichael's lambda from the factorization of n
+pub(crate) fn carmichael_lambda_factored<T: Numeric>(
+    factors: &[(T, u32)],
+) -> Result<T, nt_error::NtError> {
+    let two = T::one() + T::one();
+    let mut ret = T::one();
+    for &(p, exp) in factors {
+        let lambda_pk = if p == two && exp >= 3 {
+            // The group mod 2^k isn't cyclic for k >= 3
+            prime_power(p, exp - 2)?
+        } else {
+            prime_power(p, exp - 1)? * (p - T::one())
+        };
+        ret = euclidean::lcm(ret, lambda_pk)?;
+    }
+    Ok(ret)
+}
@@ -1,7 +1,47 @@
-use crate::number_theory::euclidean::calc_euclidean_ext;
+use crate::number_theory::continued_fraction::{
+    best_approximation, partial_quotients, quadratic_irrational, Convergents, Semiconvergents,
+};
+use crate::number_theory::elliptic::{
+    MontgomeryCurve, MontgomeryElement, Point, WeierstrassCurve, WeierstrassElement,
+};
 use crate::number_theory::euclidean::solve_diophantine;
 use crate::number_theory::euclidean::solve_linear_congruence;
-use crate::number_theory::power_mod::power;
+use crate::number_theory::euclidean::{
+    batch_inverse_mod, calc_euclidean_ext, gcd, inverse_mod, lcm, EuclideanDomain,
+};
+use crate::number_theory::factorization::{
+    ecm, factor, is_prime, pollard_p_minus_1, primes_up_to, williams_p_plus_1, EcmOptions,
+};
+use crate::number_theory::finite_field::{self, GaloisField};
+use crate::number_theory::gaussian::{self, GaussianInt};
+use crate::number_theory::monoid::{
+    multi_pow_monoid, pow_monoid, pow_monoid_with, Matrix2, ModInt, Monoid, Permutation,
+    PowerMethod, PowerOptions,
+};
+use crate::number_theory::ntt;
+use crate::number_theory::pell::{
+    generalized_pell, negative_pell_fundamental, pell_fundamental, PellSolutions,
+};
+use crate::number_theory::poly::{self, Poly, PolyMod};
+use crate::number_theory::power_mod::{
+    inverse_mod_ct, mul_mod_ct, multi_power, power, power_ct, power_tower, power_with,
+    power_with_exponent,
+};
+use crate::number_theory::quadratic_forms::{class_number, reduced_forms, BinaryQuadraticForm};
+use crate::number_theory::rational::Rational;
+use crate::number_theory::sqrt_mod::{legendre_symbol, sqrt_mod, sqrt_mod_prime};
+use crate::number_theory::squares::{
+    cornacchia, is_sum_of_two_squares, r2, r4, sum_of_four_squares, sum_of_three_squares,
+    sum_of_two_squares,
+};
+use crate::number_theory::totient::{carmichael_lambda, totient};
+use crate::utilities::bit_ops::BitOps;
+use crate::utilities::nt_error::NtError;
+use crate::utilities::u_to_i::UToI;
+use crate::utilities::utilities::{
+    icbrt, iroot, is_square, isqrt, leftmost_one_index, perfect_power, top_bit_mask,
+};
+use num::BigInt;
 
 // Euclidean Extension
 #[test]
@@ -45,4 +85,1968 @@ fn solve_linear_congruence_test() {
 fn power_test() {
     let val = power(2357, 2357, 3599).unwrap_or_else(|_| panic!("Failed!"));
     assert_eq!(3115_i64, val);
+    let val = power(
+        UToI::from(2357u64),
+        UToI::from(2357u64),
+        UToI::from(3599u64),
+    )
+    .unwrap_or_else(|_| panic!("Failed!"));
+    assert_eq!(UToI::from(3115u64), val);
+
+    assert_eq!(power(3, -1, 11), Ok(4));
+    assert_eq!(power(-3, 3, 11), Ok(6));
+    assert_eq!(power(-3, -3, 11), Ok(2));
+    assert_eq!(power(2, i64::MIN, 7), Ok(2));
+    assert_eq!(power(6, -1, 9), Err(NtError::NoSolns));
+    assert_eq!(power(5, 0, 1), Ok(0));
+    assert_eq!(power(5, 3, 0), Err(NtError::BadArgument));
+    assert_eq!(power(5, 3, -7), Err(NtError::BadArgument));
+}
+
+#[test]
+fn bit_ops_test() {
+    assert_eq!(BitOps::bit_length(0i32), Ok(0));
+    assert_eq!(BitOps::bit_length(255u8), Ok(8));
+    assert_eq!(BitOps::bit_length(-1i64), Err(NtError::BadArgument));
+    assert_eq!(BitOps::ilog2(0u32), Err(NtError::BadArgument));
+    assert_eq!(BitOps::ilog2(-8i32), Err(NtError::BadArgument));
+    assert_eq!(BitOps::ilog2(1i128 << 100), Ok(100));
+    assert_eq!(BitOps::ilog10(999_999i64), Ok(5));
+    assert!(BitOps::is_power_of_two(64i16));
+    assert!(!BitOps::is_power_of_two(i16::MIN));
+    assert_eq!(BitOps::trailing_zeros(0u16), 16);
+
+    let n = -UToI::from(40u64);
+    assert_eq!(n.count_ones(), 2);
+    assert_eq!(n.trailing_zeros(), 3);
+    assert_eq!(n.leading_zeros(), 58);
+    assert_eq!(n.bit_length(), Err(NtError::BadArgument));
+    assert_eq!((-n).ilog10(), Ok(1));
+
+    assert_eq!(top_bit_mask(1000), 512);
+    assert_eq!(top_bit_mask(-1000), 0);
+    assert_eq!(leftmost_one_index(0), Err(NtError::BadArgument));
+    assert_eq!(leftmost_one_index(UToI::from(1u128 << 127)), Ok(127));
+}
+
+#[test]
+fn roots_test() {
+    assert_eq!(isqrt(0).unwrap(), 0);
+    assert_eq!(isqrt(99).unwrap(), 9);
+    assert_eq!(isqrt(100).unwrap(), 10);
+    assert_eq!(isqrt(i64::MAX).unwrap(), 3037000499);
+    assert!(isqrt(-4).is_err());
+    assert_eq!(icbrt(-28), -3);
+    assert_eq!(icbrt(-27), -3);
+    assert_eq!(icbrt(i64::MIN), -(1 << 21));
+    assert_eq!(iroot(i64::MIN, 3), Ok(-(1 << 21)));
+    assert_eq!(iroot(i64::MIN, 63), Ok(-2));
+    assert_eq!(iroot(i64::MIN + 1, 63), Ok(-1));
+    assert_eq!(iroot(i128::MIN, 5), Ok(-44275338));
+    assert_eq!(iroot(i128::MAX, 5).unwrap(), 44275338);
+    assert_eq!(iroot(1i64 << 62, 62).unwrap(), 2);
+    assert_eq!(iroot(1i64 << 61, 62).unwrap(), 1);
+    assert!(iroot(16, 0).is_err());
+
+    let squares: Vec<i64> = (0..1000).filter(|&n| is_square(n)).collect();
+    assert_eq!(squares, (0..32).map(|n| n * n).collect::<Vec<i64>>());
+    assert!(is_square(3037000499i64 * 3037000499));
+    assert!(!is_square(3037000499i64 * 3037000499 - 1));
+
+    assert_eq!(perfect_power(1024), Some((2, 10)));
+    assert_eq!(perfect_power(36), Some((6, 2)));
+    assert_eq!(perfect_power(-64), Some((-4, 3)));
+    assert_eq!(perfect_power(-16), None);
+    assert_eq!(perfect_power(3i128.pow(79)), Some((3, 79)));
+    assert_eq!(perfect_power(1000001), None);
+    assert_eq!(perfect_power(i64::MIN), Some((-2, 63)));
+    assert_eq!(perfect_power(i32::MIN + 1), None);
+    assert_eq!(perfect_power(-(3i64.pow(39))), Some((-3, 39)));
+    assert_eq!(perfect_power(-1i64), None);
+}
+
+#[test]
+fn monoid_test() {
+    let fib = Matrix2::new([[1i64, 1], [1, 0]]);
+    let fib_91 = pow_monoid(&fib, 91).unwrap_or_else(|_| panic!("Failed!"));
+    assert_eq!(fib_91.entries()[0][1], 4660046610375530309);
+    assert_eq!(pow_monoid(&fib, 92).err(), Some(NtError::Overflow));
+    assert_eq!(pow_monoid(&fib, -1).err(), Some(NtError::BadArgument));
+
+    // Pisano period of 10 is 60
+    let fib_mod = Matrix2::with_modulus([[1i64, 1], [1, 0]], 10).unwrap();
+    assert_eq!(pow_monoid(&fib_mod, 60).unwrap(), fib_mod.identity());
+
+    let perm = Permutation::new(vec![1, 2, 0, 4, 3]).unwrap();
+    assert_eq!(pow_monoid(&perm, 6).unwrap(), perm.identity());
+    assert_eq!(pow_monoid(&perm, 5).unwrap().images(), &[2, 0, 1, 4, 3]);
+    assert!(Permutation::new(vec![0, 0]).is_err());
+
+    let val = ModInt::new(-3i64, 11).unwrap();
+    assert_eq!(val.value(), 8);
+    assert_eq!(pow_monoid(&val, 5).unwrap().value(), 10);
+    assert_eq!(
+        val.op(&ModInt::new(1, 7).unwrap()),
+        Err(NtError::BadArgument)
+    );
+}
+
+#[test]
+fn power_methods_test() {
+    let methods = [
+        PowerMethod::Binary,
+        PowerMethod::FixedWindow(1),
+        PowerMethod::FixedWindow(4),
+        PowerMethod::FixedWindow(7),
+        PowerMethod::SlidingWindow(1),
+        PowerMethod::SlidingWindow(3),
+        PowerMethod::SlidingWindow(6),
+        PowerMethod::Naf,
+    ];
+    let modulo: i128 = 4611686018427387847;
+    for method in methods.iter() {
+        let options = PowerOptions { method: *method };
+        for &(x, n) in [
+            (3, 0),
+            (3, 1),
+            (5, 7),
+            (123456789, 1 << 100),
+            (-17, i128::MAX),
+        ]
+        .iter()
+        {
+            assert_eq!(power_with(x, n, modulo, &options), power(x, n, modulo));
+        }
+        assert_eq!(power_with(2, -5, 11, &options), Ok(10));
+        // Exponents wider than T as limbs and as bits
+        let wide: i128 = 0x5a0f_93c1_77e2_0b4d_e8a6_1f3c_9d24_7b61;
+        let limbs: &[u64] = &[wide as u64, (wide >> 64) as u64, 0];
+        assert_eq!(
+            power_with_exponent(1234567, limbs, modulo, &options),
+            power(1234567, wide, modulo)
+        );
+        let bits: Vec<bool> = (0..300).map(|i| i % 3 == 0 || i % 7 == 2).collect();
+        let mut packed = vec![0u64; 5];
+        for (i, &bit) in bits.iter().enumerate() {
+            packed[i / 64] |= (bit as u64) << (i % 64);
+        }
+        assert_eq!(
+            power_with_exponent(-5, bits.as_slice(), modulo, &options),
+            power_with_exponent(-5, packed.as_slice(), modulo, &PowerOptions::default())
+        );
+        // 6 has no inverse mod 9 but positive powers are still fine
+        assert_eq!(power_with(6, 7, 9, &options), Ok(0));
+        let perm = Permutation::new(vec![3, 0, 4, 1, 2]).unwrap();
+        assert_eq!(
+            pow_monoid_with(&perm, 1001, &options),
+            pow_monoid(&perm, 1001)
+        );
+    }
+
+    // Matrices have no inverse so signed digits give way to square and multiply
+    let fib = Matrix2::new([[1i64, 1], [1, 0]]);
+    let naf = PowerOptions {
+        method: PowerMethod::Naf,
+    };
+    assert_eq!(pow_monoid_with(&fib, 7, &naf), pow_monoid(&fib, 7));
+    assert_eq!(pow_monoid_with(&fib, 8, &naf), pow_monoid(&fib, 8));
+    // Other errors from the inverse are passed through rather than hidden by the fallback
+    #[derive(Debug, Clone, PartialEq)]
+    struct Faulty(i64);
+    impl Monoid for Faulty {
+        fn identity(&self) -> Self {
+            Faulty(0)
+        }
+        fn op(&self, other: &Self) -> Result<Self, NtError> {
+            Ok(Faulty(self.0 + other.0))
+        }
+        fn inverse(&self) -> Result<Self, NtError> {
+            Err(NtError::Overflow)
+        }
+    }
+    assert_eq!(pow_monoid_with(&Faulty(2), 5, &naf), Ok(Faulty(10)));
+    assert_eq!(pow_monoid_with(&Faulty(2), 7, &naf), Err(NtError::Overflow));
+    let bad = PowerOptions {
+        method: PowerMethod::SlidingWindow(0),
+    };
+    assert_eq!(power_with(2, 5, 11, &bad), Err(NtError::BadArgument));
+    let options = PowerOptions::default();
+    assert_eq!(
+        power_with_exponent(2, -1, 11, &options),
+        Err(NtError::BadArgument)
+    );
+    assert_eq!(
+        power_with_exponent(2, -1, 1, &options),
+        Err(NtError::BadArgument)
+    );
+    let empty: &[u64] = &[];
+    assert_eq!(power_with_exponent(2, empty, 11, &options), Ok(1));
+    // Fermat with a 2048 bit exponent (p - 1) * 2^1984
+    let p: i64 = 1000003;
+    let mut limbs = vec![0u64; 32];
+    limbs[31] = (p - 1) as u64;
+    assert_eq!(power_with_exponent(7, limbs.as_slice(), p, &options), Ok(1));
+    let g = ModInt::new(2i64, p).unwrap();
+    let h = ModInt::new(3i64, p).unwrap();
+    assert_eq!(
+        multi_pow_monoid(&[(g, limbs.as_slice()), (h, &[200u64][..])]),
+        pow_monoid(&h, 200)
+    );
+}
+
+#[test]
+fn constant_time_test() {
+    let modulo: i64 = 1000000007;
+    for &(x, n) in [(0, 0), (0, 5), (2, 0), (-2, 1000), (123456789, 987654321)].iter() {
+        assert_eq!(power_ct(x, n, modulo), power(x, n, modulo));
+    }
+    assert_eq!(power_ct(5, 3, 1), Ok(0));
+    assert_eq!(power_ct(5, -3, 7), Err(NtError::BadArgument));
+    assert_eq!(power_ct(5, 3, i64::MAX), Err(NtError::Overflow));
+
+    let big: i128 = 170141183460469231731687303715884105727;
+    assert_eq!(mul_mod_ct(big - 1, big - 1, big), Err(NtError::Overflow));
+    let big: i128 = (1 << 126) - 137;
+    assert_eq!(mul_mod_ct(big - 1, -1, big), Ok(1));
+    assert_eq!(
+        mul_mod_ct(1 << 100, 1 << 100, big),
+        Ok(mul_mod_ct(1 << 74, 137, big).unwrap())
+    );
+
+    let inv = inverse_mod_ct(123456789, modulo).unwrap();
+    assert_eq!(inv * 123456789 % modulo, 1);
+    assert_eq!(inverse_mod_ct(2 * modulo, modulo), Err(NtError::NoSolns));
+    // Inputs are reduced without division - check against rem_euclid across the whole range
+    for &m in [2i64, 3, 1000000007, (1 << 62) - 57, i64::MAX / 2].iter() {
+        for &v in [
+            i64::MIN,
+            i64::MIN + 1,
+            -m - 1,
+            -m,
+            -1,
+            0,
+            1,
+            m - 1,
+            m,
+            i64::MAX,
+        ]
+        .iter()
+        {
+            assert_eq!(mul_mod_ct(v, 1, m), Ok(v.rem_euclid(m)));
+            assert_eq!(power_ct(v, 1, m), Ok(v.rem_euclid(m)));
+        }
+    }
+    assert_eq!(
+        mul_mod_ct(i128::MIN, 1, 12345),
+        Ok(i128::MIN.rem_euclid(12345))
+    );
+    // x^0 = 1 mod 2 even for even x
+    assert_eq!(inverse_mod_ct(4, 2), Err(NtError::NoSolns));
+    assert_eq!(inverse_mod_ct(0, 2), Err(NtError::NoSolns));
+    assert_eq!(inverse_mod_ct(-3, 2), Ok(1));
+}
+
+#[test]
+fn power_tower_test() {
+    assert_eq!(factor(1), Ok(vec![]));
+    assert_eq!(factor(1000000007), Ok(vec![(1000000007, 1)]));
+    assert_eq!(
+        factor(i64::MAX),
+        Ok(vec![
+            (7, 2),
+            (73, 1),
+            (127, 1),
+            (337, 1),
+            (92737, 1),
+            (649657, 1)
+        ])
+    );
+    assert_eq!(totient(1), Ok(1));
+    assert_eq!(totient(1000000007), Ok(1000000006));
+    assert_eq!(carmichael_lambda(8), Ok(2));
+    assert_eq!(carmichael_lambda(65520), Ok(12));
+    assert_eq!(carmichael_lambda(0), Err(NtError::BadArgument));
+
+    assert_eq!(power_tower(&[2i64, 3, 4, 5], 1000000007), Ok(7513152));
+    assert_eq!(power_tower(&[2, 3, 4, 5], 1000), Ok(352));
+    assert_eq!(power_tower(&[6i64, 4, 3, 2], 1024 * 81 * 7), Ok(82944));
+    assert_eq!(power_tower(&[12, 2, 2, 2], 96), Ok(0));
+    assert_eq!(power_tower(&[2, 2, 2, 2], 1 << 20), Ok(65536));
+    assert_eq!(power_tower(&[3, 3, 3], 100), Ok(87));
+    // Zero exponents at the top collapse the tower
+    assert_eq!(power_tower(&[7, 5, 0], 1000), Ok(7));
+    assert_eq!(power_tower(&[0, 0, 5], 1000), Ok(1));
+    assert_eq!(power_tower(&[0, 9, 9, 9], 1000), Ok(0));
+    assert_eq!(power_tower(&[1, 9, 9, 9], 1000), Ok(1));
+    assert_eq!(power_tower(&[], 10), Ok(1));
+    assert_eq!(power_tower(&[2, -1], 10), Err(NtError::BadArgument));
+    // 2^(2^(2^2)) mod 8 needs the exponent kept above 3 even though lambda(8) = 2
+    assert_eq!(power_tower(&[2, 2, 2, 2], 8), Ok(0));
+    assert_eq!(power_tower(&[2, 3, 3], 8), Ok(0));
+}
+
+#[test]
+fn multi_power_test() {
+    let modulo: i128 = 4611686018427387847;
+    let pairs: Vec<(i128, i128)> = (0..40)
+        .map(|i| (7919 * i - 100, (i * i * 1234567891) ^ (i << 90)))
+        .collect();
+    // Exercise both Straus (up to 4 bases) and Pippenger
+    for count in 1..pairs.len() {
+        let expected = pairs[..count].iter().fold(1, |acc, &(base, exp)| {
+            acc * power(base, exp, modulo).unwrap() % modulo
+        });
+        assert_eq!(multi_power(&pairs[..count], modulo), Ok(expected));
+    }
+    assert_eq!(multi_power(&[(3, -1), (5, 2), (2, -3)], 11), Ok(7));
+    assert_eq!(multi_power(&[(3, -1), (6, -1)], 9), Err(NtError::NoSolns));
+    assert_eq!(multi_power::<i64>(&[], 7), Ok(1));
+    assert_eq!(multi_power(&[(0, 0), (5, 0)], 7), Ok(1));
+
+    let perm = Permutation::new(vec![1, 2, 0, 4, 3]).unwrap();
+    let pairs: Vec<(Permutation, i64)> = (1..8).map(|i| (perm.clone(), i)).collect();
+    assert_eq!(multi_pow_monoid(&pairs), pow_monoid(&perm, 28));
+    assert_eq!(
+        multi_pow_monoid::<Permutation, i64>(&[]).err(),
+        Some(NtError::BadArgument)
+    );
+
+    // Matrices don't commute so the factors have to stay in order
+    let a = Matrix2::new([[1i64, 1], [0, 1]]);
+    let b = Matrix2::new([[1i64, 0], [1, 1]]);
+    assert_eq!(
+        multi_pow_monoid(&[(a, 2), (b, 3)]),
+        Ok(Matrix2::new([[7, 2], [3, 1]]))
+    );
+    let pairs: Vec<(Matrix2<i64>, i64)> = (0..6)
+        .map(|i| (if i % 2 == 0 { a } else { b }, i + 1))
+        .collect();
+    let expected = pairs.iter().fold(a.identity(), |acc, (m, exp)| {
+        acc.op(&pow_monoid(m, *exp).unwrap()).unwrap()
+    });
+    assert_eq!(multi_pow_monoid(&pairs), Ok(expected));
+}
+
+#[test]
+fn inverse_mod_test() {
+    assert_eq!(inverse_mod(3, 11), Ok(4));
+    assert_eq!(inverse_mod(-3, 11), Ok(7));
+    assert_eq!(inverse_mod(14, 11), Ok(4));
+    assert_eq!(inverse_mod(0, 1), Ok(0));
+    assert_eq!(inverse_mod(6, 9), Err(NtError::NoSolns));
+    assert_eq!(inverse_mod(3, 0), Err(NtError::BadArgument));
+
+    let modulo: i64 = 1000000007;
+    let values: Vec<i64> = (1..200).map(|i| i * 7919 - 50000).collect();
+    let inverses = batch_inverse_mod(&values, modulo).unwrap();
+    for (val, inv) in values.iter().zip(inverses.iter()) {
+        assert_eq!(Ok(*inv), inverse_mod(*val, modulo));
+    }
+    assert_eq!(batch_inverse_mod(&[], modulo), Ok(vec![]));
+    assert_eq!(
+        batch_inverse_mod(&[5, 7, 10, 15], 25),
+        Err(NtError::NonInvertible(0))
+    );
+    assert_eq!(
+        batch_inverse_mod(&[7, 3, 8, 15], 12),
+        Err(NtError::NonInvertible(1))
+    );
+    assert_eq!(batch_inverse_mod(&[7, 11, 13], 1), Ok(vec![0, 0, 0]));
+    assert_eq!(
+        batch_inverse_mod(&[1i64 << 40, 1 << 40], (1 << 50) + 1),
+        Err(NtError::Overflow)
+    );
+}
+
+#[test]
+fn continued_fraction_test() {
+    assert_eq!(partial_quotients(-415, 93), Ok(vec![-5, 1, 1, 6, 7]));
+    assert_eq!(partial_quotients(415, -93), Ok(vec![-5, 1, 1, 6, 7]));
+    assert_eq!(partial_quotients(12, 4), Ok(vec![3]));
+    assert_eq!(partial_quotients(0, 4), Ok(vec![0]));
+    assert_eq!(partial_quotients(3, 0), Err(NtError::BadArgument));
+
+    let quotients = partial_quotients(-415, 93).unwrap();
+    let last = Convergents::new(&quotients).last().unwrap();
+    assert_eq!(last, Ok((-415, 93)));
+    // Fibonacci ratios overflow eventually
+    let ones = vec![1i64; 100];
+    let convergents: Vec<_> = Convergents::new(&ones).collect();
+    assert_eq!(convergents.len(), 92);
+    assert_eq!(convergents[91], Err(NtError::Overflow));
+    let semis: Vec<(i64, i64)> = Semiconvergents::new(&quotients)
+        .map(|c| c.unwrap())
+        .collect();
+    assert_eq!(semis.len(), 1 + 1 + 1 + 6 + 7);
+    assert_eq!(semis.last(), Some(&(-415, 93)));
+
+    // Brute force the best approximations of 1000 / 1023 and -355 / 113
+    for &(p, q) in [(1000i64, 1023i64), (-355, 113), (17, 1)].iter() {
+        for max_den in 1..150 {
+            let (h, k) = best_approximation(p, q, max_den).unwrap();
+            assert!(k > 0 && k <= max_den);
+            for den in 1..=max_den {
+                let num = (p * den + q / 2).div_euclid(q);
+                for cand in [num - 1, num, num + 1].iter() {
+                    // |cand/den - p/q| >= |h/k - p/q|
+                    assert!((cand * q - p * den).abs() * k >= (h * q - p * k).abs() * den);
+                }
+            }
+        }
+    }
+    // The first semiconvergent past a_0 needs (h_{-1}, k_{-1}) = (1, 0)
+    assert_eq!(best_approximation(1, 5, 4), Ok((1, 4)));
+    assert_eq!(best_approximation(-1, 5, 4), Ok((-1, 4)));
+    assert_eq!(best_approximation(1, 3, 0), Err(NtError::BadArgument));
+
+    assert_eq!(quadratic_irrational(0, 2, 1), Ok((vec![1], vec![2])));
+    assert_eq!(quadratic_irrational(0, 61, 1).unwrap().1.len(), 11);
+    // Golden ratio (1 + sqrt(5)) / 2 and its negative
+    assert_eq!(quadratic_irrational(1, 5, 2), Ok((vec![], vec![1])));
+    assert_eq!(quadratic_irrational(-1, 5, -2), Ok((vec![-1, 2], vec![1])));
+    // (3 + sqrt(13)) / 5 where 5 doesn't divide 13 - 9
+    assert_eq!(quadratic_irrational(3, 13, 5), Ok((vec![], vec![1, 3, 8])));
+    assert_eq!(
+        quadratic_irrational(1, 13, 5),
+        Ok((vec![0, 1], vec![11, 1, 2]))
+    );
+    assert_eq!(quadratic_irrational(0, 16, 1), Err(NtError::BadArgument));
+    assert_eq!(quadratic_irrational(0, 7, 0), Err(NtError::BadArgument));
+}
+
+#[test]
+fn pell_test() {
+    // Brute force the fundamental solutions for small d
+    for d in 2i64..60 {
+        if is_square(d) {
+            assert_eq!(pell_fundamental(d), Err(NtError::BadArgument));
+            continue;
+        }
+        let y = (1..).find(|y| is_square(1 + d * y * y)).unwrap();
+        assert_eq!(pell_fundamental(d), Ok((isqrt(1 + d * y * y).unwrap(), y)));
+        let neg = (1..100).find(|y| is_square(d * y * y - 1));
+        let expected = neg.map(|y| (isqrt(d * y * y - 1).unwrap(), y));
+        assert_eq!(negative_pell_fundamental(d).ok(), expected);
+    }
+    assert_eq!(pell_fundamental(0), Err(NtError::BadArgument));
+    assert_eq!(pell_fundamental(991i64), Err(NtError::Overflow));
+    assert_eq!(
+        pell_fundamental(991i128),
+        Ok((
+            379516400906811930638014896080,
+            12055735790331359447442538767
+        ))
+    );
+
+    // Odd powers of the negative solution solve the negative equation
+    let unit = pell_fundamental(29i64).unwrap();
+    let neg = negative_pell_fundamental(29i64).unwrap();
+    let solns: Vec<_> = PellSolutions::new(29, neg, unit).collect();
+    assert_eq!(solns.last(), Some(&Err(NtError::Overflow)));
+    for soln in solns.iter().take(solns.len() - 1) {
+        let (x, y) = soln.unwrap();
+        let (x, y) = (x as i128, y as i128);
+        assert_eq!(x * x - 29 * y * y, -1);
+    }
+
+    for &(d, n) in [
+        (2i64, -7),
+        (5, 4),
+        (7, 2),
+        (3, 5),
+        (13, 27),
+        (10, -9),
+        (6, 1),
+    ]
+    .iter()
+    {
+        let reps = generalized_pell(d, n).unwrap();
+        let unit = pell_fundamental(d).unwrap();
+        for &rep in reps.iter() {
+            for soln in PellSolutions::new(d, rep, unit).take(3) {
+                let (x, y) = soln.unwrap();
+                let (x, y) = (x as i128, y as i128);
+                assert_eq!(x * x - d as i128 * y * y, n as i128);
+            }
+        }
+        // Every small solution is +/- a member of the class of a representative
+        for y in 0..2000 {
+            if is_square(n + d * y * y) {
+                let x = isqrt(n + d * y * y).unwrap();
+                for &(x, y) in [(x, y), (-x, y), (x, -y), (-x, -y)].iter() {
+                    assert!(reps.iter().any(|&(rx, ry)| {
+                        (x * rx - d * y * ry) % n == 0 && (x * ry - y * rx) % n == 0
+                    }));
+                }
+            }
+        }
+    }
+    assert_eq!(generalized_pell(3, 5), Ok(vec![]));
+    assert_eq!(generalized_pell(3i64, i64::MAX - 1), Err(NtError::Overflow));
+    assert_eq!(generalized_pell(3i64, i64::MIN), Err(NtError::Overflow));
+
+    // Big integers never overflow
+    let big = |n: i64| BigInt::from(n);
+    let (x, y) = pell_fundamental(big(991)).unwrap();
+    assert_eq!(
+        (x.to_string(), y.to_string()),
+        (
+            "379516400906811930638014896080".to_string(),
+            "12055735790331359447442538767".to_string()
+        )
+    );
+    for &d in [1000099i64, 9999991, 123456789].iter() {
+        let (x, y) = pell_fundamental(big(d)).unwrap();
+        assert_eq!(&x * &x - big(d) * &y * &y, big(1));
+    }
+    let (x, y) = negative_pell_fundamental(big(1000037)).unwrap();
+    assert_eq!(&x * &x - big(1000037) * &y * &y, big(-1));
+    let unit = pell_fundamental(big(29)).unwrap();
+    let neg = negative_pell_fundamental(big(29)).unwrap();
+    for soln in PellSolutions::new(big(29), neg, unit).take(20).step_by(2) {
+        let (x, y) = soln.unwrap();
+        assert_eq!(&x * &x - big(29) * &y * &y, big(-1));
+    }
+    let mut reps = generalized_pell(big(13), big(27)).unwrap();
+    reps.sort();
+    assert_eq!(
+        reps,
+        vec![
+            (big(-40), big(11)),
+            (big(-12), big(3)),
+            (big(12), big(3)),
+            (big(40), big(11))
+        ]
+    );
+}
+
+#[test]
+fn rational_test() {
+    let r = |n: i64, d: i64| Rational::new(n, d).unwrap();
+    assert_eq!(r(-6, -4), r(3, 2));
+    assert_eq!((r(0, -5).numer(), r(0, -5).denom()), (0, 1));
+    assert_eq!(Rational::new(1, 0), Err(NtError::BadArgument));
+    assert_eq!(Rational::new(1, i64::MIN), Err(NtError::Overflow));
+    assert_eq!(Rational::new(i64::MIN, -1), Err(NtError::Overflow));
+    // No sign change is needed or the reduced values can be negated
+    let parts = |q: Rational<i64>| (q.numer(), q.denom());
+    assert_eq!(parts(r(i64::MIN, 2)), (-(1 << 62), 1));
+    assert_eq!(parts(r(i64::MIN, -2)), (1 << 62, 1));
+    assert_eq!(parts(r(2, i64::MIN)), (-1, 1 << 62));
+    assert_eq!(parts(r(i64::MIN, i64::MIN)), (1, 1));
+    assert_eq!(parts(r(0, i64::MIN)), (0, 1));
+    assert_eq!(parts(r(i64::MIN, 1)), (i64::MIN, 1));
+
+    assert_eq!(r(1, 6).checked_add(&r(1, 3)), Ok(r(1, 2)));
+    assert_eq!(r(1, 6).checked_sub(&r(1, 6)), Ok(r(0, 1)));
+    assert_eq!(r(-3, 4).checked_mul(&r(8, 9)), Ok(r(-2, 3)));
+    assert_eq!(r(3, 4).checked_div(&r(-3, 8)), Ok(r(-2, 1)));
+    assert_eq!(r(3, 4).checked_div(&r(0, 1)), Err(NtError::BadArgument));
+    assert_eq!(r(0, 1).recip(), Err(NtError::BadArgument));
+    // Cross reduction keeps these in range even though the naive products overflow
+    let big = i64::MAX / 3;
+    assert_eq!(r(big, 7).checked_mul(&r(7, big)), Ok(r(1, 1)));
+    assert_eq!(r(1, big).checked_add(&r(1, big)), Ok(r(2, big)));
+    assert_eq!(r(i64::MAX, 1).checked_add(&r(1, 1)), Err(NtError::Overflow));
+
+    // Comparison works where cross multiplying would overflow
+    let a = r(i64::MAX - 1, i64::MAX - 2);
+    let b = r(i64::MAX - 2, i64::MAX - 3);
+    assert!(a < b);
+    assert!(r(-1, 2) < r(-1, 3));
+    assert!(r(5, 1) > r(9, 2));
+    assert_eq!(r(7, 3).cmp(&r(7, 3)), std::cmp::Ordering::Equal);
+    for n1 in -12i64..12 {
+        for d1 in 1..8 {
+            for n2 in -12i64..12 {
+                for d2 in 1..8 {
+                    assert_eq!(r(n1, d1).cmp(&r(n2, d2)), (n1 * d2).cmp(&(n2 * d1)));
+                }
+            }
+        }
+    }
+
+    assert_eq!("22/7".parse(), Ok(r(22, 7)));
+    assert_eq!("-0.125".parse(), Ok(r(-1, 8)));
+    assert_eq!("+.5".parse(), Ok(r(1, 2)));
+    assert_eq!("1.5/0.25".parse(), Ok(r(6, 1)));
+    assert_eq!("3.".parse(), Ok(r(3, 1)));
+    assert_eq!("1/0".parse::<Rational<i64>>(), Err(NtError::BadArgument));
+    assert_eq!("1/x".parse::<Rational<i64>>(), Err(NtError::BadArgument));
+    assert_eq!(".".parse::<Rational<i64>>(), Err(NtError::BadArgument));
+    assert_eq!(
+        "99999999999".parse::<Rational<i32>>(),
+        Err(NtError::Overflow)
+    );
+    // T's minimum value is representable so it parses
+    assert_eq!(
+        "-9223372036854775808".parse(),
+        Ok(Rational::from_integer(i64::MIN))
+    );
+    assert_eq!(
+        "9223372036854775808".parse::<Rational<i64>>(),
+        Err(NtError::Overflow)
+    );
+    assert_eq!(
+        "-214748364.8".parse(),
+        Ok(Rational::new(i32::MIN, 10).unwrap())
+    );
+    assert_eq!("-0.5".parse(), Ok(r(-1, 2)));
+    assert_eq!(r(-7, 2).to_string(), "-7/2");
+    assert_eq!(r(4, 2).to_string(), "2");
+
+    assert_eq!(Rational::from_f64(0.75), Ok(r(3, 4)));
+    assert_eq!(Rational::from_f64(-1024.0), Ok(r(-1024, 1)));
+    assert_eq!(
+        Rational::from_f64(0.1),
+        Ok(r(3602879701896397, 36028797018963968))
+    );
+    assert_eq!(Rational::<i32>::from_f64(0.1), Err(NtError::Overflow));
+    assert_eq!(
+        Rational::<i64>::from_f64(f64::NAN),
+        Err(NtError::BadArgument)
+    );
+    assert_eq!(Rational::from_f64_approx(0.1, 100), Ok(r(1, 10)));
+    assert_eq!(
+        Rational::<i32>::from_f64_approx(0.1, 100),
+        Ok(Rational::new(1, 10).unwrap())
+    );
+    assert_eq!(
+        Rational::from_f64_approx(-2.0f64.sqrt(), 100),
+        Ok(r(-140, 99))
+    );
+    assert_eq!(r(355, 113).to_f64(), 355.0 / 113.0);
+    assert_eq!(r(-1, 3).limit_denominator(2), Ok(r(-1, 2)));
+    assert_eq!(r(-68, 3).limit_denominator(2), Ok(r(-45, 2)));
+}
+
+#[test]
+fn sqrt_mod_test() {
+    for &p in [3i64, 5, 7, 13, 17, 41, 97, 257, 65537, 1000000009].iter() {
+        let squares: Vec<i64> = (0..p.min(300)).collect();
+        for &a in squares.iter() {
+            let expected = if a == 0 {
+                0
+            } else if (1..p.min(300)).any(|x| x * x % p == a) || p > 300 {
+                // Only small p can be checked by brute force
+                legendre_symbol(a, p).unwrap()
+            } else {
+                -1
+            };
+            assert_eq!(legendre_symbol(a, p), Ok(expected));
+            match sqrt_mod_prime(a, p) {
+                Ok(x) => assert_eq!(x * x % p, a),
+                Err(e) => {
+                    assert_eq!(e, NtError::NoSolns);
+                    assert_eq!(expected, -1);
+                }
+            }
+        }
+    }
+    // 2^16 + 1 has p - 1 = 2^16 so Tonelli-Shanks does all its rounds
+    let x = sqrt_mod_prime(-1i64, 65537).unwrap();
+    assert_eq!(x * x % 65537, 65536);
+    assert_eq!(sqrt_mod_prime(3, 2), Ok(1));
+    assert_eq!(sqrt_mod_prime(3, 1), Err(NtError::BadArgument));
+    assert_eq!(legendre_symbol(3, 2), Err(NtError::BadArgument));
+}
+
+#[test]
+fn gaussian_test() {
+    let g = |re: i64, im: i64| GaussianInt::new(re, im);
+    // Integer gcds are unchanged by going through EuclideanDomain
+    assert_eq!(gcd(-12, 18), 6);
+    assert_eq!(gcd(0, -5), 5);
+    assert_eq!(calc_euclidean_ext(-12, 18).0, 6);
+
+    for a in -6i64..7 {
+        for b in -6i64..7 {
+            let x = g(a, b);
+            let y = g(3, -4);
+            if !y.is_zero() {
+                let (q, r) = x.div_rem(&y);
+                assert_eq!(q * y + r, x);
+                assert!(2 * r.norm().unwrap() <= y.norm().unwrap());
+            }
+            let (d, c1, c2) = calc_euclidean_ext(x, g(4, 2));
+            assert_eq!(c1 * x + c2 * g(4, 2), d);
+            assert_eq!(d, gcd(x, g(4, 2)));
+            assert!(d.is_zero() || (d.re() > 0 && d.im() >= 0));
+        }
+    }
+    // 5 = (2 + i)(2 - i) and 3 + i = (1 + i)(2 - i) with 2 - i normalized to i (2 - i)
+    assert_eq!(gcd(g(5, 0), g(3, 1)), g(1, 2));
+    assert_eq!(gcd(g(0, 0), g(0, -3)), g(3, 0));
+
+    for re in -30i64..31 {
+        for im in -30i64..31 {
+            let z = g(re, im);
+            if z.is_zero() {
+                assert_eq!(gaussian::factor(z), Err(NtError::BadArgument));
+                continue;
+            }
+            let (unit, factors) = gaussian::factor(z).unwrap();
+            assert!(unit.is_unit());
+            let mut prod = unit;
+            for &(prime, exp) in factors.iter() {
+                assert!(prime.re() > 0 && prime.im() >= 0 && exp > 0);
+                // Gaussian primes have prime norm or are rational primes 3 mod 4
+                let n = prime.norm().unwrap();
+                let is_prime = |n: i64| n > 1 && (2..n).all(|d| n % d != 0);
+                assert!(
+                    is_prime(n) || (prime.im() == 0 && prime.re() % 4 == 3 && is_prime(prime.re()))
+                );
+                for _ in 0..exp {
+                    prod = prod * prime;
+                }
+            }
+            assert_eq!(prod, z);
+        }
+    }
+    assert_eq!(
+        gaussian::factor(g(-2, 0)),
+        Ok((g(0, 1), vec![(g(1, 1), 2)]))
+    );
+    assert_eq!(
+        gaussian::factor(g(10, 1)),
+        Ok((g(1, 0), vec![(g(10, 1), 1)]))
+    );
+    assert_eq!(gaussian::factor(g(i64::MAX, 1)), Err(NtError::Overflow));
+    assert_eq!(pow_monoid(&g(1, 1), 8i64), Ok(g(16, 0)));
+}
+
+#[test]
+fn euclidean_domain_test() {
+    // Integers
+    assert_eq!(lcm(4, 6), Ok(12));
+    assert_eq!(lcm(-4, 6), Ok(12));
+    assert_eq!(lcm(0, 6), Ok(0));
+    assert_eq!(lcm(i64::MAX, i64::MAX - 1), Err(NtError::Overflow));
+    assert_eq!(lcm(1i64 << 62, 1 << 61), Ok(1 << 62));
+    assert_eq!((-7).div_rem(&3), (-3, 2));
+    assert_eq!((-7).div_rem(&-3), (3, 2));
+    assert_eq!(inverse_mod(3, -11), Ok(4));
+    assert!(solve_diophantine(0, 0, 3).is_err());
+    for &(a, b, c) in [
+        (-6, 10, 4),
+        (6, -10, -8),
+        (0, 5, 15),
+        (12, 0, 36),
+        (-3, -5, 7),
+    ]
+    .iter()
+    {
+        let (fn_solve, gcd) = solve_diophantine(a, b, c).unwrap();
+        assert!(gcd > 0);
+        for i in -3..4 {
+            let (x, y) = fn_solve(i);
+            assert_eq!(a * x + b * y, c);
+        }
+    }
+
+    // Gaussian integers
+    let g = |re: i64, im: i64| GaussianInt::new(re, im);
+    assert_eq!(lcm(g(2, 0), g(1, 1)), Ok(g(2, 0)));
+    let inv = inverse_mod(g(2, 1), g(7, 0)).unwrap();
+    assert_eq!((inv * g(2, 1)).div_rem(&g(7, 0)).1.norm(), Ok(1));
+    let (fn_solve, d) = solve_diophantine(g(3, 1), g(5, 0), g(5, 5)).unwrap();
+    assert_eq!(d, g(1, 2));
+    for i in -2..3 {
+        let (x, y) = fn_solve(i);
+        assert_eq!(x * g(3, 1) + y * g(5, 0), g(5, 5));
+    }
+
+    // Polynomials over F_7
+    let poly = |coeffs: &[i64]| Poly::new(coeffs.to_vec(), 7).unwrap();
+    assert_eq!(poly(&[7, -1, 0, 0]).coeffs(), &[0, 6]);
+    assert_eq!(poly(&[]).degree(), None);
+    assert_eq!(Poly::new(vec![1], 1), Err(NtError::BadArgument));
+    assert_eq!(Poly::new(vec![1], i64::MAX), Err(NtError::Overflow));
+    // Composite moduli would leave leading coefficients without inverses
+    assert_eq!(Poly::new(vec![1, 0, 2], 4), Err(NtError::BadArgument));
+    assert_eq!(
+        Poly::new(vec![1], 1000000007i64 * 3),
+        Err(NtError::BadArgument)
+    );
+    assert_eq!(poly(&[1, 2, 3]).eval(2), (1 + 4 + 12) % 7);
+    // Mismatched coefficient moduli are errors rather than panics where a Result is returned
+    let other = Poly::new(vec![0, 1], 5).unwrap();
+    assert_eq!(poly(&[0, 1]).pow_mod(3, &other), Err(NtError::BadArgument));
+    assert_eq!(
+        poly(&[0, 1]).compose_mod(&poly(&[1, 1]), &other),
+        Err(NtError::BadArgument)
+    );
+    assert_eq!(
+        poly(&[0, 1]).compose_mod(&other, &poly(&[1, 1])),
+        Err(NtError::BadArgument)
+    );
+    assert_eq!(poly(&[0, 1]).mul_ntt(&other), Err(NtError::BadArgument));
+    assert_eq!(
+        PolyMod::new(poly(&[0, 1]), other),
+        Err(NtError::BadArgument)
+    );
+
+    // (x + 1)(x + 2) and (x + 1)(x + 3) share the monic gcd x + 1
+    let a = poly(&[1, 1]) * poly(&[2, 1]);
+    let b = poly(&[1, 1]) * poly(&[3, 1]) * poly(&[5]);
+    assert_eq!(gcd(a.clone(), b.clone()), poly(&[1, 1]));
+    let (d, c1, c2) = calc_euclidean_ext(a.clone(), b.clone());
+    assert_eq!(d, poly(&[1, 1]));
+    assert_eq!(c1 * a.clone() + c2 * b.clone(), d);
+    assert_eq!(lcm(a.clone(), b.clone()), Ok(poly(&[6, 11, 6, 1])));
+    let (q, r) = poly(&[3, 0, 2, 5]).div_rem(&poly(&[1, 2]));
+    assert_eq!(q * poly(&[1, 2]) + r.clone(), poly(&[3, 0, 2, 5]));
+    assert_eq!(r.degree(), Some(0));
+
+    // A cubic without roots is irreducible so every nonzero quadratic is invertible modulo it
+    let irreducible = poly(&[3, 0, 0, 1]);
+    assert!((0..7).all(|x| irreducible.eval(x) != 0));
+    for c0 in 0..7 {
+        for c1 in 0..7 {
+            let x = poly(&[c0, c1, 1]);
+            let inv = inverse_mod(x.clone(), irreducible.clone()).unwrap();
+            assert_eq!((inv * x).div_rem(&irreducible).1, poly(&[1]));
+        }
+    }
+    assert_eq!(inverse_mod(poly(&[1, 1]), a.clone()), Err(NtError::NoSolns));
+    let (fn_solve, d) = solve_diophantine(a.clone(), b.clone(), poly(&[2, 2])).unwrap();
+    assert_eq!(d, poly(&[1, 1]));
+    for i in 0..3 {
+        let (x, y) = fn_solve(i);
+        assert_eq!(x * a.clone() + y * b.clone(), poly(&[2, 2]));
+    }
+}
+
+#[test]
+fn squares_test() {
+    let brute_prime = |n: i64| n > 1 && (2..).take_while(|d| d * d <= n).all(|d| n % d != 0);
+    for n in -5i64..2000 {
+        assert_eq!(is_prime(n), Ok(brute_prime(n)));
+    }
+    // Strong pseudoprimes to several small bases
+    for &n in [
+        2047i64,
+        1373653,
+        25326001,
+        3215031751,
+        2152302898747,
+        3474749660383,
+    ]
+    .iter()
+    {
+        assert_eq!(is_prime(n), Ok(false));
+    }
+    assert_eq!(is_prime(9223372036854775783i64), Err(NtError::Overflow));
+    assert_eq!(is_prime(4611686018427387847i64), Ok(true));
+    assert_eq!(is_prime((1i128 << 89) - 1), Ok(true));
+    assert_eq!(is_prime(UToI::from(1000000007u64)), Ok(true));
+
+    for n in 1i64..400 {
+        let roots: Vec<i64> = (0..n).filter(|x| (x * x + 1) % n == 0).collect();
+        match sqrt_mod(-1, n) {
+            Ok(found) => assert_eq!(found, roots),
+            Err(e) => {
+                assert_eq!(e, NtError::NoSolns);
+                assert!(roots.is_empty());
+            }
+        }
+        let roots: Vec<i64> = (0..n).filter(|x| (x * x) % n == 9 % n).collect();
+        assert_eq!(sqrt_mod(9, n), Ok(roots));
+    }
+    // Roots sharing factors with the modulus
+    for n in 1i64..130 {
+        for a in -3..n {
+            let roots: Vec<i64> = (0..n).filter(|x| (x * x - a).rem_euclid(n) == 0).collect();
+            match sqrt_mod(a, n) {
+                Ok(found) => assert_eq!(found, roots),
+                Err(e) => {
+                    assert_eq!(e, NtError::NoSolns);
+                    assert!(roots.is_empty());
+                }
+            }
+        }
+    }
+    assert_eq!(sqrt_mod(6, 9), Err(NtError::NoSolns));
+    assert_eq!(sqrt_mod(0, 1i64 << 20).map(|r| r.len()), Ok(1024));
+    assert_eq!(sqrt_mod(1, 0), Err(NtError::BadArgument));
+    assert_eq!(sqrt_mod(17, 1i64 << 20).map(|r| r.len()), Ok(4));
+    // Near T::MAX the search reports Overflow rather than overflowing g^2
+    assert_eq!(sum_of_two_squares(1i64 << 62), Err(NtError::Overflow));
+    assert_eq!(sum_of_two_squares(32761i16), Err(NtError::Overflow));
+
+    for n in -3i64..600 {
+        let mut reps = Vec::new();
+        let mut count = 0;
+        for y in 0..30 {
+            for x in y..30 {
+                if x * x + y * y == n {
+                    reps.push((x, y));
+                }
+            }
+            for x in -30..30 {
+                if x * x + y * y == n {
+                    count += if y == 0 { 1 } else { 2 };
+                }
+            }
+        }
+        assert_eq!(is_sum_of_two_squares(n), Ok(!reps.is_empty()));
+        assert_eq!(sum_of_two_squares(n), Ok(reps));
+        if n >= 0 {
+            assert_eq!(r2(n), Ok(count));
+            let bound = isqrt(n).unwrap() + 1;
+            let count4 = (-bound..bound)
+                .flat_map(|w| (-bound..bound).map(move |x| (w, x)))
+                .map(|(w, x)| {
+                    let rest = n - w * w - x * x;
+                    (-bound..bound)
+                        .filter(|y| {
+                            let z2 = rest - y * y;
+                            z2 >= 0 && is_square(z2)
+                        })
+                        .map(|y| if rest - y * y == 0 { 1 } else { 2 })
+                        .sum::<i64>()
+                })
+                .sum::<i64>();
+            assert_eq!(r4(n), Ok(count4));
+
+            let (w, x, y, z) = sum_of_four_squares(n).unwrap();
+            assert_eq!(w * w + x * x + y * y + z * z, n);
+            let mut m = n;
+            while m > 0 && m % 4 == 0 {
+                m /= 4;
+            }
+            match sum_of_three_squares(n) {
+                Ok((x, y, z)) => assert_eq!(x * x + y * y + z * z, n),
+                Err(e) => {
+                    assert_eq!(e, NtError::NoSolns);
+                    assert_eq!(m % 8, 7);
+                }
+            }
+        }
+    }
+    assert_eq!(r2(-1), Err(NtError::BadArgument));
+    assert_eq!(sum_of_four_squares(-1), Err(NtError::BadArgument));
+    let n = 999999999989i64;
+    let (w, x, y, z) = sum_of_four_squares(n).unwrap();
+    assert_eq!(w * w + x * x + y * y + z * z, n);
+    let (x, y, z) = sum_of_three_squares(n).unwrap();
+    assert_eq!(x * x + y * y + z * z, n);
+}
+
+#[test]
+fn quadratic_forms_test() {
+    type Form = BinaryQuadraticForm<i64>;
+    let form = |a: i64, b: i64, c: i64| Form::new(a, b, c);
+    // f(p x + q y, r x + s y) for a substitution of determinant 1
+    let transform = |f: Form, (p, q, r, s): (i64, i64, i64, i64)| {
+        assert_eq!(p * s - q * r, 1);
+        form(
+            f.a * p * p + f.b * p * r + f.c * r * r,
+            2 * f.a * p * q + f.b * (p * s + q * r) + 2 * f.c * r * s,
+            f.a * q * q + f.b * q * s + f.c * s * s,
+        )
+    };
+    let substitutions = [
+        (1, 1, 0, 1),
+        (2, 1, 1, 1),
+        (1, 0, -3, 1),
+        (3, 5, 1, 2),
+        (-2, 7, 1, -4),
+    ];
+
+    for &(d, h) in [
+        (-3, 1),
+        (-4, 1),
+        (-7, 1),
+        (-8, 1),
+        (-12, 1),
+        (-15, 2),
+        (-16, 1),
+        (-20, 2),
+        (-23, 3),
+        (-24, 2),
+        (-28, 1),
+        (-31, 3),
+        (-39, 4),
+        (-47, 5),
+        (-56, 4),
+        (-71, 7),
+        (-84, 4),
+        (-163, 1),
+        (5, 1),
+        (8, 1),
+        (12, 2),
+        (13, 1),
+        (17, 1),
+        (21, 2),
+        (24, 2),
+        (28, 2),
+        (40, 2),
+        (60, 4),
+        (65, 2),
+        (229, 3),
+    ]
+    .iter()
+    {
+        assert_eq!(class_number(d), Ok(h), "{}", d);
+        let forms = reduced_forms(d).unwrap();
+        let principal = Form::principal(d).unwrap();
+        assert!(forms.contains(&principal));
+        for &f in forms.iter() {
+            assert_eq!(f.discriminant(), Ok(d));
+            assert_eq!(f.reduce(), Ok(f));
+            // Equivalent forms reduce into the same class
+            for &sub in substitutions.iter() {
+                let g = transform(f, sub);
+                assert_eq!(g.discriminant(), Ok(d));
+                assert_eq!(f.equivalent(&g), Ok(true));
+                if d < 0 {
+                    assert_eq!(g.reduce(), Ok(f));
+                }
+            }
+            // Group laws
+            assert!(f.compose(&principal).unwrap().equivalent(&f).unwrap());
+            let inv = f.inverse().unwrap();
+            assert!(f.compose(&inv).unwrap().equivalent(&principal).unwrap());
+            assert!(pow_monoid(&f, h as i64)
+                .unwrap()
+                .equivalent(&principal)
+                .unwrap());
+            for &g in forms.iter() {
+                let fg = f.compose(&g).unwrap();
+                assert_eq!(fg.discriminant(), Ok(d));
+                assert!(fg.equivalent(&g.compose(&f).unwrap()).unwrap());
+                for &k in forms.iter().take(3) {
+                    let left = fg.compose(&k).unwrap();
+                    let right = f.compose(&g.compose(&k).unwrap()).unwrap();
+                    assert!(left.equivalent(&right).unwrap());
+                }
+            }
+        }
+        // Different reduced definite forms are never equivalent
+        if d < 0 {
+            for &f in forms.iter() {
+                for &g in forms.iter() {
+                    assert_eq!(f.equivalent(&g), Ok(f == g));
+                }
+            }
+        }
+    }
+
+    // x^2 + 5y^2 and 2x^2 + 2xy + 3y^2 make up the class group of -20
+    let f = form(2, 2, 3);
+    assert_eq!(f.compose(&f), Ok(form(1, 0, 5)));
+    assert_eq!(f.is_reduced(), Ok(true));
+    assert_eq!(form(3, 2, 2).is_reduced(), Ok(false));
+    assert_eq!(form(3, 2, 2).reduce(), Ok(form(2, -2, 3).reduce().unwrap()));
+    assert_eq!(form(-1, 0, -1).reduce(), Err(NtError::BadArgument));
+    assert_eq!(form(1, 2, 1).reduce(), Err(NtError::BadArgument));
+    assert_eq!(
+        form(1, 0, 1).compose(&form(1, 1, 1)),
+        Err(NtError::BadArgument)
+    );
+    assert_eq!(reduced_forms(-6), Err(NtError::BadArgument));
+    assert_eq!(class_number(9), Err(NtError::BadArgument));
+    // -T::MIN doesn't fit
+    assert_eq!(Form::principal(i64::MIN), Err(NtError::Overflow));
+    assert_eq!(reduced_forms(i64::MIN), Err(NtError::Overflow));
+    assert_eq!(class_number(i64::MIN), Err(NtError::Overflow));
+    assert_eq!(class_number(i8::MIN), Err(NtError::Overflow));
+    assert_eq!(Form::new(1, i64::MIN, 1).inverse(), Err(NtError::Overflow));
+    assert!(form(1, 1, 6).is_positive_definite());
+    assert!(!form(1, 5, 1).is_positive_definite());
+}
+
+// Generalized Cornacchia
+#[test]
+fn cornacchia_test() {
+    for d in 1i64..12 {
+        for m in 1i64..400 {
+            let mut sols = Vec::new();
+            let mut x = 0;
+            while x * x <= m {
+                let rest = m - x * x;
+                if rest % d == 0 && is_square(rest / d) {
+                    let y = isqrt(rest / d).unwrap();
+                    if gcd(x, y) == 1 {
+                        sols.push((x, y));
+                    }
+                }
+                x += 1;
+            }
+            assert_eq!(cornacchia(d, m), Ok(sols));
+        }
+    }
+    assert_eq!(cornacchia(1i64, 1), Ok(vec![(0, 1), (1, 0)]));
+    assert_eq!(cornacchia(5i64, 5), Ok(vec![(0, 1)]));
+    // p = x^2 + 27y^2 exactly when p = 1 (mod 3) and 2 is a cube mod p
+    assert_eq!(cornacchia(27i64, 31), Ok(vec![(2, 1)]));
+    assert_eq!(cornacchia(27i64, 37), Ok(vec![]));
+    assert_eq!(cornacchia(0, 5), Err(NtError::BadArgument));
+    assert_eq!(cornacchia(3, -7), Err(NtError::BadArgument));
+    let p = 1000000007i64;
+    for (x, y) in cornacchia(7, p).unwrap() {
+        assert_eq!(x * x + 7 * y * y, p);
+    }
+}
+
+// Polynomials mod p
+#[test]
+fn poly_test() {
+    // The three multiplications agree, including lopsided and Karatsuba sized operands
+    let p = 998244353i64;
+    let mut state = 12345i64;
+    let mut random_poly = |len: usize| {
+        let coeffs = (0..len)
+            .map(|_| {
+                state = (state * 1103515245 + 12345) % 2147483648;
+                state
+            })
+            .collect();
+        Poly::new(coeffs, p).unwrap()
+    };
+    for &(la, lb) in [
+        (0, 5),
+        (1, 1),
+        (7, 3),
+        (40, 33),
+        (100, 37),
+        (150, 150),
+        (33, 300),
+    ]
+    .iter()
+    {
+        let a = random_poly(la);
+        let b = random_poly(lb);
+        let expected = a.mul_schoolbook(&b);
+        assert_eq!(a.mul_karatsuba(&b), expected);
+        assert_eq!(a.mul_ntt(&b), Ok(expected.clone()));
+        assert_eq!(a.clone() * b.clone(), expected);
+    }
+    let small = |coeffs: &[i64]| Poly::new(coeffs.to_vec(), 7).unwrap();
+    assert_eq!(
+        small(&[1, 2]).mul_ntt(&small(&[1, 2, 3])),
+        Ok(small(&[1, 4, 0, 6]))
+    );
+    assert_eq!(small(&[1, 2]).mul_ntt(&small(&[3])), Ok(small(&[3, 6])));
+    // 1000000007 - 1 only has a single factor of 2 so this goes through three primes
+    let big = |coeffs: Vec<i64>| Poly::new(coeffs, 1000000007).unwrap();
+    let a = big((0..200).map(|i| 1000000006 - i * i).collect());
+    let b = big((0..77).map(|i| i * 12345678).collect());
+    assert_eq!(a.mul_ntt(&b), Ok(a.mul_schoolbook(&b)));
+
+    // Derivative, powers and composition mod 7
+    let f = small(&[0, 0, 1, 0, 0, 1, 0, 1]);
+    let modulus = small(&[3, 0, 1, 5]);
+    assert_eq!(f.derivative(), small(&[0, 2, 0, 0, 5]));
+    assert_eq!(small(&[0, 0, 0, 0, 0, 0, 0, 3]).derivative(), small(&[]));
+    assert_eq!(modulus.monic(), small(&[2, 0, 3, 1]));
+    let mut direct = small(&[1]);
+    for e in 0..20i64 {
+        assert_eq!(f.pow_mod(e, &modulus), Ok(direct.div_rem(&modulus).1));
+        direct = direct * f.clone();
+    }
+    let g = small(&[4, 1, 6]);
+    let mut composed = small(&[]);
+    for &c in f.coeffs().iter().rev() {
+        composed = composed * g.clone() + small(&[c]);
+    }
+    assert_eq!(
+        f.compose_mod(&g, &modulus),
+        Ok(composed.div_rem(&modulus).1)
+    );
+    assert_eq!(f.pow_mod(-1, &modulus), Err(NtError::BadArgument));
+    assert_eq!(f.compose_mod(&g, &small(&[])), Err(NtError::BadArgument));
+
+    // Factorizations of every monic polynomial of small degree mod 2, 3 and 5 multiply back and
+    // have factors with no roots or smaller factors
+    for &p in [2i64, 3, 5].iter() {
+        let poly = |coeffs: Vec<i64>| Poly::new(coeffs, p).unwrap();
+        let max_deg = if p == 5 { 4 } else { 6 };
+        let mut monics = vec![vec![vec![1]]];
+        for deg in 1..=max_deg {
+            let mut next = Vec::new();
+            for lower in monics[deg - 1].iter() {
+                for c in 0..p {
+                    let mut coeffs = vec![c];
+                    coeffs.extend(lower.iter());
+                    next.push(coeffs);
+                }
+            }
+            monics.push(next);
+        }
+        for deg in 1..=max_deg {
+            for coeffs in monics[deg].iter() {
+                let f = poly(coeffs.clone());
+                let (lead, factors) = poly::factor(&f).unwrap();
+                assert_eq!(lead, 1);
+                let mut prod = poly(vec![1]);
+                for (g, k) in factors.iter() {
+                    assert_eq!(g.leading_coeff(), 1);
+                    for _ in 0..*k {
+                        prod = prod * g.clone();
+                    }
+                    let g_deg = g.degree().unwrap();
+                    for h in monics[1..=g_deg / 2].iter().flatten() {
+                        assert!(!g.div_rem(&poly(h.clone())).1.is_zero());
+                    }
+                }
+                assert_eq!(prod, f);
+                let mut distinct = factors.clone();
+                distinct.dedup_by(|a, b| a.0 == b.0);
+                assert_eq!(distinct.len(), factors.len());
+
+                let roots: Vec<i64> = (0..p).filter(|&x| f.eval(x) == 0).collect();
+                assert_eq!(poly::roots(&f), Ok(roots));
+                let linear = factors
+                    .iter()
+                    .filter(|(g, _)| g.degree() == Some(1))
+                    .count();
+                assert_eq!(poly::roots(&f).unwrap().len(), linear);
+
+                let mut sq_free_prod = poly(vec![1]);
+                for (g, k) in poly::square_free_factorization(&f).unwrap() {
+                    assert_eq!(gcd(g.clone(), g.derivative()).degree(), Some(0));
+                    for _ in 0..k {
+                        sq_free_prod = sq_free_prod * g.clone();
+                    }
+                }
+                assert_eq!(sq_free_prod, f);
+            }
+        }
+    }
+
+    // Larger primes and degrees
+    let p = 1000000007i64;
+    let poly = |coeffs: &[i64]| Poly::new(coeffs.to_vec(), p).unwrap();
+    let f = (1..=12).fold(poly(&[3]), |acc, r| acc * poly(&[-r * r, 0, 1]));
+    let mut expected: Vec<i64> = (1..=12).flat_map(|r| vec![r, p - r]).collect();
+    expected.sort();
+    assert_eq!(poly::roots(&f), Ok(expected));
+    let quartic = poly(&[2, 0, 0, 0, 1]);
+    let cubic = poly(&[5, 1, 0, 1]);
+    let (lead, factors) =
+        poly::factor(&(quartic.clone() * quartic.clone() * cubic.clone())).unwrap();
+    assert_eq!(lead, 1);
+    for (g, k) in factors.iter() {
+        let g_deg = g.degree().unwrap();
+        assert!(g_deg == 1 || poly::roots(g).unwrap().is_empty());
+        let mult = if quartic.div_rem(g).1.is_zero() { 2 } else { 1 };
+        assert_eq!(*k, mult);
+    }
+    let degrees: usize = factors
+        .iter()
+        .map(|(g, k)| g.degree().unwrap() * *k as usize)
+        .sum();
+    assert_eq!(degrees, 11);
+    assert_eq!(poly::factor(&poly(&[])), Err(NtError::BadArgument));
+    assert_eq!(poly::roots(&poly(&[4])), Ok(vec![]));
+    assert_eq!(
+        poly::equal_degree_factorization(&poly(&[1, 0, 1]), 3),
+        Err(NtError::BadArgument)
+    );
+
+    // Input without distinct factors of degree d is rejected rather than split forever
+    let poly3 = |coeffs: &[i64]| Poly::new(coeffs.to_vec(), 3).unwrap();
+    for &(ref f, d) in [
+        (poly3(&[1, 0, 1]), 1),
+        (poly3(&[1, 2, 1]), 1),
+        // x^4 - 1 splits into linear factors mod 5
+        (Poly::new(vec![4, 0, 0, 0, 1], 5).unwrap(), 2),
+        (poly3(&[1, 0, 1]) * poly3(&[1, 0, 1]), 2),
+    ]
+    .iter()
+    {
+        assert_eq!(
+            poly::equal_degree_factorization(f, d),
+            Err(NtError::BadArgument)
+        );
+    }
+    // Coefficients narrower than the random numbers
+    let f = Poly::new(vec![-6i16, 11, -6, 1], 101).unwrap();
+    assert_eq!(poly::roots(&f), Ok(vec![1, 2, 3]));
+
+    // pow_mod agrees with the monoid methods on PolyMod
+    let modulus = poly3(&[2, 1, 0, 0, 1]);
+    let a = PolyMod::new(poly3(&[1, 2, 1]), modulus.clone()).unwrap();
+    let naf = PowerOptions {
+        method: PowerMethod::Naf,
+    };
+    for exp in 0..100i64 {
+        let expected = poly3(&[1, 2, 1]).pow_mod(exp, &modulus).unwrap();
+        assert_eq!(pow_monoid_with(&a, exp, &naf).unwrap().value(), &expected);
+    }
+    let other = PolyMod::new(poly3(&[1, 1]), poly3(&[1, 0, 1])).unwrap();
+    assert_eq!(a.op(&other), Err(NtError::BadArgument));
+    assert_eq!(
+        PolyMod::new(poly3(&[1]), poly3(&[])),
+        Err(NtError::BadArgument)
+    );
+}
+
+// Number theoretic transform
+#[test]
+fn ntt_test() {
+    // The smallest element of order p - 1
+    let order = |g: i64, p: i64| (1..p).find(|&k| power(g, k, p) == Ok(1)).unwrap();
+    for &p in [3i64, 7, 13, 17, 97, 7919].iter() {
+        let g = ntt::primitive_root(p).unwrap();
+        assert_eq!(order(g, p), p - 1);
+        assert!((1..g).all(|h| order(h, p) < p - 1));
+    }
+    assert_eq!(ntt::primitive_root(998244353i64), Ok(3));
+    assert_eq!(ntt::primitive_root(1000000007i64), Ok(5));
+    assert_eq!(ntt::primitive_root(2), Ok(1));
+    assert_eq!(ntt::primitive_root(1), Err(NtError::BadArgument));
+    assert_eq!(ntt::primitive_root(9), Err(NtError::BadArgument));
+
+    // Transforms against the definition
+    let p = 998244353i64;
+    let a: Vec<i64> = (0..16).map(|i| i * i * 31 - 7).collect();
+    let mut fa = a.clone();
+    ntt::ntt(&mut fa, p).unwrap();
+    let w = power(3, (p - 1) / 16, p).unwrap();
+    for (k, &val) in fa.iter().enumerate() {
+        let mut expected = 0;
+        for (j, &x) in a.iter().enumerate() {
+            expected = (expected + x.rem_euclid(p) * power(w, (j * k) as i64, p).unwrap()) % p;
+        }
+        assert_eq!(val, expected);
+    }
+    ntt::inverse_ntt(&mut fa, p).unwrap();
+    let a_reduced: Vec<i64> = a.iter().map(|x| x.rem_euclid(p)).collect();
+    assert_eq!(fa, a_reduced);
+    assert_eq!(ntt::ntt(&mut [1i64, 2, 3], p), Err(NtError::BadArgument));
+    assert_eq!(ntt::ntt(&mut [1i64; 8], 13), Err(NtError::BadArgument));
+    assert_eq!(ntt::ntt(&mut [1i64; 4], 13), Ok(()));
+
+    // Convolutions against the schoolbook sums
+    let naive = |a: &[i64], b: &[i64], m: i64| {
+        let mut c = vec![0i128; a.len() + b.len() - 1];
+        for (i, &x) in a.iter().enumerate() {
+            for (j, &y) in b.iter().enumerate() {
+                c[i + j] += x as i128 * y as i128;
+            }
+        }
+        c.iter()
+            .map(|&v| v.rem_euclid(m as i128) as i64)
+            .collect::<Vec<i64>>()
+    };
+    let a: Vec<i64> = (0..123)
+        .map(|i| (i * 7919 + 13) % 1000003 - 500000)
+        .collect();
+    let b: Vec<i64> = (0..45).map(|i| (i * 104729) % 999983).collect();
+    assert_eq!(ntt::convolution(&a, &b, p), Ok(naive(&a, &b, p)));
+    for &m in [2i64, 1000000007, (1 << 30) - 35, 999999999989].iter() {
+        if m < 1 << 31 {
+            assert_eq!(ntt::convolution_any_mod(&a, &b, m), Ok(naive(&a, &b, m)));
+        } else {
+            assert_eq!(ntt::convolution_any_mod(&a, &b, m), Err(NtError::Overflow));
+        }
+    }
+    let big: Vec<i64> = vec![(1 << 30) - 36; 1000];
+    assert_eq!(
+        ntt::convolution_any_mod(&big, &big, (1 << 30) - 35),
+        Ok(naive(&big, &big, (1 << 30) - 35))
+    );
+    assert_eq!(ntt::convolution(&a, &[], p), Ok(vec![]));
+    assert_eq!(
+        ntt::convolution(&a, &b, 1000000007),
+        Err(NtError::BadArgument)
+    );
+    assert_eq!(
+        ntt::convolution_any_mod(&a, &b, 0),
+        Err(NtError::BadArgument)
+    );
+
+    // Products modulo x^n - 1 and x^n + 1
+    let a: Vec<i64> = (0..32).map(|i| i * i + 1).collect();
+    let b: Vec<i64> = (0..32).map(|i| 3 * i + 2).collect();
+    let full = naive(&a, &b, p);
+    let cyclic: Vec<i64> = (0..32)
+        .map(|k| (full[k] + full.get(k + 32).unwrap_or(&0)) % p)
+        .collect();
+    let nega: Vec<i64> = (0..32)
+        .map(|k| (full[k] - full.get(k + 32).unwrap_or(&0)).rem_euclid(p))
+        .collect();
+    assert_eq!(ntt::cyclic_convolution(&a, &b, p), Ok(cyclic));
+    assert_eq!(ntt::negacyclic_convolution(&a, &b, p), Ok(nega));
+    assert_eq!(
+        ntt::cyclic_convolution(&a, &b[1..], p),
+        Err(NtError::BadArgument)
+    );
+    // 13 - 1 = 3 * 4 has roots of unity of order 4 but not 8
+    assert!(ntt::cyclic_convolution(&[1i64; 4], &[1; 4], 13).is_ok());
+    assert_eq!(
+        ntt::negacyclic_convolution(&[1i64; 4], &[1; 4], 13),
+        Err(NtError::BadArgument)
+    );
+}
+
+// Finite fields
+#[test]
+fn finite_field_test() {
+    // Rabin's test against full factorization and Gauss's count of monic irreducibles
+    for &(p, max_deg) in [(2i64, 8usize), (3, 5), (5, 3)].iter() {
+        for deg in 1..=max_deg {
+            let mut count = 0;
+            let total = (p as usize).pow(deg as u32);
+            for index in 0..total {
+                let mut coeffs: Vec<i64> = (0..deg)
+                    .map(|i| (index / (p as usize).pow(i as u32)) as i64 % p)
+                    .collect();
+                coeffs.push(1);
+                let f = Poly::new(coeffs, p).unwrap();
+                let irreducible = finite_field::is_irreducible(&f).unwrap();
+                if deg <= 4 {
+                    let (_, factors) = poly::factor(&f).unwrap();
+                    assert_eq!(irreducible, factors.len() == 1 && factors[0].1 == 1);
+                }
+                if irreducible {
+                    count += 1;
+                }
+            }
+            // n * (number of degree n irreducibles) = sum of mu(n / d) p^d over d dividing n
+            let mobius = |n: usize| match factor(n as i64).unwrap() {
+                ref f if f.iter().any(|&(_, e)| e > 1) => 0,
+                f => {
+                    if f.len() % 2 == 0 {
+                        1
+                    } else {
+                        -1
+                    }
+                }
+            };
+            let expected: i64 = (1..=deg)
+                .filter(|d| deg % d == 0)
+                .map(|d| mobius(deg / d) * p.pow(d as u32))
+                .sum();
+            assert_eq!(count * deg as i64, expected);
+        }
+    }
+    assert_eq!(
+        finite_field::is_irreducible(&Poly::new(vec![], 5i64).unwrap()),
+        Err(NtError::BadArgument)
+    );
+    assert_eq!(
+        finite_field::is_irreducible(&Poly::new(vec![3], 5i64).unwrap()),
+        Ok(false)
+    );
+
+    // Field axioms and the maps down to GF(p) on all of GF(3^3), GF(2^4) and GF(5^2)
+    for &(p, k) in [(3i64, 3u32), (2, 4), (5, 2)].iter() {
+        let field = GaloisField::new(p, k).unwrap();
+        let q = field.order();
+        assert_eq!(q, p.pow(k));
+        assert_eq!(field.characteristic(), p);
+        assert_eq!(field.degree(), k);
+        assert_eq!(finite_field::is_irreducible(field.modulus()), Ok(true));
+        let elements: Vec<_> = (0..q).map(|n| field.nth_element(n).unwrap()).collect();
+        assert_eq!(elements[0], field.zero());
+        assert_eq!(elements[1], field.one());
+        let prim = field.primitive_element().unwrap();
+        assert_eq!(prim.order(), Ok(q - 1));
+        assert!(prim.is_primitive().unwrap());
+        let mut primitive_count = 0;
+        let mut square_count = 0;
+        for a in elements.iter() {
+            assert_eq!(a.clone() + field.zero(), a.clone());
+            assert_eq!(a.clone() * field.one(), a.clone());
+            assert_eq!(a.clone() - a.clone(), field.zero());
+            assert_eq!(a.pow(q), Ok(a.clone()));
+            assert_eq!(a.frobenius(), a.pow(p).unwrap());
+            let t = a.trace();
+            let n = a.norm();
+            assert!(0 <= t && t < p && 0 <= n && n < p);
+            assert_eq!(field.constant(n), a.pow((q - 1) / (p - 1)).unwrap());
+            if a.is_zero() {
+                assert_eq!(a.inverse(), Err(NtError::NoSolns));
+                assert_eq!(a.order(), Err(NtError::BadArgument));
+            } else {
+                let inv = a.inverse().unwrap();
+                assert_eq!(a.clone() * inv.clone(), field.one());
+                assert_eq!(a.pow(-1), Ok(inv));
+                let order = a.order().unwrap();
+                assert_eq!((q - 1) % order, 0);
+                assert_eq!(a.pow(order), Ok(field.one()));
+                if a.is_primitive().unwrap() {
+                    primitive_count += 1;
+                }
+            }
+            if a.is_square().unwrap() {
+                square_count += 1;
+                let root = a.sqrt().unwrap();
+                assert_eq!(root.clone() * root, a.clone());
+            } else {
+                assert_eq!(a.sqrt(), Err(NtError::NoSolns));
+            }
+            for b in elements.iter().step_by(3) {
+                let sum = a.clone() + b.clone();
+                let prod = a.clone() * b.clone();
+                assert_eq!(sum.trace(), (a.trace() + b.trace()) % p);
+                assert_eq!(prod.norm(), a.norm() * b.norm() % p);
+                assert_eq!(sum.frobenius(), a.frobenius() + b.frobenius());
+                assert_eq!(prod.frobenius(), a.frobenius() * b.frobenius());
+                assert_eq!(prod, b.clone() * a.clone());
+            }
+        }
+        let phi = totient(q - 1).unwrap();
+        assert_eq!(primitive_count, phi);
+        let expected_squares = if p == 2 { q } else { (q + 1) / 2 };
+        assert_eq!(square_count, expected_squares);
+        // Frobenius has order k and fixes exactly GF(p)
+        let fixed = elements.iter().filter(|a| a.frobenius() == **a).count();
+        assert_eq!(fixed as i64, p);
+        let root = field.root();
+        assert_eq!(root.pow(q), Ok(root));
+    }
+
+    // The AES field and a larger one
+    let aes = GaloisField::new(2i64, 8).unwrap();
+    assert_eq!(aes.modulus().coeffs(), &[1, 1, 0, 1, 1, 0, 0, 0, 1]);
+    // {53} {CA} = {01} in the AES field
+    let a = aes.element(vec![1, 1, 0, 0, 1, 0, 1, 0]);
+    let b = aes.element(vec![0, 1, 0, 1, 0, 0, 1, 1]);
+    assert_eq!(a.clone() * b.clone(), aes.one());
+    assert_eq!(pow_monoid(&a, 255), Ok(aes.one()));
+    assert_eq!(a.op(&b), Ok(aes.one()));
+
+    let field = GaloisField::new(1000003i64, 3).unwrap();
+    let a = field.element(vec![5, 7, 11]);
+    let sq = a.clone() * a.clone();
+    let root = sq.sqrt().unwrap();
+    assert!(root == a || root == -a.clone());
+    assert_eq!(a.pow(field.order() - 1), Ok(field.one()));
+    let modulus = field.modulus().clone();
+    assert_eq!(GaloisField::with_modulus(modulus), Ok(field.clone()));
+    assert_eq!(
+        a.op(&GaloisField::new(2i64, 3).unwrap().one()),
+        Err(NtError::BadArgument)
+    );
+    let reducible = Poly::new(vec![1, 0, 1], 5i64).unwrap();
+    assert_eq!(
+        GaloisField::with_modulus(reducible),
+        Err(NtError::BadArgument)
+    );
+    assert_eq!(GaloisField::new(4i64, 2), Err(NtError::BadArgument));
+    assert_eq!(GaloisField::new(3i64, 0), Err(NtError::BadArgument));
+    assert_eq!(GaloisField::new(1000003i64, 4), Err(NtError::Overflow));
+    assert_eq!(field.nth_element(-1), Err(NtError::BadArgument));
+
+    // Random moduli are irreducible, repeat with the seed and vary without it
+    let moduli: Vec<_> = (0..20u64)
+        .map(|seed| {
+            GaloisField::random(3i64, 5, seed)
+                .unwrap()
+                .modulus()
+                .clone()
+        })
+        .collect();
+    assert!(moduli
+        .iter()
+        .all(|f| finite_field::is_irreducible(f) == Ok(true)));
+    assert!(moduli.iter().any(|f| f != &moduli[0]));
+    assert_eq!(
+        GaloisField::random(3i64, 5, 7).unwrap().modulus(),
+        &moduli[7]
+    );
+    let field = GaloisField::random(1000003i64, 3, 11).unwrap();
+    assert_eq!(field.order(), 1000003i64.pow(3));
+    let a = field.element(vec![2, 3, 5]);
+    assert_eq!(a.pow(field.order() - 1), Ok(field.one()));
+    assert_eq!(GaloisField::random(7i64, 1, 0).unwrap().order(), 7);
+    assert_eq!(GaloisField::random(4i64, 2, 0), Err(NtError::BadArgument));
+    assert_eq!(GaloisField::random(3i64, 0, 0), Err(NtError::BadArgument));
+    assert_eq!(
+        GaloisField::random(1000003i64, 4, 0),
+        Err(NtError::Overflow)
+    );
+}
+
+// Elliptic curves
+#[test]
+fn elliptic_test() {
+    // Every point of some small curves - the group laws in every coordinate system agree
+    for &(a, b, p) in [(2i64, 3i64, 97i64), (0, 7, 101), (-3, 5, 103), (1, 0, 23)].iter() {
+        let curve = WeierstrassCurve::new(a, b, p).unwrap();
+        let mut points = vec![Point::Infinity];
+        for x in 0..p {
+            for y in 0..p {
+                if (y * y - (x * x * x + a * x + b)).rem_euclid(p) == 0 {
+                    points.push(curve.point(x, y).unwrap());
+                }
+            }
+        }
+        let n = points.len() as i64;
+        // Hasse bound
+        assert!((n - p - 1).pow(2) <= 4 * p);
+        for (i, &pt) in points.iter().enumerate() {
+            assert!(curve.is_on_curve(&pt));
+            assert_eq!(curve.add(&pt, &curve.negate(&pt)), Ok(Point::Infinity));
+            assert_eq!(curve.scalar_mul(n, &pt), Ok(Point::Infinity));
+            let proj = curve.to_projective(&pt);
+            let jac = curve.to_jacobian(&pt);
+            assert_eq!(
+                curve.from_projective(&curve.double_projective(&proj)),
+                curve.double(&pt)
+            );
+            assert_eq!(
+                curve.from_jacobian(&curve.double_jacobian(&jac)),
+                curve.double(&pt)
+            );
+            for &q in points.iter().skip(i % 7).step_by(7) {
+                let sum = curve.add(&pt, &q).unwrap();
+                assert!(curve.is_on_curve(&sum));
+                assert_eq!(curve.add(&q, &pt), Ok(sum));
+                let proj_sum = curve.add_projective(&proj, &curve.to_projective(&q));
+                assert_eq!(curve.from_projective(&proj_sum), Ok(sum));
+                let jac_sum = curve.add_jacobian(&jac, &curve.to_jacobian(&q));
+                assert_eq!(curve.from_jacobian(&jac_sum), Ok(sum));
+                let r = points[(i * 5 + 3) % points.len()];
+                assert_eq!(
+                    curve.add(&sum, &r),
+                    curve.add(&pt, &curve.add(&q, &r).unwrap())
+                );
+            }
+            // Scalar multiplication by repeated addition
+            let mut multiple = Point::Infinity;
+            for k in 0..12i64 {
+                assert_eq!(curve.scalar_mul(k, &pt), Ok(multiple));
+                assert_eq!(curve.scalar_mul_ladder(k, &pt), Ok(multiple));
+                for width in 2..6 {
+                    assert_eq!(curve.scalar_mul_wnaf(k, &pt, width), Ok(multiple));
+                }
+                assert_eq!(curve.scalar_mul(-k, &pt), Ok(curve.negate(&multiple)));
+                multiple = curve.add(&multiple, &pt).unwrap();
+            }
+            // Compression round trips
+            if let Some((x, odd)) = curve.compress(&pt) {
+                assert_eq!(curve.decompress(x, odd), Ok(pt));
+            }
+        }
+        let xs_on_curve = points.iter().filter(|pt| !pt.is_infinity()).count();
+        let decompressible = (0..p)
+            .flat_map(|x| vec![(x, false), (x, true)])
+            .filter(|&(x, odd)| curve.decompress(x, odd).is_ok())
+            .count();
+        assert_eq!(decompressible, xs_on_curve);
+    }
+
+    // A larger a = -3 curve over the Mersenne prime 2^61 - 1
+    let p = (1i128 << 61) - 1;
+    let curve = WeierstrassCurve::new(-3, 2455155546008943817740293915197451784, p).unwrap();
+    let g = curve.decompress(5, false).unwrap();
+    let k = 1234567890123456789i128;
+    let expected = curve.scalar_mul(k, &g).unwrap();
+    assert!(curve.is_on_curve(&expected));
+    assert_eq!(curve.scalar_mul_ladder(k, &g), Ok(expected));
+    assert_eq!(curve.scalar_mul_wnaf(k, &g, 5), Ok(expected));
+    assert_eq!(
+        curve.scalar_mul_wnaf(-k, &g, 7),
+        Ok(curve.negate(&expected))
+    );
+    let (k1, k2) = (987654321987i128, 123456789123i128);
+    assert_eq!(
+        curve.add(
+            &curve.scalar_mul(k1, &g).unwrap(),
+            &curve.scalar_mul(k2, &g).unwrap()
+        ),
+        curve.scalar_mul(k1 + k2, &g)
+    );
+    assert_eq!(curve.scalar_mul_wnaf(k, &g, 1), Err(NtError::BadArgument));
+    assert_eq!(curve.scalar_mul_wnaf(k, &g, 17), Err(NtError::BadArgument));
+
+    // Extreme scalars - recoding i64::MAX must not overflow and |i64::MIN| doesn't fit
+    let curve = WeierstrassCurve::new(2i64, 3, 97).unwrap();
+    let pt = Point::Affine(3, 6);
+    let top = curve.scalar_mul(i64::MAX, &pt).unwrap();
+    assert_eq!(curve.scalar_mul_ladder(i64::MAX, &pt), Ok(top));
+    for width in 2..=16 {
+        assert_eq!(curve.scalar_mul_wnaf(i64::MAX, &pt, width), Ok(top));
+    }
+    assert_eq!(
+        curve.scalar_mul_wnaf(i64::MIN + 1, &pt, 4),
+        Ok(curve.negate(&top))
+    );
+    assert_eq!(curve.scalar_mul(i64::MIN, &pt), Err(NtError::Overflow));
+    assert_eq!(
+        curve.scalar_mul_ladder(i64::MIN, &pt),
+        Err(NtError::Overflow)
+    );
+    assert_eq!(
+        curve.scalar_mul_wnaf(i64::MIN, &pt, 4),
+        Err(NtError::Overflow)
+    );
+    // Windows wider than T
+    let narrow = WeierstrassCurve::new(2i16, 3, 97).unwrap();
+    let top = narrow.scalar_mul(i16::MAX, &Point::Affine(3, 6)).unwrap();
+    for width in [2, 15, 16].iter() {
+        assert_eq!(
+            narrow.scalar_mul_wnaf(i16::MAX, &Point::Affine(3, 6), *width),
+            Ok(top)
+        );
+    }
+    let montgomery = MontgomeryCurve::new(3i64, 1, 101).unwrap();
+    assert_eq!(
+        montgomery.scalar_mul(i64::MIN, &Point::Infinity),
+        Err(NtError::Overflow)
+    );
+
+    // Square roots for moduli whose square doesn't fit - 2^62 - 143 = 1 (mod 16) exercises the
+    // whole of Tonelli-Shanks and 2^61 - 1 = 3 (mod 4) the shortcut
+    for &p in [(1i64 << 61) - 1, (1i64 << 62) - 143].iter() {
+        let curve = WeierstrassCurve::new(1, 7, p).unwrap();
+        let montgomery = MontgomeryCurve::new(486662, 1, p).unwrap();
+        let mut found = 0;
+        for x in 0..40 {
+            for &odd in [false, true].iter() {
+                match curve.decompress(x, odd) {
+                    Ok(Point::Affine(px, y)) => {
+                        assert_eq!((px, y & 1 == 1), (x, odd));
+                        assert!(curve.is_on_curve(&Point::Affine(px, y)));
+                        found += 1;
+                    }
+                    res => assert_eq!(res, Err(NtError::NoSolns)),
+                }
+            }
+            match montgomery.lift_x(x) {
+                Ok(pt) => assert!(montgomery.is_on_curve(&pt)),
+                res => assert_eq!(res, Err(NtError::NoSolns)),
+            }
+        }
+        assert!(found > 20);
+    }
+    assert_eq!(
+        WeierstrassCurve::new(1i64, 7, (1 << 61) - 1)
+            .unwrap()
+            .decompress(3, false),
+        Err(NtError::NoSolns)
+    );
+
+    // Validation
+    let curve = WeierstrassCurve::new(2i64, 3, 97).unwrap();
+    assert_eq!(curve.point(3, 7), Err(NtError::BadArgument));
+    assert!(!curve.is_on_curve(&Point::Affine(3, 6 + 97)));
+    assert_eq!(
+        WeierstrassCurve::new(-3i64, 2, 97),
+        Err(NtError::BadArgument)
+    );
+    assert_eq!(WeierstrassCurve::new(1i64, 1, 3), Err(NtError::BadArgument));
+    assert_eq!(
+        WeierstrassCurve::new(1i64, 1, i64::MAX),
+        Err(NtError::Overflow)
+    );
+    // With a composite modulus the failed inversion surfaces
+    let composite = WeierstrassCurve::new(1i64, 1, 15).unwrap();
+    assert_eq!(
+        composite.add(&Point::Affine(0, 1), &Point::Affine(3, 1)),
+        Err(NtError::NoSolns)
+    );
+
+    // Montgomery curves against their Weierstrass forms
+    for &(a, b, p) in [(3i64, 1i64, 101i64), (6, 5, 103), (486662, 1, 1009)].iter() {
+        let curve = MontgomeryCurve::new(a, b, p).unwrap();
+        let weierstrass = curve.to_weierstrass().unwrap();
+        let mut points = vec![Point::Infinity];
+        for x in 0..p {
+            if let Ok(pt) = curve.lift_x(x) {
+                assert!(curve.is_on_curve(&pt));
+                points.push(pt);
+                points.push(curve.negate(&pt));
+            }
+        }
+        points.dedup();
+        for (i, pt) in points.iter().enumerate() {
+            let image = curve.to_weierstrass_point(pt).unwrap();
+            assert!(weierstrass.is_on_curve(&image));
+            let q = points[(i * 7 + 1) % points.len()];
+            let sum = curve.add(pt, &q).unwrap();
+            assert!(curve.is_on_curve(&sum));
+            assert_eq!(
+                curve.to_weierstrass_point(&sum),
+                weierstrass.add(&image, &curve.to_weierstrass_point(&q).unwrap())
+            );
+            // The ladder matches for points whose multiples avoid the 2-torsion point (0, 0)
+            let xz = curve.to_xz(pt);
+            for k in [0i64, 1, 2, 3, 10, 77, 1000].iter() {
+                let multiple = curve.scalar_mul(*k, pt).unwrap();
+                assert_eq!(
+                    curve.to_weierstrass_point(&multiple),
+                    weierstrass.scalar_mul(*k, &image)
+                );
+                if !matches!(*pt, Point::Affine(0, _) | Point::Infinity) {
+                    let x = match multiple {
+                        Point::Infinity => None,
+                        Point::Affine(x, _) => Some(x),
+                    };
+                    assert_eq!(curve.x_coordinate(&curve.ladder(*k, &xz).unwrap()), Ok(x));
+                }
+            }
+            assert_eq!(
+                curve.x_coordinate(&curve.x_double(&xz)),
+                curve.double(pt).map(|d| match d {
+                    Point::Infinity => None,
+                    Point::Affine(x, _) => Some(x),
+                })
+            );
+        }
+    }
+    assert_eq!(
+        MontgomeryCurve::new(2i64, 1, 101),
+        Err(NtError::BadArgument)
+    );
+    assert_eq!(
+        MontgomeryCurve::new(3i64, 0, 101),
+        Err(NtError::BadArgument)
+    );
+    assert_eq!(MontgomeryCurve::new(3i64, 1, 100), Err(NtError::NoSolns));
+    let curve = MontgomeryCurve::new(3i64, 1, 101).unwrap();
+    assert_eq!(
+        curve.ladder(-1, &curve.to_xz(&Point::Infinity)),
+        Err(NtError::BadArgument)
+    );
+
+    // Points as monoid elements work with every exponentiation method
+    let curve = WeierstrassCurve::new(2i64, 3, 97).unwrap();
+    let (p, q) = (Point::Affine(3, 6), Point::Affine(0, 10));
+    let (pe, qe) = (
+        WeierstrassElement::new(&curve, &p),
+        WeierstrassElement::new(&curve, &q),
+    );
+    for method in [
+        PowerMethod::Binary,
+        PowerMethod::FixedWindow(3),
+        PowerMethod::SlidingWindow(3),
+        PowerMethod::Naf,
+    ]
+    .iter()
+    {
+        let options = PowerOptions { method: *method };
+        for k in 0..40i64 {
+            let multiple = pow_monoid_with(&pe, k, &options).unwrap();
+            assert_eq!(multiple.point(), curve.scalar_mul(k, &p));
+        }
+    }
+    let combined = multi_pow_monoid(&[(pe, 11i64), (qe, 13)]).unwrap();
+    assert_eq!(
+        combined.point(),
+        curve.add(
+            &curve.scalar_mul(11, &p).unwrap(),
+            &curve.scalar_mul(13, &q).unwrap()
+        )
+    );
+    let other = WeierstrassElement::new(&WeierstrassCurve::new(0i64, 7, 101).unwrap(), &p);
+    assert_eq!(pe.op(&other), Err(NtError::BadArgument));
+    let curve = MontgomeryCurve::new(3i64, 1, 101).unwrap();
+    let p = curve.lift_x(2).unwrap();
+    let pe = MontgomeryElement::new(&curve, &p);
+    assert_eq!(
+        pow_monoid(&pe.inverse().unwrap(), 5).map(|m| m.point()),
+        curve.scalar_mul(-5, &p)
+    );
+}
+
+// ECM
+#[test]
+fn ecm_test() {
+    let sieved = primes_up_to(1000);
+    assert_eq!(sieved.len(), 168);
+    assert!(sieved.iter().all(|&p| is_prime(p as i64) == Ok(true)));
+    assert_eq!(primes_up_to(1), vec![]);
+    assert_eq!(primes_up_to(2), vec![2]);
+
+    let options = EcmOptions {
+        b1: 200,
+        b2: 10_000,
+        curves: 50,
+        sigma: 6,
+    };
+    for n in (9i64..3000).step_by(2) {
+        if is_prime(n) == Ok(true) {
+            assert_eq!(ecm(n, &options), Err(NtError::BadArgument));
+            continue;
+        }
+        if n == 25 {
+            // Suyama curves have order divisible by 12 so they're all singular mod 5
+            assert_eq!(ecm(n, &options), Err(NtError::NoSolns));
+            continue;
+        }
+        let d = ecm(n, &options).unwrap();
+        assert!(d > 1 && d < n && n % d == 0, "{} {}", n, d);
+    }
+    assert_eq!(ecm(1000i64, &options), Ok(2));
+    assert_eq!(ecm(1i64, &options), Err(NtError::BadArgument));
+    assert_eq!(
+        ecm(
+            15i64,
+            &EcmOptions {
+                sigma: 5,
+                ..options
+            }
+        ),
+        Err(NtError::BadArgument)
+    );
+    assert_eq!(
+        ecm(15i64, &EcmOptions { b2: 100, ..options }),
+        Err(NtError::BadArgument)
+    );
+    assert_eq!(
+        ecm(
+            15i64,
+            &EcmOptions {
+                curves: 0,
+                ..options
+            }
+        ),
+        Err(NtError::NoSolns)
+    );
+    assert_eq!(ecm(i64::MAX, &options), Err(NtError::Overflow));
+    // The documented 38 digit ceiling for i128
+    assert_eq!(ecm((1i128 << 126) + 1, &options), Err(NtError::Overflow));
+
+    // n^2 fits in i128 for the first n but not the second
+    let (p, q) = (1000000007i128, 998244353i128);
+    let d = ecm(p * q, &EcmOptions::default()).unwrap();
+    assert!(d == p || d == q);
+    let p = 2305843009213693951i128;
+    let q = 100000000003i128;
+    let d = ecm(p * q, &EcmOptions::default()).unwrap();
+    assert!(d == p || d == q);
+}
+
+// Pollard p - 1 and Williams p + 1
+#[test]
+fn p_minus_1_test() {
+    // 1000001801 - 1 = 2^3 * 5^2 * 7^2 * 67 * 1523 and 1000004807 + 1 = 2^3 * 3 * 11 * 19 * 73 * 2731
+    // but 1001000113 - 1 and 1001000113 + 1 both have a prime factor over 10^5
+    let (p, q, r) = (1000001801i128, 1000004807i128, 1001000113i128);
+    assert_eq!(pollard_p_minus_1(p * r, 100, 5000), Ok(p));
+    assert_eq!(pollard_p_minus_1(p * r, 100, 1000), Err(NtError::NoSolns));
+    assert_eq!(pollard_p_minus_1(q * r, 100, 5000), Err(NtError::NoSolns));
+    assert_eq!(williams_p_plus_1(q * r, 100, 5000), Ok(q));
+    assert_eq!(williams_p_plus_1(q * r, 100, 1000), Err(NtError::NoSolns));
+    // n^2 doesn't fit in i128
+    let big = 4000000000000000037i128;
+    assert_eq!(pollard_p_minus_1(p * big, 100, 5000), Ok(p));
+    assert_eq!(williams_p_plus_1(q * big, 100, 5000), Ok(q));
+
+    for n in (9i64..2000).step_by(2) {
+        if is_prime(n) == Ok(true) {
+            assert_eq!(pollard_p_minus_1(n, 10, 100), Err(NtError::BadArgument));
+            assert_eq!(williams_p_plus_1(n, 10, 100), Err(NtError::BadArgument));
+            continue;
+        }
+        for res in [pollard_p_minus_1(n, 10, 100), williams_p_plus_1(n, 10, 100)].iter() {
+            match *res {
+                Ok(d) => assert!(d > 1 && d < n && n % d == 0),
+                Err(e) => assert_eq!(e, NtError::NoSolns),
+            }
+        }
+    }
+    assert_eq!(pollard_p_minus_1(1000i64, 10, 100), Ok(2));
+    assert_eq!(williams_p_plus_1(1000i64, 10, 100), Ok(2));
+    assert_eq!(pollard_p_minus_1(15i64, 1, 100), Err(NtError::BadArgument));
+    assert_eq!(williams_p_plus_1(15i64, 100, 10), Err(NtError::BadArgument));
+    assert_eq!(pollard_p_minus_1(1i64, 10, 100), Err(NtError::BadArgument));
+    assert_eq!(williams_p_plus_1(i64::MAX, 10, 100), Err(NtError::Overflow));
 }
@@ -0,0 +1,96 @@
+use crate::utilities::nt_error;
+
+/**
+Bit level queries backed by the native intrinsics
+
+For signed primitives the counting operations look at the two's complement bits just as the
+inherent methods do.  The logarithms and `bit_length` are only defined for positive (respectively
+nonnegative) values and return BadArgument otherwise rather than panicking.
+*/
+pub trait BitOps: Copy {
+    /// Number of bits in the representation
+    const BITS: u32;
+
+    /// Number of leading zero bits
+    fn leading_zeros(self) -> u32;
+
+    /// Number of trailing zero bits - BITS for zero
+    fn trailing_zeros(self) -> u32;
+
+    /// Number of one bits
+    fn count_ones(self) -> u32;
+
+    /// true if the value is a positive power of two
+    fn is_power_of_two(self) -> bool;
+
+    /// Bits needed to represent a nonnegative value - zero for zero, BadArgument for negatives
+    fn bit_length(self) -> Result<u32, nt_error::NtError>;
+
+    /// Floor of the base 2 logarithm - BadArgument unless the value is positive
+    fn ilog2(self) -> Result<u32, nt_error::NtError>;
+
+    /// Floor of the base 10 logarithm - BadArgument unless the value is positive
+    fn ilog10(self) -> Result<u32, nt_error::NtError>;
+}
+
+macro_rules! bit_ops_impl {
+    ($t:ty, $u:ty) => {
+        impl BitOps for $t {
+            const BITS: u32 = <$t>::BITS;
+
+            #[inline]
+            fn leading_zeros(self) -> u32 {
+                <$t>::leading_zeros(self)
+            }
+
+            #[inline]
+            fn trailing_zeros(self) -> u32 {
+                <$t>::trailing_zeros(self)
+            }
+
+            #[inline]
+            fn count_ones(self) -> u32 {
+                <$t>::count_ones(self)
+            }
+
+            #[inline]
+            #[allow(unused_comparisons)]
+            fn is_power_of_two(self) -> bool {
+                self > 0 && (self as $u).is_power_of_two()
+            }
+
+            #[inline]
+            #[allow(unused_comparisons)]
+            fn bit_length(self) -> Result<u32, nt_error::NtError> {
+                if self < 0 {
+                    Err(nt_error::NtError::BadArgument)
+                } else {
+                    Ok(<$t>::BITS - <$t>::leading_zeros(self))
+                }
+            }
+
+            #[inline]
+            fn ilog2(self) -> Result<u32, nt_error::NtError> {
+                <$t>::checked_ilog2(self).ok_or(nt_error::NtError::BadArgument)
+            }
+
+            #[inline]
+            fn ilog10(self) -> Result<u32, nt_error::NtError> {
+                <$t>::checked_ilog10(self).ok_or(nt_error::NtError::BadArgument)
+            }
+        }
+    };
+}
+
+bit_ops_impl!(i8, u8);
+bit_ops_impl!(i16, u16);
+bit_ops_impl!(i32, u32);
+bit_ops_impl!(i64, u64);
+bit_ops_impl!(i128, u128);
+bit_ops_impl!(isize, usize);
+bit_ops_impl!(u8, u8);
+bit_ops_impl!(u16, u16);
+bit_ops_impl!(u32, u32);
+bit_ops_impl!(u64, u64);
+bit_ops_impl!(u128, u128);
+bit_ops_impl!(usize, usize);
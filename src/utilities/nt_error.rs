@@ -1,5 +1,8 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NtError {
     NoSolns,
     Overflow,
     BadArgument,
-}
\ No newline at end of file
+    // Index of the first element of a batch which has no inverse
+    NonInvertible(usize),
+}
@@ -1,4 +1,5 @@
-use num::{CheckedMul, FromPrimitive, Signed, ToPrimitive};
+use crate::utilities::bit_ops::BitOps;
+use num::{CheckedAdd, CheckedMul, CheckedSub, FromPrimitive, Signed, ToPrimitive};
 use std::ops::{Add, AddAssign, BitAnd, Div, Mul, MulAssign, Neg, Rem, Shl, Shr, Sub};
 
 pub trait Numeric:
@@ -16,11 +17,14 @@ pub trait Numeric:
     + FromPrimitive
     + ToPrimitive
     + CheckedMul
+    + CheckedAdd
+    + CheckedSub
     + Shl<usize, Output = Self>
     + Shr<usize, Output = Self>
     + BitAnd<Output = Self>
     + MulAssign
     + AddAssign
+    + BitOps
 {
 }
 
@@ -39,10 +43,13 @@ impl<T> Numeric for T where
         + FromPrimitive
         + ToPrimitive
         + CheckedMul
+        + CheckedAdd
+        + CheckedSub
         + Shl<usize, Output = T>
         + Shr<usize, Output = T>
         + BitAnd<Output = Self>
         + MulAssign
         + AddAssign
+        + BitOps
 {
 }
@@ -1,6 +1,15 @@
+use crate::utilities::bit_ops::BitOps;
+use crate::utilities::nt_error::NtError;
 use core::fmt;
+use std::convert::TryFrom;
+use std::iter::{Product, Sum};
 // CheckedMul, FromPrimitive, Signed, ToPrimitive
-use num::{Num, One, Signed, Unsigned, Zero};
+use num::traits::ops::overflowing::{OverflowingAdd, OverflowingMul, OverflowingSub};
+use num::traits::{
+    Bounded, CheckedAdd, CheckedDiv, CheckedMul, CheckedNeg, CheckedRem, CheckedSub, SaturatingAdd,
+    SaturatingMul, SaturatingSub, WrappingAdd, WrappingMul, WrappingNeg, WrappingSub,
+};
+use num::{FromPrimitive, Num, NumCast, One, Signed, ToPrimitive, Unsigned, Zero};
 use std::ops::{Add, AddAssign, BitAnd, Div, Mul, MulAssign, Neg, Rem, Shl, Shr, Sub};
 
 pub trait UTraits:
@@ -21,6 +30,14 @@ pub trait UTraits:
     + BitAnd<Output = Self>
     + Shl<usize, Output = Self>
     + Shr<usize, Output = Self>
+    + Bounded
+    + CheckedAdd
+    + CheckedMul
+    + WrappingAdd
+    + WrappingMul
+    + FromPrimitive
+    + ToPrimitive
+    + BitOps
 {
 }
 
@@ -42,6 +59,14 @@ impl<T> UTraits for T where
         + BitAnd<Output = Self>
         + Shl<usize, Output = Self>
         + Shr<usize, Output = Self>
+        + Bounded
+        + CheckedAdd
+        + CheckedMul
+        + WrappingAdd
+        + WrappingMul
+        + FromPrimitive
+        + ToPrimitive
+        + BitOps
 {
 }
 
@@ -87,6 +112,43 @@ where
             absval: mgn,
         }
     }
+
+    // Same as add_help but the magnitude wraps around on overflow rather than panicking.  The
+    // returned flag tells whether that happened.
+    #[inline]
+    fn overflowing_add_help(&self, other: &UToI<T>, other_neg: bool) -> (UToI<T>, bool) {
+        if self.is_neg != other_neg {
+            // Magnitudes of opposite signs subtract and can never overflow
+            return (self.add_help(other, other_neg), false);
+        }
+        let overflow = self.absval.checked_add(&other.absval).is_none();
+        let mgn = self.absval.wrapping_add(&other.absval);
+        let ret = UToI {
+            is_neg: self.is_neg && mgn != T::zero(),
+            absval: mgn,
+        };
+        (ret, overflow)
+    }
+
+    #[inline]
+    fn overflowing_mul_help(&self, other: &UToI<T>) -> (UToI<T>, bool) {
+        let overflow = self.absval.checked_mul(&other.absval).is_none();
+        let mgn = self.absval.wrapping_mul(&other.absval);
+        let ret = UToI {
+            is_neg: self.is_neg != other.is_neg && mgn != T::zero(),
+            absval: mgn,
+        };
+        (ret, overflow)
+    }
+
+    // Largest magnitude with the given sign - what the saturating operations clamp to
+    #[inline]
+    fn saturated(is_neg: bool) -> UToI<T> {
+        UToI {
+            is_neg,
+            absval: T::max_value(),
+        }
+    }
 }
 
 impl<T> fmt::Display for UToI<T>
@@ -98,6 +160,195 @@ where
     }
 }
 
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+// Conversions
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Spelled out per type rather than as a blanket From<T> so that it doesn't collide with the
+// TryFrom impls for the signed types below
+macro_rules! unsigned_conversions {
+    ($($u:ty),*) => {$(
+        impl From<$u> for UToI<$u> {
+            #[inline]
+            fn from(n: $u) -> Self {
+                UToI::new(n)
+            }
+        }
+    )*};
+}
+
+unsigned_conversions!(u8, u16, u32, u64, u128, usize);
+
+// Native signed types convert through their magnitude which may not fit in T
+macro_rules! signed_conversions {
+    ($($i:ty),*) => {$(
+        impl<T> TryFrom<$i> for UToI<T>
+        where
+            T: UTraits,
+        {
+            type Error = NtError;
+
+            #[inline]
+            fn try_from(n: $i) -> Result<Self, Self::Error> {
+                UToI::from_i128(n as i128).ok_or(NtError::Overflow)
+            }
+        }
+
+        impl<T> TryFrom<UToI<T>> for $i
+        where
+            T: UTraits,
+        {
+            type Error = NtError;
+
+            #[inline]
+            fn try_from(n: UToI<T>) -> Result<Self, Self::Error> {
+                n.to_i128()
+                    .and_then(|val| <$i>::try_from(val).ok())
+                    .ok_or(NtError::Overflow)
+            }
+        }
+    )*};
+}
+
+signed_conversions!(i8, i16, i32, i64, i128, isize);
+
+// Lossless widening of the underlying magnitude
+macro_rules! widening_conversions {
+    ($from:ty => $($to:ty),*) => {$(
+        impl From<UToI<$from>> for UToI<$to> {
+            #[inline]
+            fn from(n: UToI<$from>) -> Self {
+                UToI {
+                    is_neg: n.is_neg,
+                    absval: <$to as From<$from>>::from(n.absval),
+                }
+            }
+        }
+    )*};
+}
+
+widening_conversions!(u8 => u16, u32, u64, u128);
+widening_conversions!(u16 => u32, u64, u128);
+widening_conversions!(u32 => u64, u128);
+widening_conversions!(u64 => u128);
+
+impl<T> FromPrimitive for UToI<T>
+where
+    T: UTraits,
+{
+    #[inline]
+    fn from_i64(n: i64) -> Option<Self> {
+        UToI::from_i128(n as i128)
+    }
+
+    #[inline]
+    fn from_u64(n: u64) -> Option<Self> {
+        T::from_u64(n).map(UToI::new)
+    }
+
+    #[inline]
+    fn from_i128(n: i128) -> Option<Self> {
+        T::from_u128(n.unsigned_abs()).map(|absval| UToI {
+            is_neg: n < 0,
+            absval,
+        })
+    }
+
+    #[inline]
+    fn from_u128(n: u128) -> Option<Self> {
+        T::from_u128(n).map(UToI::new)
+    }
+}
+
+impl<T> ToPrimitive for UToI<T>
+where
+    T: UTraits,
+{
+    #[inline]
+    fn to_i64(&self) -> Option<i64> {
+        self.to_i128().and_then(|val| i64::try_from(val).ok())
+    }
+
+    #[inline]
+    fn to_u64(&self) -> Option<u64> {
+        if self.is_neg {
+            None
+        } else {
+            self.absval.to_u64()
+        }
+    }
+
+    #[inline]
+    fn to_i128(&self) -> Option<i128> {
+        let mgn = self.absval.to_u128()?;
+        if !self.is_neg {
+            i128::try_from(mgn).ok()
+        } else if mgn <= i128::MAX as u128 + 1 {
+            // Wrapping takes care of i128::MIN whose magnitude doesn't fit in an i128
+            Some((mgn as i128).wrapping_neg())
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn to_u128(&self) -> Option<u128> {
+        if self.is_neg {
+            None
+        } else {
+            self.absval.to_u128()
+        }
+    }
+}
+
+impl<T> NumCast for UToI<T>
+where
+    T: UTraits,
+{
+    fn from<N: ToPrimitive>(n: N) -> Option<Self> {
+        match n.to_i128() {
+            Some(val) => UToI::from_i128(val),
+            None => n.to_u128().and_then(UToI::from_u128),
+        }
+    }
+}
+
+impl<T> Sum for UToI<T>
+where
+    T: UTraits,
+{
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(UToI::zero(), |acc, val| acc + val)
+    }
+}
+
+impl<'a, T> Sum<&'a UToI<T>> for UToI<T>
+where
+    T: UTraits,
+{
+    fn sum<I: Iterator<Item = &'a UToI<T>>>(iter: I) -> Self {
+        iter.fold(UToI::zero(), |acc, val| acc + *val)
+    }
+}
+
+impl<T> Product for UToI<T>
+where
+    T: UTraits,
+{
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(UToI::one(), |acc, val| acc * val)
+    }
+}
+
+impl<'a, T> Product<&'a UToI<T>> for UToI<T>
+where
+    T: UTraits,
+{
+    fn product<I: Iterator<Item = &'a UToI<T>>>(iter: I) -> Self {
+        iter.fold(UToI::one(), |acc, val| acc * *val)
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
 // Arithmetic operations
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -214,6 +465,202 @@ where
     }
 }
 
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+// Checked, wrapping, saturating and overflowing arithmetic
+//
+// UToI is sign-magnitude so its range is symmetric: [-T::max_value(), T::max_value()].  Overflow
+// only ever happens in the magnitude.  The wrapping operations wrap the magnitude exactly the way
+// T wraps and keep the mathematically correct sign.
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+impl<T> CheckedAdd for UToI<T>
+where
+    T: UTraits,
+{
+    #[inline]
+    fn checked_add(&self, other: &UToI<T>) -> Option<UToI<T>> {
+        match self.overflowing_add_help(other, other.is_neg) {
+            (_, true) => None,
+            (val, false) => Some(val),
+        }
+    }
+}
+
+impl<T> CheckedSub for UToI<T>
+where
+    T: UTraits,
+{
+    #[inline]
+    fn checked_sub(&self, other: &UToI<T>) -> Option<UToI<T>> {
+        match self.overflowing_add_help(other, !other.is_neg) {
+            (_, true) => None,
+            (val, false) => Some(val),
+        }
+    }
+}
+
+impl<T> CheckedMul for UToI<T>
+where
+    T: UTraits,
+{
+    #[inline]
+    fn checked_mul(&self, other: &UToI<T>) -> Option<UToI<T>> {
+        match self.overflowing_mul_help(other) {
+            (_, true) => None,
+            (val, false) => Some(val),
+        }
+    }
+}
+
+impl<T> CheckedDiv for UToI<T>
+where
+    T: UTraits,
+{
+    #[inline]
+    fn checked_div(&self, other: &UToI<T>) -> Option<UToI<T>> {
+        if other.absval == T::zero() {
+            None
+        } else {
+            Some(*self / *other)
+        }
+    }
+}
+
+impl<T> CheckedRem for UToI<T>
+where
+    T: UTraits,
+{
+    #[inline]
+    fn checked_rem(&self, other: &UToI<T>) -> Option<UToI<T>> {
+        if other.absval == T::zero() {
+            None
+        } else {
+            Some(*self % *other)
+        }
+    }
+}
+
+impl<T> CheckedNeg for UToI<T>
+where
+    T: UTraits,
+{
+    // The range is symmetric so negation can't overflow
+    #[inline]
+    fn checked_neg(&self) -> Option<UToI<T>> {
+        Some(-*self)
+    }
+}
+
+impl<T> WrappingAdd for UToI<T>
+where
+    T: UTraits,
+{
+    #[inline]
+    fn wrapping_add(&self, other: &UToI<T>) -> UToI<T> {
+        self.overflowing_add_help(other, other.is_neg).0
+    }
+}
+
+impl<T> WrappingSub for UToI<T>
+where
+    T: UTraits,
+{
+    #[inline]
+    fn wrapping_sub(&self, other: &UToI<T>) -> UToI<T> {
+        self.overflowing_add_help(other, !other.is_neg).0
+    }
+}
+
+impl<T> WrappingMul for UToI<T>
+where
+    T: UTraits,
+{
+    #[inline]
+    fn wrapping_mul(&self, other: &UToI<T>) -> UToI<T> {
+        self.overflowing_mul_help(other).0
+    }
+}
+
+impl<T> WrappingNeg for UToI<T>
+where
+    T: UTraits,
+{
+    #[inline]
+    fn wrapping_neg(&self) -> UToI<T> {
+        -*self
+    }
+}
+
+impl<T> SaturatingAdd for UToI<T>
+where
+    T: UTraits,
+{
+    #[inline]
+    fn saturating_add(&self, other: &UToI<T>) -> UToI<T> {
+        match self.overflowing_add_help(other, other.is_neg) {
+            // Overflow only happens when both signs agree
+            (_, true) => UToI::saturated(self.is_neg),
+            (val, false) => val,
+        }
+    }
+}
+
+impl<T> SaturatingSub for UToI<T>
+where
+    T: UTraits,
+{
+    #[inline]
+    fn saturating_sub(&self, other: &UToI<T>) -> UToI<T> {
+        match self.overflowing_add_help(other, !other.is_neg) {
+            (_, true) => UToI::saturated(self.is_neg),
+            (val, false) => val,
+        }
+    }
+}
+
+impl<T> SaturatingMul for UToI<T>
+where
+    T: UTraits,
+{
+    #[inline]
+    fn saturating_mul(&self, other: &UToI<T>) -> UToI<T> {
+        match self.overflowing_mul_help(other) {
+            (_, true) => UToI::saturated(self.is_neg != other.is_neg),
+            (val, false) => val,
+        }
+    }
+}
+
+impl<T> OverflowingAdd for UToI<T>
+where
+    T: UTraits,
+{
+    #[inline]
+    fn overflowing_add(&self, other: &UToI<T>) -> (UToI<T>, bool) {
+        self.overflowing_add_help(other, other.is_neg)
+    }
+}
+
+impl<T> OverflowingSub for UToI<T>
+where
+    T: UTraits,
+{
+    #[inline]
+    fn overflowing_sub(&self, other: &UToI<T>) -> (UToI<T>, bool) {
+        self.overflowing_add_help(other, !other.is_neg)
+    }
+}
+
+impl<T> OverflowingMul for UToI<T>
+where
+    T: UTraits,
+{
+    #[inline]
+    fn overflowing_mul(&self, other: &UToI<T>) -> (UToI<T>, bool) {
+        self.overflowing_mul_help(other)
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
 // Logical operations
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -266,6 +713,61 @@ where
     }
 }
 
+// Bit queries look at the magnitude - there's no two's complement representation to report on
+impl<T> BitOps for UToI<T>
+where
+    T: UTraits,
+{
+    const BITS: u32 = T::BITS;
+
+    #[inline]
+    fn leading_zeros(self) -> u32 {
+        self.absval.leading_zeros()
+    }
+
+    #[inline]
+    fn trailing_zeros(self) -> u32 {
+        self.absval.trailing_zeros()
+    }
+
+    #[inline]
+    fn count_ones(self) -> u32 {
+        self.absval.count_ones()
+    }
+
+    #[inline]
+    fn is_power_of_two(self) -> bool {
+        !self.is_neg && self.absval.is_power_of_two()
+    }
+
+    #[inline]
+    fn bit_length(self) -> Result<u32, NtError> {
+        if self.is_neg {
+            Err(NtError::BadArgument)
+        } else {
+            self.absval.bit_length()
+        }
+    }
+
+    #[inline]
+    fn ilog2(self) -> Result<u32, NtError> {
+        if self.is_neg {
+            Err(NtError::BadArgument)
+        } else {
+            self.absval.ilog2()
+        }
+    }
+
+    #[inline]
+    fn ilog10(self) -> Result<u32, NtError> {
+        if self.is_neg {
+            Err(NtError::BadArgument)
+        } else {
+            self.absval.ilog10()
+        }
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
 // Comparison operations
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -327,6 +829,21 @@ where
     }
 }
 
+impl<T> Bounded for UToI<T>
+where
+    T: UTraits,
+{
+    #[inline]
+    fn min_value() -> Self {
+        UToI::saturated(true)
+    }
+
+    #[inline]
+    fn max_value() -> Self {
+        UToI::saturated(false)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FromStrRadixErrInt {
     kind: IntErrorKind,
@@ -350,7 +867,7 @@ where
 
     #[inline]
     fn from_str_radix(str: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
-        if str.len() == 0 {
+        if str.is_empty() {
             let err = FromStrRadixErrInt {
                 kind: IntErrorKind::Empty,
             };
@@ -445,3 +962,68 @@ fn test_u_to_i() {
     assert_eq!(o >> 1, UToI::new(3u32));
     assert_eq!(o << 1, UToI::new(12u32));
 }
+
+#[test]
+fn test_u_to_i_overflow() {
+    let big = UToI::new(200u8);
+    let small = UToI::new(100u8);
+    assert_eq!(big.checked_add(&small), None);
+    assert_eq!(big.checked_sub(&small), Some(small));
+    assert_eq!((-big).checked_sub(&small), None);
+    assert_eq!(small.checked_sub(&big), Some(-small));
+    assert_eq!(big.checked_mul(&UToI::new(2u8)), None);
+    assert_eq!(big.checked_div(&UToI::zero()), None);
+    assert_eq!(big.checked_rem(&UToI::zero()), None);
+
+    // Magnitude wraps as u8 does and the sign is kept
+    assert_eq!(big.wrapping_add(&small), UToI::new(44u8));
+    assert_eq!((-big).wrapping_sub(&small), -UToI::new(44u8));
+    assert_eq!(
+        big.overflowing_mul(&-UToI::new(2u8)),
+        (-UToI::new(144u8), true)
+    );
+    assert_eq!(big.overflowing_add(&-small), (small, false));
+
+    assert_eq!(big.saturating_add(&small), UToI::max_value());
+    assert_eq!((-big).saturating_sub(&small), UToI::min_value());
+    assert_eq!(big.saturating_mul(&-big), UToI::min_value());
+    assert_eq!(UToI::<u8>::min_value(), -UToI::new(255u8));
+}
+
+#[test]
+fn test_u_to_i_conversions() {
+    use crate::number_theory::euclidean::calc_euclidean_ext;
+    use std::convert::TryInto;
+
+    let m: UToI<u64> = (-1234i64).try_into().unwrap();
+    let n: UToI<u64> = 1234u64.into();
+    assert_eq!(m, -n);
+    assert_eq!(m, -UToI::new(1234u64));
+    assert_eq!(i64::try_from(m), Ok(-1234));
+    assert_eq!(i8::try_from(m), Err(NtError::Overflow));
+    assert_eq!(UToI::<u8>::try_from(-300i32), Err(NtError::Overflow));
+    assert_eq!(UToI::<u8>::try_from(-255i32), Ok(-UToI::new(255u8)));
+    let min: UToI<u128> = i128::MIN.try_into().unwrap();
+    assert_eq!(i128::try_from(min), Ok(i128::MIN));
+    assert_eq!((-min).to_u64(), None);
+
+    assert_eq!(
+        <UToI<u32> as NumCast>::from(-7.9f64),
+        Some(-UToI::new(7u32))
+    );
+    assert_eq!(<UToI<u32> as NumCast>::from(u64::MAX), None);
+    assert_eq!(UToI::<u64>::from_i32(-5).and_then(|v| v.to_i32()), Some(-5));
+    assert_eq!(m.to_u64(), None);
+
+    let wide: UToI<u128> = m.into();
+    assert_eq!(wide.to_i64(), Some(-1234));
+
+    let vals: Vec<UToI<u64>> = (1..=5).map(|i| UToI::new(i as u64)).collect();
+    assert_eq!(vals.iter().sum::<UToI<u64>>(), UToI::new(15u64));
+    assert_eq!(vals.into_iter().product::<UToI<u64>>(), UToI::new(120u64));
+
+    // UToI satisfies the crate's Numeric bound now
+    let (gcd, coeff1, coeff2) = calc_euclidean_ext(UToI::new(97u64), UToI::new(18u64));
+    assert_eq!(gcd, UToI::one());
+    assert_eq!(coeff1 * UToI::new(97u64) + coeff2 * UToI::new(18u64), gcd);
+}
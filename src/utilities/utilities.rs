@@ -1,13 +1,21 @@
+use crate::utilities::nt_error;
 use crate::utilities::numeric_trait::Numeric;
 use num::ToPrimitive;
 
-static LMOB_MAPPING: [usize; 16] = [0, 0, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 3, 3];
+/**
+Returns a number with only the leftmost one bit of n set
+
+# Arguments
+
+* `n` - value
 
+# Returns
+* 2^k where k is the index of the leftmost one bit in n or zero if n isn't positive
+*/
 pub fn top_bit_mask<T: Numeric>(n: T) -> T {
-    if n == T::zero() {
-        T::zero()
-    } else {
-        T::one() << leftmost_one_index(n)
+    match leftmost_one_index(n) {
+        Ok(idx) => T::one() << idx,
+        Err(_) => T::zero(),
     }
 }
 
@@ -20,24 +28,175 @@ Returns the index of the leftmost one bit in a number
 
 # Returns
 * Position of the leftmost one bit with the 2^0 digit being digit 0 and counting
-* left
+  left.  BadArgument if n isn't positive.
+*/
+pub fn leftmost_one_index<T: Numeric>(n: T) -> Result<usize, nt_error::NtError> {
+    Ok(n.ilog2()? as usize)
+}
+
+// x^k or None on overflow
+fn checked_pow<T: Numeric>(x: T, k: u32) -> Option<T> {
+    let mut ret = T::one();
+    for _ in 0..k {
+        ret = ret.checked_mul(&x)?;
+    }
+    Some(ret)
+}
+
+/**
+Returns the integer k-th root of a number
+
+# Arguments
+
+* `n` - value
+* `k` - root to take
+
+# Returns
+* The largest r with r^k <= n for nonnegative n.  For negative n and odd k this is -iroot(-n, k)
+  so the result is truncated towards zero.  BadArgument if k is zero or if n is negative and k is
+  even.
+*/
+pub fn iroot<T: Numeric>(n: T, k: u32) -> Result<T, nt_error::NtError> {
+    if k == 0 {
+        return Err(nt_error::NtError::BadArgument);
+    }
+    if n < T::zero() {
+        if k.is_multiple_of(2) {
+            return Err(nt_error::NtError::BadArgument);
+        }
+        // -n overflows for T::MIN so take the root of |n| - 1 and step up if |n| is a k-th power
+        let root = iroot(-(n + T::one()), k)?;
+        let next = -(root + T::one());
+        return Ok(match checked_pow(next, k) {
+            Some(val) if val >= n => next,
+            _ => -root,
+        });
+    }
+    if n < T::from_u8(2).unwrap() || k == 1 {
+        return Ok(n);
+    }
+
+    let top = leftmost_one_index(n)?;
+    let k_idx = k as usize;
+    if k_idx > top {
+        // n < 2^k so the root has to be 1
+        return Ok(T::one());
+    }
+
+    // Newton iteration starting from a power of two which is guaranteed to be above the root so
+    // that the iterates decrease monotonically until they pass the floor of the root
+    let k_t = T::from_u32(k).unwrap();
+    let k_minus_one = k_t - T::one();
+    let mut x = T::one() << (top / k_idx + 1);
+    loop {
+        let quot = match checked_pow(x, k - 1) {
+            Some(val) => n / val,
+            // x^(k-1) > n
+            None => T::zero(),
+        };
+        let next = (k_minus_one * x + quot) / k_t;
+        if next >= x {
+            return Ok(x);
+        }
+        x = next;
+    }
+}
+
+/**
+Returns the integer square root of a number
+
+# Arguments
+
+* `n` - value
+
+# Returns
+* The largest r with r * r <= n or BadArgument if n is negative
+*/
+pub fn isqrt<T: Numeric>(n: T) -> Result<T, nt_error::NtError> {
+    iroot(n, 2)
+}
+
+/**
+Returns the integer cube root of a number
+
+# Arguments
+
+* `n` - value
+
+# Returns
+* The cube root of n truncated towards zero
+*/
+pub fn icbrt<T: Numeric>(n: T) -> T {
+    // Odd roots are defined everywhere
+    iroot(n, 3).unwrap()
+}
+
+// Bit i is set if i is a square mod 64, 63, 65 and 11 respectively
+const SQUARES_MOD_64: u64 = 0x0202_0212_0203_0213;
+const SQUARES_MOD_63: u64 = 0x0402_4830_1245_0293;
+const SQUARES_MOD_65: u128 = 0x1_218a_0198_6601_4613;
+const SQUARES_MOD_11: u16 = 0x023b;
+
+/**
+Determines whether a number is a perfect square
+
+# Arguments
+
+* `n` - value
+
+# Returns
+* true if n is the square of an integer.  Most non-squares are rejected by quadratic residue
+  filters mod 64, 63, 65 and 11 before any root is taken.
 */
-pub fn leftmost_one_index<T: Numeric>(n: T) -> usize {
-    let mut bit_count = std::mem::size_of::<T>() * 8;
-    let mut ret = 0;
+pub fn is_square<T: Numeric>(n: T) -> bool {
+    if n < T::zero() {
+        return false;
+    }
+    let residue = |m: u32| ToPrimitive::to_u32(&(n % T::from_u32(m).unwrap())).unwrap();
+    if SQUARES_MOD_64 >> residue(64) & 1 == 0
+        || SQUARES_MOD_63 >> residue(63) & 1 == 0
+        || SQUARES_MOD_65 >> residue(65) & 1 == 0
+        || SQUARES_MOD_11 >> residue(11) & 1 == 0
+    {
+        return false;
+    }
+    let root = isqrt(n).unwrap();
+    root * root == n
+}
+
+/**
+Determines whether a number is a perfect power
 
+# Arguments
+
+* `n` - value
+
+# Returns
+* Some((base, exp)) with base^exp = n and exp >= 2 as large as possible or None if n isn't a
+  perfect power.  Negative numbers only have odd exponents.  0, 1 and -1 return None since their
+  exponent isn't well defined.
+*/
+pub fn perfect_power<T: Numeric>(n: T) -> Option<(T, u32)> {
     if n < T::zero() {
-        return bit_count;
-    }
-    let mut mut_n = n;
-    while bit_count > 4 {
-        bit_count = bit_count >> 1;
-        let shifted = mut_n >> bit_count;
-        if shifted != T::zero() {
-            ret += bit_count;
-            mut_n = shifted;
+        // Only odd exponents and -n overflows for T::MIN so search them directly
+        let bits = (-(n + T::one())).bit_length().ok()?;
+        for exp in (3..=bits).rev().filter(|exp| exp % 2 == 1) {
+            let base = iroot(n, exp).unwrap();
+            if base < -T::one() && checked_pow(base, exp) == Some(n) {
+                return Some((base, exp));
+            }
+        }
+        return None;
+    }
+    if n < T::from_u8(2).unwrap() {
+        return None;
+    }
+    let bits = n.bit_length().ok()?;
+    for exp in (2..=bits).rev() {
+        let base = iroot(n, exp).unwrap();
+        if base > T::one() && checked_pow(base, exp) == Some(n) {
+            return Some((base, exp));
         }
     }
-    let n = ToPrimitive::to_usize(&mut_n).unwrap();
-    ret + LMOB_MAPPING[n]
+    None
 }